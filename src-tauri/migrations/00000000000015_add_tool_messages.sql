@@ -0,0 +1,25 @@
+-- Add tool calling support to messages
+-- Tool calls requested by the assistant and the results returned for them are stored
+-- as their own message kinds so they participate in branches and history like any other message
+
+-- Allow the 'tool' role for tool result messages
+ALTER TABLE messages DROP CONSTRAINT IF EXISTS messages_role_check;
+ALTER TABLE messages ADD CONSTRAINT messages_role_check CHECK (role IN ('user', 'assistant', 'system', 'tool'));
+
+-- Message kind: plain text, assistant tool call, or tool result
+ALTER TABLE messages ADD COLUMN message_type VARCHAR(20) NOT NULL DEFAULT 'text'
+    CHECK (message_type IN ('text', 'tool_call', 'tool_result'));
+
+-- Tool calls requested by the assistant (array of {id, name, arguments})
+ALTER TABLE messages ADD COLUMN tool_calls JSONB;
+
+-- Identifier of the tool call a tool result answers
+ALTER TABLE messages ADD COLUMN tool_call_id VARCHAR(255);
+
+CREATE INDEX idx_messages_message_type ON messages(message_type);
+CREATE INDEX idx_messages_tool_call_id ON messages(tool_call_id);
+
+COMMENT ON COLUMN messages.role IS 'Message role: user, assistant, system, or tool';
+COMMENT ON COLUMN messages.message_type IS 'Message kind: text, tool_call (assistant requested tools), or tool_result (output of a tool call)';
+COMMENT ON COLUMN messages.tool_calls IS 'JSON array of tool calls requested by the assistant: [{id, name, arguments}]';
+COMMENT ON COLUMN messages.tool_call_id IS 'ID of the tool call this tool_result message answers';
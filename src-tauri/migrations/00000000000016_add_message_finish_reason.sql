@@ -0,0 +1,5 @@
+-- Record why generation of an assistant message stopped
+-- ("stop", "length", "tool_calls", ... from the provider, or "cancelled" when the user stopped it)
+ALTER TABLE messages ADD COLUMN finish_reason VARCHAR(50);
+
+COMMENT ON COLUMN messages.finish_reason IS 'Why generation stopped: provider finish reason, or cancelled when stopped by the user';
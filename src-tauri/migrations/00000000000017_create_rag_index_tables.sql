@@ -0,0 +1,45 @@
+-- Storage for the local RAG index: files ingested into a RAG database and their embedded chunks
+-- Embeddings are stored as REAL[] (the embedded PostgreSQL has no pgvector); similarity search
+-- runs in-process over vectors loaded from this table
+
+-- Files ingested into a RAG database and the state of their ingestion
+CREATE TABLE rag_database_files (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rag_database_id UUID NOT NULL REFERENCES rag_databases(id) ON DELETE CASCADE,
+    file_id UUID NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'completed', 'failed')),
+    chunk_count INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (rag_database_id, file_id)
+);
+
+CREATE INDEX idx_rag_database_files_database ON rag_database_files(rag_database_id);
+CREATE INDEX idx_rag_database_files_status ON rag_database_files(status);
+
+CREATE TRIGGER update_rag_database_files_updated_at
+    BEFORE UPDATE ON rag_database_files
+    FOR EACH ROW
+    EXECUTE FUNCTION update_rag_updated_at();
+
+-- Embedded chunks of text belonging to a RAG database
+-- file_id is NULL for chunks that did not come from an uploaded file (e.g. imported packages)
+CREATE TABLE rag_chunks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rag_database_id UUID NOT NULL REFERENCES rag_databases(id) ON DELETE CASCADE,
+    file_id UUID REFERENCES files(id) ON DELETE CASCADE,
+    chunk_index INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    embedding REAL[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_rag_chunks_database ON rag_chunks(rag_database_id);
+CREATE INDEX idx_rag_chunks_database_file ON rag_chunks(rag_database_id, file_id);
+
+COMMENT ON COLUMN rag_database_files.status IS 'Ingestion state: pending, processing, completed, or failed';
+COMMENT ON COLUMN rag_chunks.metadata IS 'Chunk metadata such as filename and character offsets in the source text';
+COMMENT ON COLUMN rag_chunks.embedding IS 'Embedding vector produced by the database embedding model';
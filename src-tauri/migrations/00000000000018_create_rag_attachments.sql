@@ -0,0 +1,18 @@
+-- Attach RAG databases to assistants and projects for retrieval at chat time
+
+CREATE TABLE IF NOT EXISTS assistant_rag_databases (
+    assistant_id UUID NOT NULL REFERENCES assistants(id) ON DELETE CASCADE,
+    rag_database_id UUID NOT NULL REFERENCES rag_databases(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (assistant_id, rag_database_id)
+);
+
+CREATE TABLE IF NOT EXISTS project_rag_databases (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    rag_database_id UUID NOT NULL REFERENCES rag_databases(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (project_id, rag_database_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_assistant_rag_databases_rag_database_id ON assistant_rag_databases(rag_database_id);
+CREATE INDEX IF NOT EXISTS idx_project_rag_databases_rag_database_id ON project_rag_databases(rag_database_id);
@@ -0,0 +1,20 @@
+-- Track RAG database package downloads in download_instances alongside model downloads
+
+ALTER TABLE download_instances
+    ADD COLUMN download_type VARCHAR(50) NOT NULL DEFAULT 'model'
+        CHECK (download_type IN ('model', 'rag_database'));
+
+ALTER TABLE download_instances
+    ADD COLUMN rag_database_id UUID REFERENCES rag_databases(id) ON DELETE SET NULL;
+
+-- provider_id and repository_id now reference providers/repositories for model downloads and
+-- rag_providers/rag_repositories for RAG database downloads. Download instances are cleared
+-- on startup, so they no longer need foreign keys to cascade from either pair of tables.
+ALTER TABLE download_instances DROP CONSTRAINT IF EXISTS download_instances_provider_id_fkey;
+ALTER TABLE download_instances DROP CONSTRAINT IF EXISTS download_instances_repository_id_fkey;
+
+CREATE INDEX IF NOT EXISTS idx_download_instances_download_type ON download_instances(download_type);
+CREATE INDEX IF NOT EXISTS idx_download_instances_rag_database_id ON download_instances(rag_database_id);
+
+COMMENT ON COLUMN download_instances.download_type IS 'model or rag_database; decides which tables provider_id and repository_id refer to';
+COMMENT ON COLUMN download_instances.rag_database_id IS 'References the imported RAG database after a successful rag_database download';
@@ -0,0 +1,6 @@
+-- Optional expiry, permission scope and last-used tracking for personal API keys
+-- A NULL scope grants the key all permissions of its owner
+
+ALTER TABLE user_api_keys ADD COLUMN IF NOT EXISTS scopes JSONB;
+ALTER TABLE user_api_keys ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE user_api_keys ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMP WITH TIME ZONE;
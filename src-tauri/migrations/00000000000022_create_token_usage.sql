@@ -0,0 +1,25 @@
+-- Token usage per provider request, kept separately from messages so usage and quotas
+-- survive deleted conversations and also cover gateway requests
+
+CREATE TABLE token_usage (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message_id UUID REFERENCES messages(id) ON DELETE SET NULL,
+    provider_id UUID REFERENCES providers(id) ON DELETE SET NULL,
+    model_id UUID REFERENCES models(id) ON DELETE SET NULL,
+    source VARCHAR(20) NOT NULL DEFAULT 'chat' CHECK (source IN ('chat', 'gateway')),
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    total_tokens INTEGER NOT NULL DEFAULT 0,
+    -- TRUE when the provider did not report usage and the counts were estimated locally
+    is_estimated BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_token_usage_user_created ON token_usage(user_id, created_at);
+CREATE INDEX idx_token_usage_message_id ON token_usage(message_id);
+CREATE INDEX idx_token_usage_provider_id ON token_usage(provider_id);
+CREATE INDEX idx_token_usage_model_id ON token_usage(model_id);
+
+-- Monthly token and request quotas of a group, NULL when unlimited
+ALTER TABLE user_groups ADD COLUMN IF NOT EXISTS quotas JSONB;
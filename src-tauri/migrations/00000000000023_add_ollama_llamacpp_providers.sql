@@ -0,0 +1,9 @@
+-- Native provider types for self-hosted Ollama and llama.cpp (llama-server) servers
+
+ALTER TABLE providers DROP CONSTRAINT IF EXISTS providers_provider_type_check;
+ALTER TABLE providers ADD CONSTRAINT providers_provider_type_check
+    CHECK (provider_type IN ('local', 'openai', 'anthropic', 'groq', 'gemini', 'mistral', 'ollama', 'llamacpp', 'custom'));
+
+INSERT INTO providers (name, provider_type, enabled, built_in, base_url) VALUES
+('Ollama', 'ollama', false, true, 'http://localhost:11434'),
+('llama.cpp', 'llamacpp', false, true, 'http://localhost:8080');
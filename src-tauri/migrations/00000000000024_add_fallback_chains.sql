@@ -0,0 +1,8 @@
+-- Ordered (provider, model) pairs tried when a model's provider fails with a retryable error
+
+ALTER TABLE models ADD COLUMN fallback_chain JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE assistants ADD COLUMN fallback_chain JSONB NOT NULL DEFAULT '[]';
+
+-- The provider and model that actually produced an assistant message
+ALTER TABLE messages ADD COLUMN provider_id UUID REFERENCES providers(id) ON DELETE SET NULL;
+ALTER TABLE messages ADD COLUMN model_id UUID REFERENCES models(id) ON DELETE SET NULL;
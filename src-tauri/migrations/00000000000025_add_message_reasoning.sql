@@ -0,0 +1,3 @@
+-- Reasoning a model produced before its answer, kept apart from the content so clients can collapse it
+
+ALTER TABLE messages ADD COLUMN reasoning TEXT;
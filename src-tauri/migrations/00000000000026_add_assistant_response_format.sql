@@ -0,0 +1,3 @@
+-- JSON Schema an assistant's answers must match
+
+ALTER TABLE assistants ADD COLUMN response_format JSONB;
@@ -0,0 +1,5 @@
+-- Usage recorded by the embeddings endpoint
+
+ALTER TABLE token_usage DROP CONSTRAINT IF EXISTS token_usage_source_check;
+ALTER TABLE token_usage ADD CONSTRAINT token_usage_source_check
+    CHECK (source IN ('chat', 'gateway', 'embeddings'));
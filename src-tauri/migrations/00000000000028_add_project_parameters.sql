@@ -0,0 +1,3 @@
+-- Inference parameters for every conversation in a project, applied over the assistant's
+
+ALTER TABLE projects ADD COLUMN parameters JSONB;
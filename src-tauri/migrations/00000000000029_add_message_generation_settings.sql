@@ -0,0 +1,4 @@
+-- Settings an assistant message was generated with, so it can be regenerated the same way
+
+ALTER TABLE messages ADD COLUMN parameters JSONB;
+ALTER TABLE messages ADD COLUMN system_prompt_override TEXT;
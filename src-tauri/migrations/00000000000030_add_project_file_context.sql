@@ -0,0 +1,8 @@
+-- How a project's files enter the context of its conversations: whole files until the token
+-- budget is spent, or the chunks most relevant to each message
+
+ALTER TABLE projects
+    ADD COLUMN file_context_mode TEXT NOT NULL DEFAULT 'always'
+        CHECK (file_context_mode IN ('always', 'retrieve')),
+    ADD COLUMN file_context_budget INTEGER NOT NULL DEFAULT 8000
+        CHECK (file_context_budget >= 0);
@@ -0,0 +1,6 @@
+-- Context length of a model in tokens, for models whose length is not set by their server
+-- settings, and how an assistant fits long conversations into it
+
+ALTER TABLE models ADD COLUMN context_length INTEGER CHECK (context_length > 0);
+
+ALTER TABLE assistants ADD COLUMN context_window JSONB;
@@ -0,0 +1,11 @@
+-- Full-text search over conversation titles and message content. The search vectors are
+-- generated columns, so they follow every insert and edit without triggers.
+
+ALTER TABLE conversations
+    ADD COLUMN title_tsv tsvector GENERATED ALWAYS AS (to_tsvector('english', title)) STORED;
+
+ALTER TABLE messages
+    ADD COLUMN content_tsv tsvector GENERATED ALWAYS AS (to_tsvector('english', content)) STORED;
+
+CREATE INDEX idx_conversations_title_tsv ON conversations USING GIN (title_tsv);
+CREATE INDEX idx_messages_content_tsv ON messages USING GIN (content_tsv);
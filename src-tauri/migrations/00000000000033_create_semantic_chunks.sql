@@ -0,0 +1,24 @@
+-- Embedded chunks of messages and extracted file text for semantic search over a user's
+-- conversations and files. Like rag_chunks, vectors are REAL[] and similarity is computed
+-- in-process. Chunks are kept per embedding model: after switching models, searches only see
+-- content already re-indexed with the new model, and chunks of the previous model are deleted
+-- once re-indexing is done.
+
+CREATE TABLE semantic_chunks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message_id UUID REFERENCES messages(id) ON DELETE CASCADE,
+    file_id UUID REFERENCES files(id) ON DELETE CASCADE,
+    embedding_model_id UUID NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    embedding REAL[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK ((message_id IS NULL) <> (file_id IS NULL))
+);
+
+CREATE INDEX idx_semantic_chunks_user_model ON semantic_chunks(user_id, embedding_model_id);
+CREATE INDEX idx_semantic_chunks_message ON semantic_chunks(message_id, embedding_model_id);
+CREATE INDEX idx_semantic_chunks_file ON semantic_chunks(file_id, embedding_model_id);
+
+COMMENT ON COLUMN semantic_chunks.embedding_model_id IS 'Model that produced the embedding; searches only compare vectors of the configured model';
@@ -0,0 +1,364 @@
+//! Fitting a conversation into the model's context window
+//!
+//! The leading system messages and the newest message are always sent. The history between
+//! them is split into turns, each starting at a user message, and the oldest turns are left
+//! out until the estimated prompt fits the context length less the tokens reserved for the
+//! reply. An assistant chooses how: drop the oldest turns, keep a sliding window of the newest
+//! turns, or replace the left out turns with a rolling summary written by a cheap model.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use uuid::Uuid;
+
+use crate::ai::core::ChatMessage;
+use crate::ai::usage::{estimate_message_tokens, estimate_prompt_tokens};
+use crate::database::models::{Model, ModelParameters};
+
+/// Turns kept by the sliding window when the assistant does not set a number
+pub const DEFAULT_WINDOW_TURNS: u32 = 10;
+/// Tokens kept free for the reply when the request does not set `max_tokens`
+pub const DEFAULT_REPLY_TOKENS: u32 = 1024;
+/// Longest rolling summary, also reserved in the prompt when one is added
+pub const SUMMARY_MAX_TOKENS: u32 = 512;
+/// Prefix of the `conversation_metadata` key caching a branch's rolling summary
+pub const SUMMARY_METADATA_KEY_PREFIX: &str = "context_summary:";
+
+/// How history that does not fit the context window is left out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextStrategy {
+    /// Drop the oldest turns until the prompt fits
+    #[default]
+    Drop,
+    /// Keep at most `window_turns` of the newest turns, fewer if they do not fit
+    SlidingWindow,
+    /// Replace the dropped turns with a summary that is extended as more turns are dropped
+    Summary,
+}
+
+/// How files attached to earlier user messages are sent with later requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentPolicy {
+    /// Attach the files again, so the model can look at them as when they were first sent
+    #[default]
+    Resend,
+    /// Replace each file with its name, type, size and the start of its extracted text
+    Summarize,
+}
+
+/// Context window settings of an assistant
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextWindowSettings {
+    #[serde(default)]
+    pub strategy: ContextStrategy,
+    /// Turns kept by the sliding window; also caps the turns kept before summarizing if set
+    #[serde(default)]
+    pub window_turns: Option<u32>,
+    /// Model that writes rolling summaries, the conversation's model if not set
+    #[serde(default)]
+    pub summary_model_id: Option<Uuid>,
+    /// How files attached to earlier user messages are sent
+    #[serde(default)]
+    pub attachments: AttachmentPolicy,
+    /// With `resend`, only the files of this many of the newest user messages are attached
+    /// again and older ones are summarized
+    #[serde(default)]
+    pub attachment_turns: Option<u32>,
+}
+
+impl ContextWindowSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_turns == Some(0) {
+            return Err("window_turns must be at least 1".to_string());
+        }
+        if self.summary_model_id.is_some() && self.strategy != ContextStrategy::Summary {
+            return Err("summary_model_id is only used by the summary strategy".to_string());
+        }
+        if self.attachment_turns == Some(0) {
+            return Err("attachment_turns must be at least 1".to_string());
+        }
+        if self.attachment_turns.is_some() && self.attachments != AttachmentPolicy::Resend {
+            return Err(
+                "attachment_turns is only used by the resend attachment policy".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// What was left out of a request to fit the context window, sent with the `complete` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextWindowReport {
+    pub strategy: ContextStrategy,
+    /// Context length of the model, in tokens
+    pub context_length: u32,
+    /// Estimated prompt tokens after trimming
+    pub estimated_prompt_tokens: u32,
+    /// History messages left out of the prompt
+    pub dropped_messages: usize,
+    /// Of the dropped messages, those covered by the rolling summary
+    pub summarized_messages: usize,
+}
+
+/// A rolling summary cached in `conversation_metadata`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSummary {
+    pub summary: String,
+    /// Number of history messages, from the start of the branch, the summary covers
+    pub covered_messages: usize,
+}
+
+/// The history of a request split around its pinned messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextLayout {
+    /// Leading system messages
+    pub system_count: usize,
+    /// History messages, between the system messages and the newest message
+    pub history: usize,
+}
+
+impl ContextLayout {
+    pub fn of(messages: &[ChatMessage]) -> Self {
+        let system_count = messages
+            .iter()
+            .take(messages.len().saturating_sub(1))
+            .take_while(|message| message.role == "system")
+            .count();
+        Self {
+            system_count,
+            history: messages.len().saturating_sub(system_count + 1),
+        }
+    }
+
+    fn history_range(&self) -> Range<usize> {
+        self.system_count..self.system_count + self.history
+    }
+}
+
+/// The context length a request is limited to: the per-request allocation when set, else
+/// what is known of the model. `None` when nothing is known, in which case nothing is trimmed.
+pub fn effective_context_length(model: &Model, parameters: &ModelParameters) -> Option<u32> {
+    parameters
+        .num_ctx
+        .or_else(|| {
+            model
+                .context_length
+                .and_then(|length| u32::try_from(length).ok())
+        })
+        .or_else(|| {
+            model
+                .settings
+                .as_ref()
+                .and_then(|settings| settings.max_seq_len)
+                .and_then(|length| u32::try_from(length).ok())
+        })
+        .filter(|length| *length > 0)
+}
+
+/// Tokens of the prompt budget: the context length less the reply reserve
+pub fn prompt_budget(context_length: u32, parameters: &ModelParameters) -> u32 {
+    let reply = parameters
+        .max_tokens
+        .unwrap_or(DEFAULT_REPLY_TOKENS)
+        .min(context_length / 2);
+    context_length - reply
+}
+
+/// Number of history messages to drop, always a whole number of turns, so the prompt fits
+/// `budget`. `extra_tokens` are added to the prompt when anything is dropped, such as room
+/// for a summary.
+pub fn history_to_drop(
+    messages: &[ChatMessage],
+    settings: &ContextWindowSettings,
+    budget: u32,
+    extra_tokens: u32,
+) -> usize {
+    let layout = ContextLayout::of(messages);
+    let history = &messages[layout.history_range()];
+    let turns = turn_starts(history);
+
+    let window = match settings.strategy {
+        ContextStrategy::SlidingWindow => {
+            Some(settings.window_turns.unwrap_or(DEFAULT_WINDOW_TURNS))
+        }
+        ContextStrategy::Summary => settings.window_turns,
+        ContextStrategy::Drop => None,
+    };
+    let mut dropped_turns = window
+        .map(|window| turns.len().saturating_sub(window as usize))
+        .unwrap_or(0);
+
+    let tokens = estimate_prompt_tokens(messages);
+    let history_tokens: Vec<u32> = history.iter().map(estimate_message_tokens).collect();
+    let dropped_tokens = |turn_count: usize| -> u32 {
+        let end = turns.get(turn_count).copied().unwrap_or(history.len());
+        history_tokens[..end].iter().sum()
+    };
+
+    loop {
+        let dropped = dropped_turns.min(turns.len());
+        let mut prompt = tokens.saturating_sub(dropped_tokens(dropped));
+        if dropped > 0 {
+            prompt += extra_tokens;
+        }
+        if prompt <= budget || dropped == turns.len() {
+            return turns.get(dropped).copied().unwrap_or(history.len());
+        }
+        dropped_turns = dropped + 1;
+    }
+}
+
+/// Index in the history where each turn starts. A turn starts at a user message; messages
+/// before the first user message belong to the first turn.
+fn turn_starts(history: &[ChatMessage]) -> Vec<usize> {
+    let mut starts: Vec<usize> = history
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| message.role == "user")
+        .map(|(index, _)| index)
+        .collect();
+    if !history.is_empty() && starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts
+}
+
+/// The first `count` history messages of a request
+pub fn leading_history(messages: &[ChatMessage], count: usize) -> &[ChatMessage] {
+    let layout = ContextLayout::of(messages);
+    &messages[layout.system_count..layout.system_count + count.min(layout.history)]
+}
+
+/// The request without its first `dropped` history messages, with the summary of them
+/// after the system messages when there is one
+pub fn trim_history(
+    mut messages: Vec<ChatMessage>,
+    dropped: usize,
+    summary: Option<&str>,
+) -> Vec<ChatMessage> {
+    let layout = ContextLayout::of(&messages);
+    let start = layout.system_count;
+    messages.drain(start..start + dropped.min(layout.history));
+
+    if let Some(summary) = summary.filter(|summary| !summary.trim().is_empty()) {
+        messages.insert(
+            start,
+            ChatMessage::text(
+                "system",
+                &format!(
+                    "Summary of the earlier part of this conversation:\n{}",
+                    summary.trim()
+                ),
+            ),
+        );
+    }
+    messages
+}
+
+/// Prompt asking for a summary of `messages`, extending `previous` when there is one
+pub fn summary_prompt(previous: Option<&str>, messages: &[ChatMessage]) -> String {
+    let transcript = messages
+        .iter()
+        .map(|message| format!("{}: {}", message.role, message.content.to_plain_text()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    match previous {
+        Some(previous) => format!(
+            "Here is a summary of the earlier part of a conversation:\n\n{}\n\nThe conversation \
+             continued as follows:\n\n{}\n\nUpdate the summary so it also covers the new \
+             messages. Keep facts, decisions, names and open questions. Respond with only the \
+             summary, in at most {} words.",
+            previous,
+            transcript,
+            SUMMARY_MAX_TOKENS / 2
+        ),
+        None => format!(
+            "Summarize the following conversation:\n\n{}\n\nKeep facts, decisions, names and \
+             open questions. Respond with only the summary, in at most {} words.",
+            transcript,
+            SUMMARY_MAX_TOKENS / 2
+        ),
+    }
+}
+
+/// `conversation_metadata` key of the rolling summary of a branch
+pub fn summary_metadata_key(branch_id: Uuid) -> String {
+    format!("{}{}", SUMMARY_METADATA_KEY_PREFIX, branch_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(turns: usize) -> Vec<ChatMessage> {
+        let mut messages = vec![ChatMessage::text("system", "You are helpful.")];
+        for turn in 0..turns {
+            messages.push(ChatMessage::text(
+                "user",
+                &format!("question {} {}", turn, "word ".repeat(40)),
+            ));
+            messages.push(ChatMessage::text(
+                "assistant",
+                &format!("answer {} {}", turn, "word ".repeat(40)),
+            ));
+        }
+        messages.push(ChatMessage::text("user", "latest question"));
+        messages
+    }
+
+    #[test]
+    fn test_drop_keeps_pinned_messages_and_whole_turns() {
+        let messages = conversation(6);
+        let settings = ContextWindowSettings::default();
+
+        assert_eq!(history_to_drop(&messages, &settings, u32::MAX, 0), 0);
+
+        let budget = estimate_prompt_tokens(&messages) - 1;
+        let dropped = history_to_drop(&messages, &settings, budget, 0);
+        assert_eq!(dropped, 2);
+
+        let trimmed = trim_history(messages.clone(), dropped, None);
+        assert_eq!(trimmed.len(), messages.len() - 2);
+        assert_eq!(trimmed[0].role, "system");
+        assert_eq!(
+            trimmed[1].content.to_plain_text(),
+            messages[3].content.to_plain_text()
+        );
+        assert_eq!(
+            trimmed.last().unwrap().content.to_plain_text(),
+            "latest question"
+        );
+
+        // Nothing left to drop once only the pinned messages remain
+        assert_eq!(history_to_drop(&messages, &settings, 0, 0), 12);
+    }
+
+    #[test]
+    fn test_sliding_window_and_summary() {
+        let messages = conversation(6);
+        let window = ContextWindowSettings {
+            strategy: ContextStrategy::SlidingWindow,
+            window_turns: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(history_to_drop(&messages, &window, u32::MAX, 0), 8);
+
+        let summary = ContextWindowSettings {
+            strategy: ContextStrategy::Summary,
+            ..Default::default()
+        };
+        let budget = estimate_prompt_tokens(&messages) - 1;
+        // Room for the summary makes a second turn go
+        assert_eq!(history_to_drop(&messages, &summary, budget, 100), 4);
+
+        let trimmed = trim_history(messages, 4, Some("They talked about questions."));
+        assert_eq!(trimmed[1].role, "system");
+        assert!(trimmed[1]
+            .content
+            .to_plain_text()
+            .ends_with("They talked about questions."));
+        assert_eq!(trimmed.len(), 14 - 4 + 1);
+    }
+}
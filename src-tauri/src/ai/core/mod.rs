@@ -6,6 +6,7 @@
 pub mod device_detection;
 pub mod provider_base;
 pub mod providers;
+pub mod think_tags;
 
 pub use provider_base::*;
 pub use providers::*;
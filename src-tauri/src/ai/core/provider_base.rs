@@ -1,5 +1,7 @@
 use super::providers::ProxyConfig;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use std::fmt;
 use std::time::Duration;
 
 /// Configuration options for HTTP client creation
@@ -112,3 +114,277 @@ pub fn build_http_client_with_config(
 
     Ok(client_builder.build()?)
 }
+
+/// An unsuccessful HTTP response from a provider API. The status is kept so callers can tell
+/// whether retrying, or falling back to another provider, may succeed.
+#[derive(Debug)]
+pub struct ProviderHttpError {
+    pub provider: String,
+    pub status: StatusCode,
+    /// Delay requested by the provider's `Retry-After` header
+    pub retry_after: Option<Duration>,
+    pub message: String,
+}
+
+impl ProviderHttpError {
+    pub async fn from_response(provider: &str, response: Response) -> Self {
+        let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
+        let message = response.text().await.unwrap_or_default();
+        Self {
+            provider: provider.to_string(),
+            status,
+            retry_after,
+            message,
+        }
+    }
+
+    /// Rate limits, timeouts and server errors are worth another attempt
+    pub fn is_retryable(&self) -> bool {
+        is_retryable_status(self.status)
+    }
+}
+
+impl fmt::Display for ProviderHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} API error ({}): {}", self.provider, self.status, self.message)
+    }
+}
+
+impl std::error::Error for ProviderHttpError {}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// Whether a provider call failed in a way another attempt or another provider may not:
+/// rate limits, server errors, timeouts and refused connections
+pub fn is_retryable_error(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<ProviderHttpError>() {
+        return error.is_retryable();
+    }
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return error.is_timeout()
+            || error.is_connect()
+            || error.status().is_some_and(is_retryable_status);
+    }
+    false
+}
+
+/// Read `Retry-After` as either delay seconds or an HTTP date
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// How often and how long to retry a provider request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts after the first one
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Longest `Retry-After` worth waiting for; beyond it the error is returned so a
+    /// fallback provider can take over
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff delay before retry number `retry` (starting at 0)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// Send a provider request with the default retry policy, returning a [`ProviderHttpError`]
+/// for unsuccessful responses
+pub async fn send_with_retry(
+    request: RequestBuilder,
+    provider: &str,
+) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    send_with_retry_policy(request, provider, &RetryPolicy::default()).await
+}
+
+/// Send a provider request, retrying rate limits, server errors, timeouts and refused
+/// connections with exponential backoff. A `Retry-After` header replaces the backoff delay.
+pub async fn send_with_retry_policy(
+    request: RequestBuilder,
+    provider: &str,
+    policy: &RetryPolicy,
+) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    // Streaming bodies cannot be cloned, so such requests get a single attempt
+    if request.try_clone().is_none() {
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(Box::new(ProviderHttpError::from_response(provider, response).await));
+        }
+        return Ok(response);
+    }
+
+    let mut retry = 0;
+    loop {
+        let attempt = request.try_clone().ok_or("Request body cannot be retried")?;
+        let can_retry = retry < policy.max_retries;
+
+        let delay = match attempt.send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let error = ProviderHttpError::from_response(provider, response).await;
+                if !can_retry || !error.is_retryable() {
+                    return Err(Box::new(error));
+                }
+                match error.retry_after {
+                    Some(retry_after) if retry_after > policy.max_retry_after => {
+                        return Err(Box::new(error));
+                    }
+                    Some(retry_after) => retry_after,
+                    None => policy.backoff(retry),
+                }
+            }
+            Err(error) => {
+                if !can_retry || !(error.is_timeout() || error.is_connect()) {
+                    return Err(Box::new(error));
+                }
+                policy.backoff(retry)
+            }
+        };
+
+        eprintln!(
+            "{} request failed, retrying in {} ms (retry {} of {})",
+            provider,
+            delay.as_millis(),
+            retry + 1,
+            policy.max_retries
+        );
+        tokio::time::sleep(delay).await;
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helpers::spawn_mock_server;
+    use axum::{http::HeaderValue, response::IntoResponse, routing::post, Router};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn quick_policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    /// A server that fails with `status` for the first `failures` requests
+    async fn flaky_server(status: StatusCode, failures: usize, retry_after: &'static str) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/chat",
+            post(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        let mut response = (status, "busy").into_response();
+                        response.headers_mut().insert(RETRY_AFTER, HeaderValue::from_static(retry_after));
+                        response
+                    } else {
+                        "ok".into_response()
+                    }
+                }
+            }),
+        );
+        (spawn_mock_server(router).await, calls)
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let (base_url, calls) = flaky_server(StatusCode::SERVICE_UNAVAILABLE, 2, "0").await;
+
+        let response = send_with_retry_policy(Client::new().post(format!("{}/chat", base_url)), "test", &quick_policy())
+            .await
+            .unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let (base_url, calls) = flaky_server(StatusCode::TOO_MANY_REQUESTS, 10, "0").await;
+
+        let error = send_with_retry_policy(Client::new().post(format!("{}/chat", base_url)), "test", &quick_policy())
+            .await
+            .unwrap_err();
+
+        assert!(is_retryable_error(error.as_ref()));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_is_not_waited_for() {
+        let (base_url, calls) = flaky_server(StatusCode::TOO_MANY_REQUESTS, 1, "3600").await;
+
+        let error = send_with_retry_policy(Client::new().post(format!("{}/chat", base_url)), "test", &quick_policy())
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<ProviderHttpError>().unwrap();
+        assert_eq!(error.retry_after, Some(Duration::from_secs(3600)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let (base_url, calls) = flaky_server(StatusCode::BAD_REQUEST, 1, "0").await;
+
+        let error = send_with_retry_policy(Client::new().post(format!("{}/chat", base_url)), "test", &quick_policy())
+            .await
+            .unwrap_err();
+
+        assert!(!is_retryable_error(error.as_ref()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(10), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_parse_retry_after_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(120)));
+    }
+}
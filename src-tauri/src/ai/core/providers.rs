@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use futures_util::Stream;
+use serde::Serialize;
 use std::pin::Pin;
 use uuid::Uuid;
 
@@ -11,7 +12,11 @@ pub use crate::database::models::chat::{
     ContentPart, 
     FileReference, 
     MessageContent, 
+    ResponseFormat,
     StreamingChunk, 
+    ToolCall,
+    ToolChoice,
+    ToolDefinition,
     Usage,
 };
 
@@ -28,6 +33,28 @@ pub struct ProxyConfig {
     pub ignore_ssl_certificates: bool,
 }
 
+/// A model reported by a provider's model-listing endpoint
+#[derive(Debug, Clone)]
+pub struct DiscoveredModel {
+    /// Model id used in API requests
+    pub name: String,
+    /// Human-readable name, when the vendor reports one
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    /// Capabilities, only when the vendor reports them
+    pub capabilities: Option<crate::database::models::ModelCapabilities>,
+}
+
+/// A model a self-hosted server currently holds in memory
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedModel {
+    pub name: String,
+    /// Memory the model occupies on the GPU, in bytes
+    pub size_vram: Option<u64>,
+    /// When the server will unload the model if it stays idle
+    pub expires_at: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum ProviderFileContent {
     ProviderFileId(String),        // Use provider's uploaded file ID
@@ -56,6 +83,30 @@ pub trait AIProvider: Send + Sync {
         true
     }
 
+    /// Indicates whether this provider translates `ChatRequest.tools` into native function calling
+    fn supports_tools(&self) -> bool {
+        false
+    }
+
+    /// List the chat models the vendor currently offers
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        Err("Model discovery not supported by this provider".into())
+    }
+
+    /// Report the models the server has loaded, for servers that load models on demand
+    async fn loaded_models(&self) -> Result<Vec<LoadedModel>, Box<dyn std::error::Error + Send + Sync>> {
+        Err("Load state not supported by this provider".into())
+    }
+
+    /// Embed a batch of texts with the given model, returning one vector per input in order
+    async fn embed(
+        &self,
+        _model_name: &str,
+        _texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        Err("Embeddings not supported by this provider".into())
+    }
+
     /// File management capabilities
     fn supports_file_upload(&self) -> bool { 
         false 
@@ -0,0 +1,163 @@
+//! Separation of inline `<think>` blocks from streamed model output
+//!
+//! Open-weight reasoning models served without a reasoning parser emit their chain of
+//! thought at the start of the content, wrapped in `<think>...</think>`. The splitter
+//! routes that block to the reasoning channel so clients never see the raw tags.
+
+const OPEN_TAG: &str = "<think>";
+const CLOSE_TAG: &str = "</think>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitState {
+    /// Waiting to see whether the output opens with a think block
+    Start,
+    /// Inside the think block
+    Thinking,
+    /// Just after the think block, dropping the whitespace that separates it from the answer
+    AfterThinking,
+    /// Regular content
+    Content,
+}
+
+/// Incremental splitter for a single streamed response. Tags may arrive split across chunks.
+#[derive(Debug)]
+pub struct ThinkTagSplitter {
+    state: SplitState,
+    pending: String,
+}
+
+impl Default for ThinkTagSplitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThinkTagSplitter {
+    pub fn new() -> Self {
+        Self {
+            state: SplitState::Start,
+            pending: String::new(),
+        }
+    }
+
+    /// Feed the next piece of content, returning the `(content, reasoning)` that can be emitted now
+    pub fn push(&mut self, text: &str) -> (String, String) {
+        let mut content = String::new();
+        let mut reasoning = String::new();
+        self.pending.push_str(text);
+
+        loop {
+            match self.state {
+                SplitState::Start => {
+                    let trimmed = self.pending.trim_start();
+                    if let Some(rest) = trimmed.strip_prefix(OPEN_TAG) {
+                        self.pending = rest.to_string();
+                        self.state = SplitState::Thinking;
+                    } else if OPEN_TAG.starts_with(trimmed) {
+                        // Could still become the opening tag
+                        break;
+                    } else {
+                        content.push_str(&self.pending);
+                        self.pending.clear();
+                        self.state = SplitState::Content;
+                    }
+                }
+                SplitState::Thinking => {
+                    if let Some(index) = self.pending.find(CLOSE_TAG) {
+                        reasoning.push_str(&self.pending[..index]);
+                        self.pending = self.pending[index + CLOSE_TAG.len()..].to_string();
+                        self.state = SplitState::AfterThinking;
+                    } else {
+                        // Hold back a trailing partial closing tag
+                        let keep = partial_suffix_len(&self.pending, CLOSE_TAG);
+                        let emit = self.pending.len() - keep;
+                        reasoning.push_str(&self.pending[..emit]);
+                        self.pending.drain(..emit);
+                        break;
+                    }
+                }
+                SplitState::AfterThinking => {
+                    let trimmed = self.pending.trim_start();
+                    if !trimmed.is_empty() {
+                        content.push_str(trimmed);
+                        self.state = SplitState::Content;
+                    }
+                    self.pending.clear();
+                    break;
+                }
+                SplitState::Content => {
+                    content.push_str(&self.pending);
+                    self.pending.clear();
+                    break;
+                }
+            }
+        }
+
+        (content, reasoning)
+    }
+
+    /// Flush whatever is still held back once the stream has ended
+    pub fn finish(&mut self) -> (String, String) {
+        let pending = std::mem::take(&mut self.pending);
+        match self.state {
+            SplitState::Start | SplitState::Content => (pending, String::new()),
+            SplitState::Thinking => (String::new(), pending),
+            SplitState::AfterThinking => (String::new(), String::new()),
+        }
+    }
+}
+
+/// Length of the longest suffix of `text` that is a proper prefix of `tag`
+fn partial_suffix_len(text: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&len| text.ends_with(&tag[..len]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_all(chunks: &[&str]) -> (String, String) {
+        let mut splitter = ThinkTagSplitter::new();
+        let (mut content, mut reasoning) = (String::new(), String::new());
+        for chunk in chunks {
+            let (c, r) = splitter.push(chunk);
+            content.push_str(&c);
+            reasoning.push_str(&r);
+        }
+        let (c, r) = splitter.finish();
+        content.push_str(&c);
+        reasoning.push_str(&r);
+        (content, reasoning)
+    }
+
+    #[test]
+    fn test_splits_leading_think_block() {
+        let (content, reasoning) = split_all(&["<think>Let me check.</think>\n\nThe answer is 4."]);
+        assert_eq!(reasoning, "Let me check.");
+        assert_eq!(content, "The answer is 4.");
+    }
+
+    #[test]
+    fn test_tags_split_across_chunks() {
+        let (content, reasoning) = split_all(&["\n<thi", "nk>2 + 2", " is 4</th", "ink>", "\n", "Four."]);
+        assert_eq!(reasoning, "2 + 2 is 4");
+        assert_eq!(content, "Four.");
+    }
+
+    #[test]
+    fn test_plain_content_passes_through() {
+        let (content, reasoning) = split_all(&["<", "b>Bold</b> and <think> later"]);
+        assert_eq!(reasoning, "");
+        assert_eq!(content, "<b>Bold</b> and <think> later");
+    }
+
+    #[test]
+    fn test_unterminated_block_is_reasoning() {
+        let (content, reasoning) = split_all(&["<think>Still going </thi"]);
+        assert_eq!(reasoning, "Still going </thi");
+        assert_eq!(content, "");
+    }
+}
@@ -4,11 +4,15 @@
 //! Groq, Gemini, Mistral, and Custom providers with support for streaming responses and proxy configurations.
 //! It also includes local ML inference capabilities using the Candle framework.
 
+pub mod context_window;
 pub mod core;
 pub mod file_helpers;
 pub mod model_manager;
 pub mod models;
+pub mod parameters;
 pub mod providers;
+pub mod structured_output;
+pub mod usage;
 
 // Define local types that were previously from local_server
 #[derive(Debug, Clone)]
@@ -31,8 +35,8 @@ pub enum ModelSelected {
 pub use core::device_detection;
 // Re-export commonly used items for convenience
 pub use core::{
-  AIProvider, ChatMessage, ChatRequest, ChatResponse, ContentPart, FileReference, MessageContent, ProviderFileContent, ProxyConfig,
-  StreamingChunk, StreamingResponse, Usage, build_http_client,
+  AIProvider, ChatMessage, ChatRequest, ChatResponse, ContentPart, DiscoveredModel, FileReference, LoadedModel, MessageContent, ProviderFileContent, ProxyConfig,
+  ResponseFormat, StreamingChunk, StreamingResponse, ToolCall, ToolChoice, ToolDefinition, Usage, build_http_client,
 };
 pub use model_manager::{
   check_and_cleanup_model, is_model_running, start_model, stop_model, ModelStartParams,
@@ -1435,6 +1435,9 @@ pub async fn cleanup_dead_processes() -> Result<(), Box<dyn std::error::Error +
             }
         }
     }
+
+    // Local RAG databases hold their index in memory instead of a process
+    crate::rag::manager::cleanup_rag_indexes().await?;
     
     Ok(())
 }
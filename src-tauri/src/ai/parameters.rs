@@ -0,0 +1,266 @@
+//! Resolution of inference parameters
+//!
+//! Parameters are set on the model and overridden field by field by the assistant, the
+//! project and the request. Provider APIs name the fields differently and each supports a
+//! different subset, so the resolved set is narrowed to what the provider honours here and
+//! mapped to its native names when the request body is built.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::database::models::ModelParameters;
+
+/// Fields of the OpenAI chat completions API, also accepted by servers that copy it
+const OPENAI_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "max_tokens"),
+    ("temperature", "temperature"),
+    ("top_p", "top_p"),
+    ("presence_penalty", "presence_penalty"),
+    ("frequency_penalty", "frequency_penalty"),
+    ("seed", "seed"),
+    ("stop", "stop"),
+];
+
+const DEEPSEEK_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "max_tokens"),
+    ("temperature", "temperature"),
+    ("top_p", "top_p"),
+    ("presence_penalty", "presence_penalty"),
+    ("frequency_penalty", "frequency_penalty"),
+    ("stop", "stop"),
+];
+
+const MISTRAL_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "max_tokens"),
+    ("temperature", "temperature"),
+    ("top_p", "top_p"),
+    ("presence_penalty", "presence_penalty"),
+    ("frequency_penalty", "frequency_penalty"),
+    ("seed", "random_seed"),
+    ("stop", "stop"),
+];
+
+const LLAMACPP_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "max_tokens"),
+    ("temperature", "temperature"),
+    ("top_k", "top_k"),
+    ("top_p", "top_p"),
+    ("min_p", "min_p"),
+    ("repeat_last_n", "repeat_last_n"),
+    ("repeat_penalty", "repeat_penalty"),
+    ("presence_penalty", "presence_penalty"),
+    ("frequency_penalty", "frequency_penalty"),
+    ("seed", "seed"),
+    ("stop", "stop"),
+];
+
+// mistralrs-server
+const LOCAL_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "max_tokens"),
+    ("temperature", "temperature"),
+    ("top_k", "top_k"),
+    ("top_p", "top_p"),
+    ("min_p", "min_p"),
+    ("repeat_penalty", "repetition_penalty"),
+    ("presence_penalty", "presence_penalty"),
+    ("frequency_penalty", "frequency_penalty"),
+    ("seed", "seed"),
+    ("stop", "stop"),
+];
+
+// Sent inside `options`
+const OLLAMA_FIELDS: &[(&str, &str)] = &[
+    ("num_ctx", "num_ctx"),
+    ("max_tokens", "num_predict"),
+    ("temperature", "temperature"),
+    ("top_k", "top_k"),
+    ("top_p", "top_p"),
+    ("min_p", "min_p"),
+    ("repeat_last_n", "repeat_last_n"),
+    ("repeat_penalty", "repeat_penalty"),
+    ("presence_penalty", "presence_penalty"),
+    ("frequency_penalty", "frequency_penalty"),
+    ("seed", "seed"),
+    ("stop", "stop"),
+];
+
+const ANTHROPIC_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "max_tokens"),
+    ("temperature", "temperature"),
+    ("top_k", "top_k"),
+    ("top_p", "top_p"),
+    ("stop", "stop_sequences"),
+];
+
+// Sent inside `generationConfig`
+const GEMINI_FIELDS: &[(&str, &str)] = &[
+    ("max_tokens", "maxOutputTokens"),
+    ("temperature", "temperature"),
+    ("top_k", "topK"),
+    ("top_p", "topP"),
+    ("presence_penalty", "presencePenalty"),
+    ("frequency_penalty", "frequencyPenalty"),
+    ("seed", "seed"),
+    ("stop", "stopSequences"),
+];
+
+/// Reasoning settings, which providers translate into their own request structures
+const THINKING_FIELDS: &[&str] = &["enable_thinking", "thinking_budget"];
+
+/// Parameter fields a provider type accepts, named as in `ModelParameters`, with the name
+/// its API uses for them
+pub fn native_field_names(provider_type: &str) -> &'static [(&'static str, &'static str)] {
+    match provider_type {
+        "openai" | "groq" | "custom" => OPENAI_FIELDS,
+        "deepseek" => DEEPSEEK_FIELDS,
+        "mistral" => MISTRAL_FIELDS,
+        "llamacpp" => LLAMACPP_FIELDS,
+        "local" => LOCAL_FIELDS,
+        "ollama" => OLLAMA_FIELDS,
+        "anthropic" => ANTHROPIC_FIELDS,
+        "gemini" => GEMINI_FIELDS,
+        _ => &[],
+    }
+}
+
+fn supports_thinking(provider_type: &str) -> bool {
+    matches!(
+        provider_type,
+        "anthropic" | "gemini" | "ollama" | "llamacpp" | "local"
+    )
+}
+
+/// Parameters after resolution for one provider
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResolvedParameters {
+    /// The parameters sent to the provider
+    pub parameters: ModelParameters,
+    /// Fields that were set but are not supported by the provider, and were left out
+    pub unsupported: Vec<String>,
+}
+
+/// Merge parameter layers, lowest priority first, and keep the fields the provider supports.
+/// Dropped fields are logged so a setting that has no effect does not go unnoticed.
+pub fn resolve_parameters(
+    layers: &[Option<&ModelParameters>],
+    provider_type: &str,
+) -> ResolvedParameters {
+    let merged = layers
+        .iter()
+        .flatten()
+        .fold(ModelParameters::default(), |merged, layer| merged.merged_with(layer));
+
+    let Value::Object(fields) = serde_json::to_value(&merged).unwrap_or_default() else {
+        return ResolvedParameters::default();
+    };
+
+    let supported = native_field_names(provider_type);
+    let mut kept = Map::new();
+    let mut unsupported = Vec::new();
+    for (field, value) in fields {
+        if value.is_null() {
+            continue;
+        }
+        let is_supported = supported.iter().any(|(name, _)| *name == field)
+            || (THINKING_FIELDS.contains(&field.as_str()) && supports_thinking(provider_type));
+        if is_supported {
+            kept.insert(field, value);
+        } else {
+            unsupported.push(field);
+        }
+    }
+
+    if !unsupported.is_empty() {
+        eprintln!(
+            "Parameters not supported by {} provider were not sent: {}",
+            provider_type,
+            unsupported.join(", ")
+        );
+    }
+
+    ResolvedParameters {
+        parameters: serde_json::from_value(Value::Object(kept)).unwrap_or_default(),
+        unsupported,
+    }
+}
+
+/// The set fields of `params` under the provider's native names. Reasoning settings are
+/// not included, as they do not map onto a single field.
+pub fn native_parameters(params: &ModelParameters, provider_type: &str) -> Map<String, Value> {
+    let Value::Object(fields) = serde_json::to_value(params).unwrap_or_default() else {
+        return Map::new();
+    };
+
+    native_field_names(provider_type)
+        .iter()
+        .filter_map(|(field, native)| {
+            fields
+                .get(*field)
+                .filter(|value| !value.is_null())
+                .map(|value| (native.to_string(), value.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_later_layers_override_per_field() {
+        let model = ModelParameters {
+            temperature: Some(0.2),
+            top_k: Some(40),
+            ..Default::default()
+        };
+        let assistant = ModelParameters {
+            temperature: Some(0.9),
+            ..Default::default()
+        };
+        let request = ModelParameters {
+            seed: Some(7),
+            ..Default::default()
+        };
+
+        let resolved = resolve_parameters(&[Some(&model), Some(&assistant), None, Some(&request)], "ollama");
+        assert_eq!(resolved.parameters.temperature, Some(0.9));
+        assert_eq!(resolved.parameters.top_k, Some(40));
+        assert_eq!(resolved.parameters.seed, Some(7));
+        assert!(resolved.unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_fields_are_dropped() {
+        let params = ModelParameters {
+            temperature: Some(0.5),
+            top_k: Some(40),
+            min_p: Some(0.05),
+            ..Default::default()
+        };
+
+        let resolved = resolve_parameters(&[Some(&params)], "openai");
+        assert_eq!(resolved.parameters.temperature, Some(0.5));
+        assert_eq!(resolved.parameters.top_k, None);
+        let mut unsupported = resolved.unsupported;
+        unsupported.sort();
+        assert_eq!(unsupported, vec!["min_p", "top_k"]);
+    }
+
+    #[test]
+    fn test_native_names() {
+        let params = ModelParameters {
+            max_tokens: Some(256),
+            seed: Some(1),
+            stop: Some(vec!["END".to_string()]),
+            ..Default::default()
+        };
+
+        let gemini = native_parameters(&params, "gemini");
+        assert_eq!(gemini["maxOutputTokens"], 256);
+        assert_eq!(gemini["stopSequences"][0], "END");
+
+        let mistral = native_parameters(&params, "mistral");
+        assert_eq!(mistral["random_seed"], 1);
+        assert!(!mistral.contains_key("seed"));
+    }
+}
@@ -5,18 +5,26 @@ use futures_util::StreamExt;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
-use crate::ai::core::provider_base::build_http_client;
+use crate::ai::core::provider_base::{build_http_client, send_with_retry};
 use crate::ai::core::providers::{
-    AIProvider, ChatRequest, ChatResponse, ContentPart, FileReference, MessageContent,
-    ProviderFileContent, ProxyConfig, StreamingChunk, StreamingResponse, Usage,
+    AIProvider, ChatRequest, ChatResponse, ContentPart, DiscoveredModel, FileReference,
+    MessageContent, ProviderFileContent, ProxyConfig, StreamingChunk, StreamingResponse, ToolCall,
+    ToolChoice, Usage,
 };
 use crate::ai::file_helpers::{add_provider_mapping_to_file_ref, load_file_content};
+use crate::ai::parameters::native_parameters;
 use crate::database::queries::files::{create_provider_file_mapping, get_provider_file_mapping};
 use crate::utils::file_storage::extract_extension;
 use crate::FILE_STORAGE;
 
+/// Smallest reasoning budget the API accepts
+const MIN_THINKING_BUDGET: u32 = 1024;
+/// Reasoning budget when thinking is enabled without one
+const DEFAULT_THINKING_BUDGET: u32 = 4096;
+
 #[derive(Debug, Clone)]
 pub struct AnthropicProvider {
     client: Client,
@@ -37,6 +45,12 @@ struct AnthropicContent {
     #[serde(rename = "type")]
     content_type: String,
     text: Option<String>,
+    // thinking blocks
+    thinking: Option<String>,
+    // tool_use blocks
+    id: Option<String>,
+    name: Option<String>,
+    input: Option<Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,20 +59,85 @@ struct AnthropicUsage {
     output_tokens: u32,
 }
 
+#[derive(Debug, Deserialize)]
+struct AnthropicModelList {
+    data: Vec<AnthropicModel>,
+    #[serde(default)]
+    has_more: bool,
+    last_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicModel {
+    id: String,
+    display_name: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct AnthropicStreamResponse {
     #[serde(rename = "type")]
     event_type: String,
+    index: Option<usize>,
     delta: Option<AnthropicDelta>,
     content_block: Option<AnthropicContent>,
+    // message_start carries the prompt usage, message_delta the output usage
+    message: Option<AnthropicStreamMessage>,
+    usage: Option<AnthropicStreamUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicStreamMessage {
+    usage: Option<AnthropicStreamUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicStreamUsage {
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
 struct AnthropicDelta {
+    // message_delta events carry no type, only stop_reason
     #[serde(rename = "type")]
-    delta_type: String,
+    delta_type: Option<String>,
     text: Option<String>,
+    thinking: Option<String>,
+    partial_json: Option<String>,
+    stop_reason: Option<String>,
+}
+
+/// Streaming state shared across network reads: open tool_use blocks, the final stop reason
+/// and the token usage reported so far
+#[derive(Debug, Default)]
+struct AnthropicStreamState {
+    tool_blocks: BTreeMap<usize, (String, String, String)>, // index -> (id, name, partial json)
+    // Tool carrying a structured answer, and the index of its block once started
+    format_tool: Option<String>,
+    format_block: Option<usize>,
     stop_reason: Option<String>,
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+}
+
+impl AnthropicStreamState {
+    fn record_usage(&mut self, usage: Option<AnthropicStreamUsage>) {
+        if let Some(usage) = usage {
+            self.input_tokens = usage.input_tokens.or(self.input_tokens);
+            self.output_tokens = usage.output_tokens.or(self.output_tokens);
+        }
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        if self.input_tokens.is_none() && self.output_tokens.is_none() {
+            return None;
+        }
+        Some(Usage {
+            prompt_tokens: self.input_tokens,
+            completion_tokens: self.output_tokens,
+            total_tokens: Some(self.input_tokens.unwrap_or(0) + self.output_tokens.unwrap_or(0)),
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -97,6 +176,41 @@ impl AnthropicProvider {
         &self,
         msg: &crate::ai::core::providers::ChatMessage,
     ) -> Result<(Option<String>, Option<Value>), Box<dyn std::error::Error + Send + Sync>> {
+        // Tool results are sent back as user messages containing a tool_result block
+        if let Some(tool_call_id) = &msg.tool_call_id {
+            let message = json!({
+                "role": "user",
+                "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": tool_call_id,
+                    "content": msg.content.to_plain_text()
+                }]
+            });
+            return Ok((None, Some(message)));
+        }
+
+        // Assistant tool calls become tool_use blocks after any text
+        if let Some(tool_calls) = msg.tool_calls.as_ref().filter(|calls| !calls.is_empty()) {
+            let mut content_array = Vec::new();
+            let text = msg.content.to_plain_text();
+            if !text.trim().is_empty() {
+                content_array.push(json!({ "type": "text", "text": text }));
+            }
+            for call in tool_calls {
+                content_array.push(json!({
+                    "type": "tool_use",
+                    "id": call.id,
+                    "name": call.name,
+                    "input": call.arguments
+                }));
+            }
+            let message = json!({
+                "role": "assistant",
+                "content": content_array
+            });
+            return Ok((None, Some(message)));
+        }
+
         match &msg.content {
             MessageContent::Text(text) => {
                 if msg.role == "system" {
@@ -357,6 +471,26 @@ impl AnthropicProvider {
         Ok(upload_response.id)
     }
 
+    /// Reasoning budget for the request, or None when thinking is off. Thinking is also left
+    /// off when answering tool results, as the API then wants the signed thinking blocks of
+    /// the previous turn back and those are not stored.
+    fn thinking_budget(request: &ChatRequest) -> Option<u32> {
+        let parameters = request.parameters.as_ref().filter(|p| p.thinking_enabled())?;
+        if request.messages.last().is_some_and(|msg| msg.tool_call_id.is_some()) {
+            return None;
+        }
+        // Forcing the structured-output tool is not allowed together with thinking
+        if request.response_format.is_some() {
+            return None;
+        }
+        Some(
+            parameters
+                .thinking_budget
+                .unwrap_or(DEFAULT_THINKING_BUDGET)
+                .max(MIN_THINKING_BUDGET),
+        )
+    }
+
     async fn prepare_request(
         &self,
         request: &ChatRequest,
@@ -400,19 +534,68 @@ impl AnthropicProvider {
             body["system"] = json!(system_message);
         }
 
-        if let Some(parameters) = &request.parameters {
-            if let Some(temperature) = parameters.temperature {
-                body["temperature"] = json!(temperature);
+        let thinking_budget = Self::thinking_budget(request);
+        if let Some(budget_tokens) = thinking_budget {
+            body["thinking"] = json!({ "type": "enabled", "budget_tokens": budget_tokens });
+            // max_tokens includes the reasoning, so it has to leave room for the answer
+            let max_tokens = body["max_tokens"].as_u64().unwrap_or(4096) as u32;
+            if max_tokens <= budget_tokens {
+                body["max_tokens"] = json!(budget_tokens + max_tokens);
             }
-            if let Some(top_p) = parameters.top_p {
-                body["top_p"] = json!(top_p);
+        }
+
+        if let Some(parameters) = &request.parameters {
+            for (name, value) in native_parameters(parameters, "anthropic") {
+                // max_tokens is always set above, and thinking only works with the default
+                // sampling settings
+                let sampling = matches!(name.as_str(), "temperature" | "top_p" | "top_k");
+                if name == "max_tokens" || (sampling && thinking_budget.is_some()) {
+                    continue;
+                }
+                body[name] = value;
             }
-            // Note: Anthropic doesn't support seed parameter, but we can add stop sequences
-            if let Some(stop) = &parameters.stop {
-                body["stop_sequences"] = json!(stop);
+        }
+
+        if let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) {
+            body["tools"] = json!(tools
+                .iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description.clone().unwrap_or_default(),
+                    "input_schema": tool.parameters
+                }))
+                .collect::<Vec<_>>());
+
+            // Anthropic has no "none" choice; omitting tool_choice leaves it on auto
+            match &request.tool_choice {
+                Some(ToolChoice::Auto) => body["tool_choice"] = json!({ "type": "auto" }),
+                Some(ToolChoice::Required) => body["tool_choice"] = json!({ "type": "any" }),
+                Some(ToolChoice::Function { name }) => {
+                    body["tool_choice"] = json!({ "type": "tool", "name": name })
+                }
+                Some(ToolChoice::None) => {
+                    body.as_object_mut().map(|obj| obj.remove("tools"));
+                }
+                None => {}
             }
         }
 
+        // Anthropic has no response format; the answer is forced through a tool whose input
+        // schema is the requested one, and the tool input becomes the message content
+        if let Some(format) = &request.response_format {
+            let mut tools = body["tools"].as_array().cloned().unwrap_or_default();
+            tools.push(json!({
+                "name": format.name,
+                "description": format
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| "Give the final answer".to_string()),
+                "input_schema": format.schema,
+            }));
+            body["tools"] = json!(tools);
+            body["tool_choice"] = json!({ "type": "tool", "name": format.name });
+        }
+
         Ok(body)
     }
 }
@@ -431,30 +614,39 @@ impl AIProvider for AnthropicProvider {
         let processed_request = request;
         let body = self.prepare_request(&processed_request).await?;
 
-        let response = self
+        let request = self
             .client
             .post(&format!("{}/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("Content-Type", "application/json")
             .header("anthropic-version", "2023-06-01")
             .header("anthropic-beta", "files-api-2025-04-14")
-            .json(&body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Anthropic API error: {}", error_text).into());
-        }
+            .json(&body);
+        let response = send_with_retry(request, "Anthropic").await?;
 
         let anthropic_response: AnthropicResponse = response.json().await?;
-
-        let content = anthropic_response
-            .content
-            .into_iter()
-            .find(|c| c.content_type == "text")
-            .and_then(|c| c.text)
-            .unwrap_or_default();
+        let format_tool = processed_request.response_format.as_ref().map(|format| format.name.as_str());
+
+        let mut content = String::new();
+        let mut reasoning = String::new();
+        let mut tool_calls = Vec::new();
+        let mut stop_reason = anthropic_response.stop_reason;
+        for block in anthropic_response.content {
+            match block.content_type.as_str() {
+                "text" => content.push_str(&block.text.unwrap_or_default()),
+                "thinking" => reasoning.push_str(&block.thinking.unwrap_or_default()),
+                "tool_use" if format_tool.is_some() && block.name.as_deref() == format_tool => {
+                    content = block.input.unwrap_or_else(|| json!({})).to_string();
+                    stop_reason = Some("end_turn".to_string());
+                }
+                "tool_use" => tool_calls.push(ToolCall {
+                    id: block.id.unwrap_or_default(),
+                    name: block.name.unwrap_or_default(),
+                    arguments: block.input.unwrap_or_else(|| json!({})),
+                }),
+                _ => {}
+            }
+        }
 
         let usage = anthropic_response.usage.map(|u| Usage {
             prompt_tokens: Some(u.input_tokens),
@@ -464,8 +656,10 @@ impl AIProvider for AnthropicProvider {
 
         Ok(ChatResponse {
             content,
-            finish_reason: anthropic_response.stop_reason,
+            reasoning: if reasoning.is_empty() { None } else { Some(reasoning) },
+            finish_reason: stop_reason,
             usage,
+            tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
         })
     }
 
@@ -481,26 +675,24 @@ impl AIProvider for AnthropicProvider {
         let processed_request = request; // TODO: Add provider_id parameter
         let body = self.prepare_request(&processed_request).await?;
 
-        let response = self
+        let request = self
             .client
             .post(&format!("{}/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("Content-Type", "application/json")
             .header("anthropic-version", "2023-06-01")
             .header("anthropic-beta", "files-api-2025-04-14")
-            .json(&body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Anthropic API error: {}", error_text).into());
-        }
+            .json(&body);
+        let response = send_with_retry(request, "Anthropic").await?;
 
         use std::sync::{Arc, Mutex};
 
         // Use a shared buffer to handle partial SSE chunks
         let buffer = Arc::new(Mutex::new(String::new()));
+        let state = Arc::new(Mutex::new(AnthropicStreamState {
+            format_tool: processed_request.response_format.map(|format| format.name),
+            ..Default::default()
+        }));
 
         let stream = response.bytes_stream().map(move |result| {
             result.map_err(|e| e.into()).and_then(|bytes| {
@@ -508,8 +700,9 @@ impl AIProvider for AnthropicProvider {
 
                 let mut buffer_guard = buffer.lock().unwrap();
                 buffer_guard.push_str(&text);
+                let mut state_guard = state.lock().unwrap();
 
-                let mut chunks = Vec::new();
+                let mut merged = StreamingChunk::empty();
 
                 // Process complete lines from buffer
                 while let Some(line_end) = buffer_guard.find('\n') {
@@ -523,34 +716,140 @@ impl AIProvider for AnthropicProvider {
                         {
                             match chunk.event_type.as_str() {
                                 "content_block_start" => {
-                                    // Handle initial content block if needed
                                     if let Some(content_block) = chunk.content_block {
-                                        if content_block.content_type == "text" {
-                                            if content_block.text.is_some() {
-                                                chunks.push(StreamingChunk {
-                                                    content: content_block.text,
-                                                    finish_reason: None,
+                                        match content_block.content_type.as_str() {
+                                            "text" => {
+                                                if content_block.text.is_some() {
+                                                    merged.merge(StreamingChunk {
+                                                        content: content_block.text,
+                                                        reasoning: None,
+                                                        finish_reason: None,
+                                                        tool_calls: None,
+                                                        usage: None,
+                                                    });
+                                                }
+                                            }
+                                            "thinking" => {
+                                                merged.merge(StreamingChunk {
+                                                    reasoning: content_block
+                                                        .thinking
+                                                        .filter(|thinking| !thinking.is_empty()),
+                                                    ..StreamingChunk::empty()
                                                 });
                                             }
+                                            "tool_use"
+                                                if content_block.name.is_some()
+                                                    && content_block.name == state_guard.format_tool =>
+                                            {
+                                                // The structured answer streams as content
+                                                state_guard.format_block = chunk.index;
+                                            }
+                                            "tool_use" => {
+                                                // Arguments arrive later as input_json_delta events
+                                                state_guard.tool_blocks.insert(
+                                                    chunk.index.unwrap_or_default(),
+                                                    (
+                                                        content_block.id.unwrap_or_default(),
+                                                        content_block.name.unwrap_or_default(),
+                                                        String::new(),
+                                                    ),
+                                                );
+                                            }
+                                            _ => {}
                                         }
                                     }
                                 }
                                 "content_block_delta" => {
                                     if let Some(delta) = chunk.delta {
-                                        if delta.delta_type == "text_delta" {
-                                            if delta.text.is_some() {
-                                                chunks.push(StreamingChunk {
-                                                    content: delta.text,
-                                                    finish_reason: delta.stop_reason,
+                                        match delta.delta_type.as_deref() {
+                                            Some("text_delta") => {
+                                                if delta.text.is_some() {
+                                                    merged.merge(StreamingChunk {
+                                                        content: delta.text,
+                                                        reasoning: None,
+                                                        finish_reason: delta.stop_reason,
+                                                        tool_calls: None,
+                                                        usage: None,
+                                                    });
+                                                }
+                                            }
+                                            Some("thinking_delta") => {
+                                                merged.merge(StreamingChunk {
+                                                    reasoning: delta.thinking,
+                                                    ..StreamingChunk::empty()
+                                                });
+                                            }
+                                            Some("input_json_delta")
+                                                if chunk.index.is_some()
+                                                    && chunk.index == state_guard.format_block =>
+                                            {
+                                                merged.merge(StreamingChunk {
+                                                    content: delta.partial_json,
+                                                    ..StreamingChunk::empty()
                                                 });
                                             }
+                                            Some("input_json_delta") => {
+                                                if let (Some(block), Some(partial_json)) = (
+                                                    state_guard
+                                                        .tool_blocks
+                                                        .get_mut(&chunk.index.unwrap_or_default()),
+                                                    delta.partial_json,
+                                                ) {
+                                                    block.2.push_str(&partial_json);
+                                                }
+                                            }
+                                            _ => {}
                                         }
                                     }
                                 }
+                                "content_block_stop" => {
+                                    // A finished tool_use block is a complete tool call
+                                    if let Some((id, name, arguments)) = state_guard
+                                        .tool_blocks
+                                        .remove(&chunk.index.unwrap_or_default())
+                                    {
+                                        merged.merge(StreamingChunk {
+                                            content: None,
+                                            reasoning: None,
+                                            finish_reason: None,
+                                            tool_calls: Some(vec![ToolCall::from_raw_arguments(
+                                                id, name, &arguments,
+                                            )]),
+                                            usage: None,
+                                        });
+                                    }
+                                }
+                                "message_start" => {
+                                    state_guard
+                                        .record_usage(chunk.message.and_then(|message| message.usage));
+                                }
+                                "message_delta" => {
+                                    state_guard.record_usage(chunk.usage);
+                                    if let Some(stop_reason) =
+                                        chunk.delta.and_then(|delta| delta.stop_reason)
+                                    {
+                                        // Finishing the structured answer is the end of the turn
+                                        state_guard.stop_reason = if state_guard.format_block.is_some()
+                                            && stop_reason == "tool_use"
+                                        {
+                                            Some("end_turn".to_string())
+                                        } else {
+                                            Some(stop_reason)
+                                        };
+                                    }
+                                }
                                 "message_stop" => {
-                                    chunks.push(StreamingChunk {
+                                    merged.merge(StreamingChunk {
                                         content: None,
-                                        finish_reason: Some("stop".to_string()),
+                                        reasoning: None,
+                                        finish_reason: Some(
+                                            state_guard
+                                                .stop_reason
+                                                .take()
+                                                .unwrap_or_else(|| "stop".to_string()),
+                                        ),
+                                        tool_calls: None,
+                                        usage: state_guard.usage(),
                                     });
                                     break;
                                 }
@@ -560,11 +859,7 @@ impl AIProvider for AnthropicProvider {
                     }
                 }
 
-                // Return the first chunk if we have any, otherwise return empty
-                Ok(chunks.into_iter().next().unwrap_or(StreamingChunk {
-                    content: None,
-                    finish_reason: None,
-                }))
+                Ok(merged)
             })
         });
 
@@ -575,6 +870,48 @@ impl AIProvider for AnthropicProvider {
         "anthropic"
     }
 
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut models = Vec::new();
+        let mut after_id: Option<String> = None;
+
+        loop {
+            let mut req_builder = self
+                .client
+                .get(&format!("{}/models", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .query(&[("limit", "1000")]);
+            if let Some(after_id) = &after_id {
+                req_builder = req_builder.query(&[("after_id", after_id)]);
+            }
+
+            let response = req_builder.send().await?;
+            if !response.status().is_success() {
+                let error_text = response.text().await?;
+                return Err(format!("Anthropic API error: {}", error_text).into());
+            }
+
+            let page: AnthropicModelList = response.json().await?;
+            models.extend(page.data.into_iter().map(|model| DiscoveredModel {
+                name: model.id,
+                display_name: model.display_name,
+                description: None,
+                capabilities: None,
+            }));
+
+            match page.last_id {
+                Some(last_id) if page.has_more => after_id = Some(last_id),
+                _ => break,
+            }
+        }
+
+        Ok(models)
+    }
+
     fn supports_file_upload(&self) -> bool {
         true
     }
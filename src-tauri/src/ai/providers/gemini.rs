@@ -2,15 +2,17 @@ use async_trait::async_trait;
 use futures_util::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Map, Value};
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
-use crate::ai::core::provider_base::build_http_client;
+use crate::ai::core::provider_base::{build_http_client, send_with_retry};
 use crate::ai::core::providers::{
-    AIProvider, ChatMessage, ChatRequest, ChatResponse, ProxyConfig, StreamingChunk,
-    StreamingResponse, Usage,
+    AIProvider, ChatMessage, ChatRequest, ChatResponse, DiscoveredModel, ProxyConfig,
+    StreamingChunk, StreamingResponse, ToolCall, ToolChoice, Usage,
 };
+use crate::ai::parameters::native_parameters;
+use crate::database::models::ModelParameters;
 
 #[derive(Debug, Clone)]
 pub struct GeminiProvider {
@@ -41,7 +43,20 @@ struct GeminiContent {
 
 #[derive(Debug, Deserialize, Serialize)]
 struct GeminiPart {
-    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(rename = "functionCall", skip_serializing_if = "Option::is_none")]
+    function_call: Option<GeminiFunctionCall>,
+    // Set on parts that hold a thought summary rather than answer text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thought: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct GeminiFunctionCall {
+    name: String,
+    #[serde(default)]
+    args: Value,
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,6 +69,45 @@ struct GeminiUsage {
     total_token_count: Option<u32>,
 }
 
+impl From<GeminiUsage> for Usage {
+    fn from(usage: GeminiUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.total_token_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiModelList {
+    #[serde(default)]
+    models: Vec<GeminiModel>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiModel {
+    name: String,
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+    description: Option<String>,
+    #[serde(rename = "supportedGenerationMethods", default)]
+    supported_generation_methods: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiBatchEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<GeminiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbedding {
+    values: Vec<f32>,
+}
+
 #[derive(Debug, Serialize)]
 struct GeminiMessage {
     role: String,
@@ -62,16 +116,144 @@ struct GeminiMessage {
 
 #[derive(Debug, Serialize)]
 struct GeminiMessagePart {
-    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(rename = "functionCall", skip_serializing_if = "Option::is_none")]
+    function_call: Option<GeminiFunctionCall>,
+    #[serde(rename = "functionResponse", skip_serializing_if = "Option::is_none")]
+    function_response: Option<Value>,
 }
 
 #[derive(Debug, Serialize)]
 struct GeminiGenerationConfig {
-    temperature: Option<f64>,
-    #[serde(rename = "maxOutputTokens")]
-    max_output_tokens: Option<u32>,
-    #[serde(rename = "topP")]
-    top_p: Option<f64>,
+    /// Sampling parameters under their Gemini names
+    #[serde(flatten)]
+    sampling: Map<String, Value>,
+    #[serde(rename = "thinkingConfig", skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<GeminiThinkingConfig>,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    response_schema: Option<Value>,
+}
+
+impl GeminiGenerationConfig {
+    fn from_request(request: &ChatRequest) -> Self {
+        let params = request.parameters.as_ref();
+        Self {
+            sampling: params
+                .map(|p| native_parameters(p, "gemini"))
+                .unwrap_or_default(),
+            thinking_config: params.and_then(GeminiThinkingConfig::from_parameters),
+            response_mime_type: request
+                .response_format
+                .as_ref()
+                .map(|_| "application/json".to_string()),
+            response_schema: request
+                .response_format
+                .as_ref()
+                .map(|format| gemini_schema(&format.schema)),
+        }
+    }
+}
+
+// Schema fields accepted by responseSchema, a subset of the OpenAPI schema object
+const GEMINI_SCHEMA_FIELDS: &[&str] = &[
+    "type",
+    "format",
+    "title",
+    "description",
+    "nullable",
+    "enum",
+    "items",
+    "minItems",
+    "maxItems",
+    "properties",
+    "required",
+    "minProperties",
+    "maxProperties",
+    "propertyOrdering",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+    "anyOf",
+];
+
+/// Reduce a JSON Schema to the fields responseSchema accepts. A type list with "null"
+/// becomes a nullable type.
+fn gemini_schema(schema: &Value) -> Value {
+    let Some(object) = schema.as_object() else {
+        return schema.clone();
+    };
+
+    let mut converted = serde_json::Map::new();
+    for (key, value) in object {
+        if !GEMINI_SCHEMA_FIELDS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match key.as_str() {
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .map(|properties| {
+                        properties
+                            .iter()
+                            .map(|(name, property)| (name.clone(), gemini_schema(property)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+            "items" => gemini_schema(value),
+            "anyOf" => Value::Array(
+                value
+                    .as_array()
+                    .map(|schemas| schemas.iter().map(gemini_schema).collect())
+                    .unwrap_or_default(),
+            ),
+            "type" => match value.as_array() {
+                Some(types) => {
+                    if types.iter().any(|t| t == "null") {
+                        converted.insert("nullable".to_string(), json!(true));
+                    }
+                    types.iter().find(|t| *t != "null").cloned().unwrap_or(Value::Null)
+                }
+                None => value.clone(),
+            },
+            _ => value.clone(),
+        };
+        converted.insert(key.clone(), value);
+    }
+    Value::Object(converted)
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiThinkingConfig {
+    #[serde(rename = "includeThoughts")]
+    include_thoughts: bool,
+    #[serde(rename = "thinkingBudget", skip_serializing_if = "Option::is_none")]
+    thinking_budget: Option<u32>,
+}
+
+impl GeminiThinkingConfig {
+    /// Thinking settings, only when the parameters mention thinking at all. Without a budget
+    /// the model decides how long to think; a budget of 0 turns thinking off.
+    fn from_parameters(params: &ModelParameters) -> Option<Self> {
+        if params.enable_thinking.is_none() && params.thinking_budget.is_none() {
+            return None;
+        }
+        if !params.thinking_enabled() {
+            return Some(Self {
+                include_thoughts: false,
+                thinking_budget: Some(0),
+            });
+        }
+        Some(Self {
+            include_thoughts: true,
+            thinking_budget: params.thinking_budget,
+        })
+    }
 }
 
 impl GeminiProvider {
@@ -97,6 +279,29 @@ impl GeminiProvider {
         messages
             .iter()
             .filter_map(|msg| {
+                // Tool results go back as functionResponse parts; Gemini matches them by
+                // function name, so look the name up from the assistant call that produced them
+                if let Some(tool_call_id) = &msg.tool_call_id {
+                    let name = messages
+                        .iter()
+                        .filter_map(|m| m.tool_calls.as_ref())
+                        .flatten()
+                        .find(|call| &call.id == tool_call_id)
+                        .map(|call| call.name.clone())
+                        .unwrap_or_default();
+                    return Some(GeminiMessage {
+                        role: "user".to_string(),
+                        parts: vec![GeminiMessagePart {
+                            text: None,
+                            function_call: None,
+                            function_response: Some(json!({
+                                "name": name,
+                                "response": { "content": msg.content.to_plain_text() }
+                            })),
+                        }],
+                    });
+                }
+
                 // Convert role names to Gemini format
                 let role = match msg.role.as_str() {
                     "system" => return None, // Gemini doesn't support system messages in the same way
@@ -105,23 +310,43 @@ impl GeminiProvider {
                     _ => "user",
                 };
 
+                let text = match &msg.content {
+                    crate::ai::MessageContent::Text(text) => text.clone(),
+                    crate::ai::MessageContent::Multimodal(parts) => {
+                        // For now, just join text parts
+                        parts.iter()
+                            .filter_map(|part| match part {
+                                crate::ai::ContentPart::Text(text) => Some(text.clone()),
+                                crate::ai::ContentPart::FileReference(file_ref) => Some(format!("File: {}", file_ref.filename)),
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    }
+                };
+
+                let mut parts = Vec::new();
+                let tool_calls = msg.tool_calls.as_deref().unwrap_or_default();
+                if !text.is_empty() || tool_calls.is_empty() {
+                    parts.push(GeminiMessagePart {
+                        text: Some(text),
+                        function_call: None,
+                        function_response: None,
+                    });
+                }
+                for call in tool_calls {
+                    parts.push(GeminiMessagePart {
+                        text: None,
+                        function_call: Some(GeminiFunctionCall {
+                            name: call.name.clone(),
+                            args: call.arguments.clone(),
+                        }),
+                        function_response: None,
+                    });
+                }
+
                 Some(GeminiMessage {
                     role: role.to_string(),
-                    parts: vec![GeminiMessagePart {
-                        text: match &msg.content {
-                            crate::ai::MessageContent::Text(text) => text.clone(),
-                            crate::ai::MessageContent::Multimodal(parts) => {
-                                // For now, just join text parts
-                                parts.iter()
-                                    .filter_map(|part| match part {
-                                        crate::ai::ContentPart::Text(text) => Some(text.clone()),
-                                        crate::ai::ContentPart::FileReference(file_ref) => Some(format!("File: {}", file_ref.filename)),
-                                    })
-                                    .collect::<Vec<_>>()
-                                    .join("\n")
-                            }
-                        },
-                    }],
+                    parts,
                 })
             })
             .collect()
@@ -133,7 +358,7 @@ impl GeminiProvider {
             .find(|msg| msg.role == "system")
             .map(|msg| GeminiContent {
                 parts: vec![GeminiPart {
-                    text: match &msg.content {
+                    text: Some(match &msg.content {
                         crate::ai::MessageContent::Text(text) => text.clone(),
                         crate::ai::MessageContent::Multimodal(parts) => {
                             // For now, just join text parts
@@ -145,10 +370,66 @@ impl GeminiProvider {
                                 .collect::<Vec<_>>()
                                 .join("\n")
                         }
-                    },
+                    }),
+                    function_call: None,
+                    thought: None,
                 }],
             })
     }
+
+    /// Add function declarations and the calling mode to a request payload
+    fn add_tools_to_payload(&self, payload: &mut Value, request: &ChatRequest) {
+        let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) else {
+            return;
+        };
+
+        payload["tools"] = json!([{
+            "functionDeclarations": tools
+                .iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description.clone().unwrap_or_default(),
+                    "parameters": tool.parameters
+                }))
+                .collect::<Vec<_>>()
+        }]);
+
+        let config = match &request.tool_choice {
+            Some(ToolChoice::Auto) => json!({ "mode": "AUTO" }),
+            Some(ToolChoice::None) => json!({ "mode": "NONE" }),
+            Some(ToolChoice::Required) => json!({ "mode": "ANY" }),
+            Some(ToolChoice::Function { name }) => {
+                json!({ "mode": "ANY", "allowedFunctionNames": [name] })
+            }
+            None => return,
+        };
+        payload["toolConfig"] = json!({ "functionCallingConfig": config });
+    }
+
+    /// Split response parts into text, thoughts and tool calls. Gemini does not assign call
+    /// ids, so one is generated for each call to pair it with its result later
+    fn split_parts(parts: Vec<GeminiPart>) -> (String, String, Vec<ToolCall>) {
+        let mut content = String::new();
+        let mut reasoning = String::new();
+        let mut tool_calls = Vec::new();
+        for part in parts {
+            if let Some(text) = part.text {
+                if part.thought == Some(true) {
+                    reasoning.push_str(&text);
+                } else {
+                    content.push_str(&text);
+                }
+            }
+            if let Some(call) = part.function_call {
+                tool_calls.push(ToolCall {
+                    id: format!("call_{}", Uuid::new_v4().simple()),
+                    name: call.name,
+                    arguments: call.args,
+                });
+            }
+        }
+        (content, reasoning, tool_calls)
+    }
 }
 
 #[async_trait]
@@ -165,14 +446,9 @@ impl AIProvider for GeminiProvider {
         let contents = self.convert_messages_to_gemini(&request.messages);
         let system_instruction = self.create_system_instruction(&request.messages);
 
-        let params = request.parameters.as_ref();
         let mut payload = json!({
             "contents": contents,
-            "generationConfig": GeminiGenerationConfig {
-                temperature: params.and_then(|p| p.temperature).map(|t| t as f64),
-                max_output_tokens: params.and_then(|p| p.max_tokens),
-                top_p: params.and_then(|p| p.top_p).map(|t| t as f64),
-            }
+            "generationConfig": GeminiGenerationConfig::from_request(&request)
         });
 
         // Add system instruction if present
@@ -180,46 +456,26 @@ impl AIProvider for GeminiProvider {
             payload["systemInstruction"] = json!({ "parts": system_instruction.parts });
         }
 
-        // Add stop sequences if present (Gemini uses "stopSequences")
-        if let Some(params) = params {
-            if let Some(stop) = &params.stop {
-                payload["generationConfig"]["stopSequences"] = json!(stop);
-            }
-            // Note: Gemini doesn't support seed parameter
-        }
+        self.add_tools_to_payload(&mut payload, &request);
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Gemini API error: {}", error_text).into());
-        }
+            .json(&payload);
+        let response = send_with_retry(request, "Gemini").await?;
 
         let gemini_response: GeminiResponse = response.json().await?;
 
         if let Some(candidate) = gemini_response.candidates.into_iter().next() {
-            let content = candidate
-                .content
-                .parts
-                .into_iter()
-                .map(|part| part.text)
-                .collect::<Vec<_>>()
-                .join("");
+            let (content, reasoning, tool_calls) = Self::split_parts(candidate.content.parts);
 
             Ok(ChatResponse {
                 content,
+                reasoning: if reasoning.is_empty() { None } else { Some(reasoning) },
                 finish_reason: candidate.finish_reason,
-                usage: gemini_response.usage_metadata.map(|u| Usage {
-                    prompt_tokens: u.prompt_token_count,
-                    completion_tokens: u.candidates_token_count,
-                    total_tokens: u.total_token_count,
-                }),
+                tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+                usage: gemini_response.usage_metadata.map(Usage::from),
             })
         } else {
             Err("No candidates returned from Gemini API".into())
@@ -238,14 +494,9 @@ impl AIProvider for GeminiProvider {
         let contents = self.convert_messages_to_gemini(&request.messages);
         let system_instruction = self.create_system_instruction(&request.messages);
 
-        let params = request.parameters.as_ref();
         let mut payload = json!({
             "contents": contents,
-            "generationConfig": GeminiGenerationConfig {
-                temperature: params.and_then(|p| p.temperature).map(|t| t as f64),
-                max_output_tokens: params.and_then(|p| p.max_tokens),
-                top_p: params.and_then(|p| p.top_p).map(|t| t as f64),
-            }
+            "generationConfig": GeminiGenerationConfig::from_request(&request)
         });
 
         // Add system instruction if present
@@ -253,26 +504,14 @@ impl AIProvider for GeminiProvider {
             payload["systemInstruction"] = json!({ "parts": system_instruction.parts });
         }
 
-        // Add stop sequences if present (Gemini uses "stopSequences")
-        if let Some(params) = params {
-            if let Some(stop) = &params.stop {
-                payload["generationConfig"]["stopSequences"] = json!(stop);
-            }
-            // Note: Gemini doesn't support seed parameter
-        }
+        self.add_tools_to_payload(&mut payload, &request);
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Gemini API error: {}", error_text).into());
-        }
+            .json(&payload);
+        let response = send_with_retry(request, "Gemini").await?;
 
         // Create a buffer to accumulate partial SSE chunks
         let buffer = Arc::new(Mutex::new(String::new()));
@@ -286,7 +525,7 @@ impl AIProvider for GeminiProvider {
                     buffer_guard.push_str(&chunk);
 
                     // Process complete lines from buffer
-                    let mut result = None;
+                    let mut merged = StreamingChunk::empty();
                     while let Some(line_end) = buffer_guard.find('\n') {
                         let line = buffer_guard[..line_end].trim().to_string();
                         buffer_guard.drain(..=line_end);
@@ -298,24 +537,38 @@ impl AIProvider for GeminiProvider {
                         // Gemini returns JSON objects separated by newlines, not SSE format
                         match serde_json::from_str::<GeminiResponse>(&line) {
                             Ok(gemini_response) => {
+                                // Every streamed response repeats the running usage totals
+                                let usage = gemini_response.usage_metadata.map(Usage::from);
                                 if let Some(candidate) =
                                     gemini_response.candidates.into_iter().next()
                                 {
-                                    let content = candidate
-                                        .content
-                                        .parts
-                                        .into_iter()
-                                        .map(|part| part.text)
-                                        .collect::<Vec<_>>()
-                                        .join("");
-
-                                    if !content.is_empty() {
-                                        result = Some(Ok(StreamingChunk {
-                                            content: Some(content),
-                                            finish_reason: candidate.finish_reason,
-                                        }));
-                                        break;
-                                    }
+                                    let (content, reasoning, tool_calls) =
+                                        Self::split_parts(candidate.content.parts);
+
+                                    merged.merge(StreamingChunk {
+                                        content: if content.is_empty() {
+                                            None
+                                        } else {
+                                            Some(content)
+                                        },
+                                        reasoning: if reasoning.is_empty() {
+                                            None
+                                        } else {
+                                            Some(reasoning)
+                                        },
+                                        finish_reason: candidate.finish_reason,
+                                        tool_calls: if tool_calls.is_empty() {
+                                            None
+                                        } else {
+                                            Some(tool_calls)
+                                        },
+                                        usage,
+                                    });
+                                } else if usage.is_some() {
+                                    merged.merge(StreamingChunk {
+                                        usage,
+                                        ..StreamingChunk::empty()
+                                    });
                                 }
                             }
                             Err(e) => {
@@ -327,10 +580,7 @@ impl AIProvider for GeminiProvider {
                         }
                     }
 
-                    result.unwrap_or(Ok(StreamingChunk {
-                        content: None,
-                        finish_reason: None,
-                    }))
+                    Ok(merged)
                 }
                 Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
             }
@@ -342,4 +592,92 @@ impl AIProvider for GeminiProvider {
     fn provider_name(&self) -> &'static str {
         "gemini"
     }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut models = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut req_builder = self
+                .client
+                .get(&format!("{}/models", self.base_url))
+                .query(&[("key", self.api_key.as_str()), ("pageSize", "1000")]);
+            if let Some(page_token) = &page_token {
+                req_builder = req_builder.query(&[("pageToken", page_token)]);
+            }
+
+            let response = req_builder.send().await?;
+            if !response.status().is_success() {
+                let error_text = response.text().await?;
+                return Err(format!("Gemini API error: {}", error_text).into());
+            }
+
+            let page: GeminiModelList = response.json().await?;
+            models.extend(
+                page.models
+                    .into_iter()
+                    // Embedding and other non-chat models do not support generateContent
+                    .filter(|model| model.supported_generation_methods.iter().any(|m| m == "generateContent"))
+                    .map(|model| DiscoveredModel {
+                        name: model.name.trim_start_matches("models/").to_string(),
+                        display_name: model.display_name,
+                        description: model.description.filter(|description| !description.is_empty()),
+                        capabilities: None,
+                    }),
+            );
+
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(models)
+    }
+
+    async fn embed(
+        &self,
+        model_name: &str,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Every request in the batch names the model again, with the "models/" prefix
+        let model = format!("models/{}", model_name.trim_start_matches("models/"));
+        let url = format!("{}/{}:batchEmbedContents?key={}", self.base_url, model, self.api_key);
+        let payload = json!({
+            "requests": texts
+                .iter()
+                .map(|text| json!({
+                    "model": model,
+                    "content": { "parts": [{ "text": text }] }
+                }))
+                .collect::<Vec<_>>()
+        });
+
+        let request = self
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(&payload);
+        let response = send_with_retry(request, "Gemini").await?;
+
+        let batch: GeminiBatchEmbedResponse = response.json().await?;
+        if batch.embeddings.len() != texts.len() {
+            return Err(format!(
+                "Gemini returned {} embeddings for {} inputs",
+                batch.embeddings.len(),
+                texts.len()
+            )
+            .into());
+        }
+
+        Ok(batch.embeddings.into_iter().map(|embedding| embedding.values).collect())
+    }
 }
@@ -2,7 +2,9 @@ use async_trait::async_trait;
 use uuid::Uuid;
 
 use super::openai_compatible::OpenAICompatibleProvider;
-use crate::ai::core::providers::{AIProvider, ChatRequest, ChatResponse, ProxyConfig, StreamingResponse};
+use crate::ai::core::providers::{
+    AIProvider, ChatRequest, ChatResponse, DiscoveredModel, ProxyConfig, StreamingResponse,
+};
 
 #[derive(Debug, Clone)]
 pub struct GroqProvider {
@@ -43,4 +45,12 @@ impl AIProvider for GroqProvider {
     fn provider_name(&self) -> &'static str {
         self.inner.provider_name()
     }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.list_models().await
+    }
 }
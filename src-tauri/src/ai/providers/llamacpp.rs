@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::openai_compatible::OpenAICompatibleProvider;
+use crate::ai::core::provider_base::build_http_client;
+use crate::ai::core::providers::{
+    AIProvider, ChatRequest, ChatResponse, DiscoveredModel, LoadedModel, ProxyConfig,
+    StreamingResponse,
+};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:8080";
+
+/// Provider for a `llama-server` from llama.cpp, started by the user or any other tool.
+/// Chat goes through the server's OpenAI-compatible API.
+#[derive(Debug, Clone)]
+pub struct LlamaCppProvider {
+    inner: OpenAICompatibleProvider,
+    client: Client,
+    api_key: String,
+    // Server root, where /health lives
+    root_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaCppModelList {
+    data: Vec<LlamaCppModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaCppModel {
+    id: String,
+    // Only reported by servers running in router mode with several models
+    status: Option<LlamaCppModelStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaCppModelStatus {
+    value: String,
+}
+
+impl LlamaCppProvider {
+    pub fn new(
+        api_key: String,
+        base_url: Option<String>,
+        proxy_config: Option<ProxyConfig>,
+        provider_id: Uuid,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let base_url = base_url
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let root_url = base_url.trim_end_matches("/v1").to_string();
+
+        let client = build_http_client(&base_url, proxy_config.as_ref())?;
+        let inner = OpenAICompatibleProvider::new(api_key.clone(), base_url, "llamacpp", proxy_config, provider_id)?;
+
+        Ok(Self {
+            inner,
+            client,
+            api_key,
+            root_url,
+        })
+    }
+
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(format!("{}{}", self.root_url, path));
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        }
+    }
+}
+
+#[async_trait]
+impl AIProvider for LlamaCppProvider {
+    async fn chat(
+        &self,
+        request: ChatRequest,
+    ) -> Result<ChatResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.chat(request).await
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamingResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.chat_stream(request).await
+    }
+
+    fn provider_name(&self) -> &'static str {
+        self.inner.provider_name()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.list_models().await
+    }
+
+    async fn loaded_models(&self) -> Result<Vec<LoadedModel>, Box<dyn std::error::Error + Send + Sync>> {
+        let health = self.get("/health").send().await?;
+        // The server answers 503 until the model has finished loading
+        if health.status() == StatusCode::SERVICE_UNAVAILABLE {
+            return Ok(Vec::new());
+        }
+        if !health.status().is_success() {
+            let error_text = health.text().await?;
+            return Err(format!("llama.cpp server error: {}", error_text).into());
+        }
+
+        let response = self.get("/v1/models").send().await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("llama.cpp server error: {}", error_text).into());
+        }
+
+        let models: LlamaCppModelList = response.json().await?;
+        Ok(models
+            .data
+            .into_iter()
+            .filter(|model| model.status.as_ref().is_none_or(|status| status.value == "loaded"))
+            .map(|model| LoadedModel {
+                name: model.id,
+                size_vram: None,
+                expires_at: None,
+            })
+            .collect())
+    }
+
+    async fn embed(
+        &self,
+        model_name: &str,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.embed(model_name, texts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_helpers::spawn_mock_server;
+    use super::*;
+    use crate::ai::core::providers::ChatMessage;
+    use crate::database::models::model::ModelParameters;
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
+    use futures_util::StreamExt;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    type Requests = Arc<Mutex<Vec<Value>>>;
+
+    async fn mock_llama_server(healthy: bool) -> (LlamaCppProvider, Requests) {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+
+        let router = Router::new()
+            .route(
+                "/health",
+                get(move || async move {
+                    if healthy {
+                        (StatusCode::OK, Json(json!({ "status": "ok" })))
+                    } else {
+                        (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            Json(json!({ "error": { "code": 503, "message": "Loading model" } })),
+                        )
+                    }
+                }),
+            )
+            .route(
+                "/v1/models",
+                get(|| async {
+                    Json(json!({
+                        "object": "list",
+                        "data": [
+                            { "id": "qwen2.5-7b-instruct", "object": "model", "status": { "value": "loaded" } },
+                            { "id": "gemma-3-4b", "object": "model", "status": { "value": "unloaded" } },
+                        ]
+                    }))
+                }),
+            )
+            .route(
+                "/v1/chat/completions",
+                post(|State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push(body);
+                    concat!(
+                        "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"},\"finish_reason\":null}]}\n\n",
+                        "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+                        "data: [DONE]\n\n",
+                    )
+                }),
+            )
+            .route(
+                "/v1/embeddings",
+                post(|Json(body): Json<Value>| async move {
+                    // Out of order, as the API allows
+                    let count = body["input"].as_array().map_or(0, Vec::len);
+                    let data: Vec<Value> = (0..count)
+                        .rev()
+                        .map(|index| json!({ "object": "embedding", "index": index, "embedding": [index as f32, 1.0] }))
+                        .collect();
+                    Json(json!({ "object": "list", "data": data, "model": body["model"] }))
+                }),
+            )
+            .with_state(requests.clone());
+
+        let base_url = spawn_mock_server(router).await;
+        (LlamaCppProvider::new(String::new(), Some(base_url), None, Uuid::nil()).unwrap(), requests)
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_sends_sampling_options() {
+        let (provider, requests) = mock_llama_server(true).await;
+
+        let mut stream = provider
+            .chat_stream(ChatRequest {
+                messages: vec![ChatMessage::text("user", "Hello")],
+                model_name: "qwen2.5-7b-instruct".to_string(),
+                model_id: Uuid::nil(),
+                provider_id: Uuid::nil(),
+                stream: true,
+                parameters: Some(ModelParameters {
+                    top_k: Some(40),
+                    min_p: Some(0.5),
+                    ..Default::default()
+                }),
+                tools: None,
+                tool_choice: None,
+                response_format: None,
+            })
+            .await
+            .unwrap();
+
+        let mut content = String::new();
+        while let Some(chunk) = stream.next().await {
+            content.push_str(&chunk.unwrap().content.unwrap_or_default());
+        }
+        assert_eq!(content, "Hi");
+
+        let body = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(body["top_k"], 40);
+        assert_eq!(body["min_p"], 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_loaded_models_reports_router_status() {
+        let (provider, _) = mock_llama_server(true).await;
+
+        let loaded = provider.loaded_models().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "qwen2.5-7b-instruct");
+    }
+
+    #[tokio::test]
+    async fn test_embed_returns_vectors_in_input_order() {
+        let (provider, _) = mock_llama_server(true).await;
+
+        let texts = vec!["first".to_string(), "second".to_string()];
+        let vectors = provider.embed("nomic-embed-text", &texts).await.unwrap();
+        assert_eq!(vectors, vec![vec![0.0, 1.0], vec![1.0, 1.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_loaded_models_empty_while_loading() {
+        let (provider, _) = mock_llama_server(false).await;
+        assert!(provider.loaded_models().await.unwrap().is_empty());
+    }
+}
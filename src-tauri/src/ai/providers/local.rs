@@ -7,7 +7,12 @@ use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 use crate::ai::core::providers::{
-    AIProvider, ChatRequest, ChatResponse, ContentPart, FileReference, MessageContent, StreamingChunk, StreamingResponse, Usage,
+    AIProvider, ChatRequest, ChatResponse, ContentPart, FileReference, MessageContent, StreamingChunk, StreamingResponse, ToolCall, Usage,
+};
+use crate::ai::parameters::native_parameters;
+use crate::ai::providers::openai_compatible::{
+    add_tools_to_payload, message_to_openai, read_embeddings_response, OpenAICompatibleToolCallDelta,
+    ReasoningFields, ToolCallAccumulator,
 };
 use crate::ai::file_helpers::{get_file_content_for_local_provider, LocalProviderFileContent};
 use crate::database::models::model::ModelCapabilities;
@@ -35,7 +40,22 @@ struct LocalChoice {
 
 #[derive(Debug, Deserialize)]
 struct LocalMessage {
-    content: String,
+    content: Option<String>,
+    #[serde(flatten)]
+    reasoning: ReasoningFields,
+    tool_calls: Option<Vec<LocalToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalToolCall {
+    id: String,
+    function: LocalFunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalFunctionCall {
+    name: String,
+    arguments: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,9 +65,22 @@ struct LocalUsage {
     total_tokens: Option<u32>,
 }
 
+impl From<LocalUsage> for Usage {
+    fn from(usage: LocalUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct LocalStreamResponse {
+    #[serde(default)]
     choices: Vec<LocalStreamChoice>,
+    // The model server includes usage on the final chunk
+    usage: Option<LocalUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -59,6 +92,9 @@ struct LocalStreamChoice {
 #[derive(Debug, Deserialize)]
 struct LocalStreamDelta {
     content: Option<String>,
+    #[serde(flatten)]
+    reasoning: ReasoningFields,
+    tool_calls: Option<Vec<OpenAICompatibleToolCallDelta>>,
 }
 
 impl LocalProvider {
@@ -93,49 +129,117 @@ impl LocalProvider {
             let openai_message = match &message.content {
                 MessageContent::Text(text) => {
                     // Simple text message
-                    json!({
-                        "role": message.role,
-                        "content": text
-                    })
+                    message_to_openai(message, json!(text))
                 }
                 MessageContent::Multimodal(parts) => {
                     // Convert multimodal content to OpenAI format
                     let content_array = self.process_multimodal_to_openai_format(parts, capabilities).await?;
-                    json!({
-                        "role": message.role,
-                        "content": content_array
-                    })
+                    message_to_openai(message, json!(content_array))
                 }
             };
             
             processed_messages.push(openai_message);
         }
         
-        let params = request.parameters.as_ref();
         let mut payload = json!({
             "model": "default".to_string(), // Use "default" for local provider
             "messages": processed_messages,
-            "temperature": params.and_then(|p| p.temperature).unwrap_or(0.7),
-            "max_tokens": params.and_then(|p| p.max_tokens).unwrap_or(4096),
-            "top_p": params.and_then(|p| p.top_p).unwrap_or(0.95),
-            "frequency_penalty": params.and_then(|p| p.frequency_penalty).unwrap_or(0.0),
-            "presence_penalty": params.and_then(|p| p.presence_penalty).unwrap_or(0.0),
             "stream": stream
         });
 
-        // Add optional parameters if present
-        if let Some(params) = params {
-            if let Some(seed) = params.seed {
-                payload["seed"] = json!(seed);
+        // Unset parameters are left to the defaults the model was started with
+        if let Some(params) = &request.parameters {
+            for (name, value) in native_parameters(params, "local") {
+                payload[name] = value;
             }
-            if let Some(stop) = &params.stop {
-                payload["stop"] = json!(stop);
+            if params.enable_thinking.is_some() || params.thinking_budget.is_some() {
+                payload["enable_thinking"] = json!(params.thinking_enabled());
             }
         }
 
+        add_tools_to_payload(&mut payload, request);
+
+        // mistral.rs constrains decoding with a grammar compiled from the schema
+        if let Some(format) = &request.response_format {
+            payload["grammar"] = json!({ "type": "json_schema", "value": format.schema });
+        }
+
         Ok(payload)
     }
 
+    /// Turn the server's SSE body into a stream of chunks, merging every event in a network read
+    /// and emitting accumulated tool calls once the finish reason arrives
+    fn parse_stream(response: reqwest::Response) -> StreamingResponse {
+        // Create a buffer to accumulate partial SSE chunks
+        let buffer = Arc::new(Mutex::new(String::new()));
+        let tool_calls = Arc::new(Mutex::new(ToolCallAccumulator::default()));
+
+        let stream = response.bytes_stream().map(move |result| {
+            let buffer = buffer.clone();
+            match result {
+                Ok(bytes) => {
+                    let chunk = String::from_utf8_lossy(&bytes);
+                    let mut buffer_guard = buffer.lock().unwrap();
+                    buffer_guard.push_str(&chunk);
+                    let mut tool_calls_guard = tool_calls.lock().unwrap();
+
+                    // Process complete lines from buffer
+                    let mut merged = StreamingChunk::empty();
+                    while let Some(line_end) = buffer_guard.find('\n') {
+                        let line = buffer_guard[..line_end].trim().to_string();
+                        buffer_guard.drain(..=line_end);
+
+                        if line.is_empty() || line == "data: [DONE]" {
+                            continue;
+                        }
+
+                        if let Some(data) = line.strip_prefix("data: ") {
+                            match serde_json::from_str::<LocalStreamResponse>(data) {
+                                Ok(stream_response) => {
+                                    if let Some(usage) = stream_response.usage {
+                                        merged.merge(StreamingChunk {
+                                            usage: Some(usage.into()),
+                                            ..StreamingChunk::empty()
+                                        });
+                                    }
+                                    if let Some(choice) = stream_response.choices.into_iter().next()
+                                    {
+                                        for delta in choice.delta.tool_calls.unwrap_or_default() {
+                                            tool_calls_guard.push(delta);
+                                        }
+                                        let finished_calls = if choice.finish_reason.is_some() {
+                                            tool_calls_guard.take()
+                                        } else {
+                                            None
+                                        };
+                                        merged.merge(StreamingChunk {
+                                            content: choice.delta.content,
+                                            reasoning: choice.delta.reasoning.into_text(),
+                                            finish_reason: choice.finish_reason,
+                                            tool_calls: finished_calls,
+                                            usage: None,
+                                        });
+                                    }
+                                }
+                                Err(e) => {
+                                    eprintln!(
+                                        "Failed to parse Local streaming response: {} for data: {}",
+                                        e, data
+                                    );
+                                }
+                            }
+                        }
+                    }
+
+                    Ok(merged)
+                }
+                Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
+            }
+        });
+
+        Box::pin(stream)
+    }
+
     fn get_endpoint_url(&self) -> String {
         format!("{}/v1/chat/completions", self.base_url)
     }
@@ -301,14 +405,21 @@ impl AIProvider for LocalProvider {
         let api_response: LocalResponse = response.json().await?;
 
         if let Some(choice) = api_response.choices.into_iter().next() {
+            let tool_calls = choice.message.tool_calls.map(|calls| {
+                calls
+                    .into_iter()
+                    .map(|call| {
+                        ToolCall::from_raw_arguments(call.id, call.function.name, &call.function.arguments)
+                    })
+                    .collect::<Vec<_>>()
+            });
+
             Ok(ChatResponse {
-                content: choice.message.content,
+                content: choice.message.content.unwrap_or_default(),
+                reasoning: choice.message.reasoning.into_text(),
                 finish_reason: choice.finish_reason,
-                usage: api_response.usage.map(|u| Usage {
-                    prompt_tokens: u.prompt_tokens,
-                    completion_tokens: u.completion_tokens,
-                    total_tokens: u.total_tokens,
-                }),
+                tool_calls,
+                usage: api_response.usage.map(Usage::from),
             })
         } else {
             Err("No choices returned from Candle API".into())
@@ -339,63 +450,44 @@ impl AIProvider for LocalProvider {
             return Err(format!("Candle API error: {}", error_text).into());
         }
 
-        // Create a buffer to accumulate partial SSE chunks
-        let buffer = Arc::new(Mutex::new(String::new()));
-
-        let stream = response.bytes_stream().map(move |result| {
-            let buffer = buffer.clone();
-            match result {
-                Ok(bytes) => {
-                    let chunk = String::from_utf8_lossy(&bytes);
-                    let mut buffer_guard = buffer.lock().unwrap();
-                    buffer_guard.push_str(&chunk);
+        Ok(Self::parse_stream(response))
+    }
 
-                    // Process complete lines from buffer
-                    let mut result = None;
-                    while let Some(line_end) = buffer_guard.find('\n') {
-                        let line = buffer_guard[..line_end].trim().to_string();
-                        buffer_guard.drain(..=line_end);
+    fn provider_name(&self) -> &'static str {
+        "local"
+    }
 
-                        if line.is_empty() || line == "data: [DONE]" {
-                            continue;
-                        }
+    fn supports_tools(&self) -> bool {
+        true
+    }
 
-                        if let Some(data) = line.strip_prefix("data: ") {
-                            match serde_json::from_str::<LocalStreamResponse>(data) {
-                                Ok(stream_response) => {
-                                    if let Some(choice) = stream_response.choices.into_iter().next()
-                                    {
-                                        result = Some(Ok(StreamingChunk {
-                                            content: choice.delta.content,
-                                            finish_reason: choice.finish_reason,
-                                        }));
-                                        break;
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!(
-                                        "Failed to parse Local streaming response: {} for data: {}",
-                                        e, data
-                                    );
-                                }
-                            }
-                        }
-                    }
+    async fn embed(
+        &self,
+        _model_name: &str,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
 
-                    result.unwrap_or(Ok(StreamingChunk {
-                        content: None,
-                        finish_reason: None,
-                    }))
-                }
-                Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
-            }
-        });
+        // mistralrs-server serves a single model per process, addressed as "default"
+        let response = self
+            .client
+            .post(format!("{}/v1/embeddings", self.base_url))
+            .header("Content-Type", "application/json")
+            .json(&json!({
+                "model": "default",
+                "input": texts,
+            }))
+            .send()
+            .await?;
 
-        Ok(Box::pin(stream))
-    }
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("Candle API error: {}", error_text).into());
+        }
 
-    fn provider_name(&self) -> &'static str {
-        "local"
+        read_embeddings_response(response, texts.len()).await
     }
 }
 
@@ -434,14 +526,21 @@ impl LocalProvider {
         let api_response: LocalResponse = response.json().await?;
 
         if let Some(choice) = api_response.choices.into_iter().next() {
+            let tool_calls = choice.message.tool_calls.map(|calls| {
+                calls
+                    .into_iter()
+                    .map(|call| {
+                        ToolCall::from_raw_arguments(call.id, call.function.name, &call.function.arguments)
+                    })
+                    .collect::<Vec<_>>()
+            });
+
             Ok(ChatResponse {
-                content: choice.message.content,
+                content: choice.message.content.unwrap_or_default(),
+                reasoning: choice.message.reasoning.into_text(),
                 finish_reason: choice.finish_reason,
-                usage: api_response.usage.map(|u| Usage {
-                    prompt_tokens: u.prompt_tokens,
-                    completion_tokens: u.completion_tokens,
-                    total_tokens: u.total_tokens,
-                }),
+                tool_calls,
+                usage: api_response.usage.map(Usage::from),
             })
         } else {
             Err("No choices returned from Candle API".into())
@@ -478,58 +577,6 @@ impl LocalProvider {
             return Err(format!("Candle API error: {}", error_text).into());
         }
 
-        // Create a buffer to accumulate partial SSE chunks
-        let buffer = Arc::new(Mutex::new(String::new()));
-
-        let stream = response.bytes_stream().map(move |result| {
-            let buffer = buffer.clone();
-            match result {
-                Ok(bytes) => {
-                    let chunk = String::from_utf8_lossy(&bytes);
-                    let mut buffer_guard = buffer.lock().unwrap();
-                    buffer_guard.push_str(&chunk);
-
-                    // Process complete lines from buffer
-                    let mut result = None;
-                    while let Some(line_end) = buffer_guard.find('\n') {
-                        let line = buffer_guard[..line_end].trim().to_string();
-                        buffer_guard.drain(..=line_end);
-
-                        if line.is_empty() || line == "data: [DONE]" {
-                            continue;
-                        }
-
-                        if let Some(data) = line.strip_prefix("data: ") {
-                            match serde_json::from_str::<LocalStreamResponse>(data) {
-                                Ok(stream_response) => {
-                                    if let Some(choice) = stream_response.choices.into_iter().next()
-                                    {
-                                        result = Some(Ok(StreamingChunk {
-                                            content: choice.delta.content,
-                                            finish_reason: choice.finish_reason,
-                                        }));
-                                        break;
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!(
-                                        "Failed to parse Local streaming response: {} for data: {}",
-                                        e, data
-                                    );
-                                }
-                            }
-                        }
-                    }
-
-                    result.unwrap_or(Ok(StreamingChunk {
-                        content: None,
-                        finish_reason: None,
-                    }))
-                }
-                Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
-            }
-        });
-
-        Ok(Box::pin(stream))
+        Ok(Self::parse_stream(response))
     }
 }
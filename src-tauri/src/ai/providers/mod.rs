@@ -1,15 +1,17 @@
 //! External AI provider implementations
 //!
 //! This module contains implementations for various external AI providers
-//! including OpenAI, Anthropic, Groq, Gemini, Mistral, and custom providers.
+//! including OpenAI, Anthropic, Groq, Gemini, Mistral, Ollama, llama.cpp and custom providers.
 
 pub mod anthropic;
 pub mod custom;
 pub mod deepseek;
 pub mod gemini;
 pub mod groq;
+pub mod llamacpp;
 pub mod local;
 pub mod mistral;
+pub mod ollama;
 pub mod openai;
 pub mod openai_compatible;
 pub mod openai_types;
@@ -19,8 +21,10 @@ pub use custom::*;
 pub use deepseek::*;
 pub use gemini::*;
 pub use groq::*;
+pub use llamacpp::LlamaCppProvider;
 pub use local::*;
 pub use mistral::*;
+pub use ollama::OllamaProvider;
 pub use openai::*;
 pub use openai_compatible::*;
 pub use openai_types::*;
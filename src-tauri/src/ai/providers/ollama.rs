@@ -0,0 +1,693 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::ai::core::provider_base::{build_http_client, send_with_retry};
+use crate::ai::core::providers::{
+    AIProvider, ChatMessage, ChatRequest, ChatResponse, ContentPart, DiscoveredModel,
+    LoadedModel, MessageContent, ProxyConfig, StreamingChunk, StreamingResponse, ToolCall,
+    ToolChoice, Usage,
+};
+use crate::ai::file_helpers::{get_file_content_for_local_provider, LocalProviderFileContent};
+use crate::ai::parameters::native_parameters;
+use crate::database::models::ModelCapabilities;
+use crate::database::queries::models::get_model_by_id;
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Provider for the native Ollama API. Talks to any reachable Ollama server, whether or not
+/// it was started by the app, and streams `/api/chat` as newline-delimited JSON.
+#[derive(Debug, Clone)]
+pub struct OllamaProvider {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    provider_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: Option<OllamaMessage>,
+    #[serde(default)]
+    done: bool,
+    done_reason: Option<String>,
+    prompt_eval_count: Option<u32>,
+    eval_count: Option<u32>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    content: String,
+    // Reasoning of thinking models, returned separately when `think` is set
+    #[serde(default)]
+    thinking: String,
+    tool_calls: Option<Vec<OllamaToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTag {
+    name: String,
+    details: Option<OllamaModelDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModelDetails {
+    family: Option<String>,
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaShowResponse {
+    #[serde(default)]
+    capabilities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaPsResponse {
+    #[serde(default)]
+    models: Vec<OllamaRunningModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaRunningModel {
+    name: String,
+    size_vram: Option<u64>,
+    expires_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl OllamaChatResponse {
+    fn usage(&self) -> Option<Usage> {
+        if self.prompt_eval_count.is_none() && self.eval_count.is_none() {
+            return None;
+        }
+        Some(Usage {
+            prompt_tokens: self.prompt_eval_count,
+            completion_tokens: self.eval_count,
+            total_tokens: Some(self.prompt_eval_count.unwrap_or(0) + self.eval_count.unwrap_or(0)),
+        })
+    }
+}
+
+impl From<OllamaToolCall> for ToolCall {
+    fn from(call: OllamaToolCall) -> Self {
+        // Ollama does not assign ids to tool calls
+        ToolCall {
+            id: format!("call_{}", Uuid::new_v4().simple()),
+            name: call.function.name,
+            arguments: call.function.arguments,
+        }
+    }
+}
+
+/// Ollama reports `stop` or `length`; tool calls finish the turn like OpenAI's `tool_calls`
+fn finish_reason(done_reason: Option<String>, has_tool_calls: bool) -> Option<String> {
+    if has_tool_calls {
+        Some("tool_calls".to_string())
+    } else {
+        Some(done_reason.unwrap_or_else(|| "stop".to_string()))
+    }
+}
+
+/// Map model parameters onto Ollama `options`, leaving unset values to the model defaults
+fn request_options(request: &ChatRequest) -> Map<String, Value> {
+    request
+        .parameters
+        .as_ref()
+        .map(|params| native_parameters(params, "ollama"))
+        .unwrap_or_default()
+}
+
+/// Ollama expects raw base64 images rather than data URLs
+fn strip_data_url(image: &str) -> &str {
+    image.split_once("base64,").map(|(_, data)| data).unwrap_or(image)
+}
+
+impl OllamaProvider {
+    pub fn new(
+        api_key: String,
+        base_url: Option<String>,
+        proxy_config: Option<ProxyConfig>,
+        provider_id: Uuid,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let base_url = base_url
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        // The native API lives at the server root, not under the OpenAI-compatible /v1 prefix
+        let base_url = base_url.trim_end_matches('/').trim_end_matches("/v1").to_string();
+        let client = build_http_client(&base_url, proxy_config.as_ref())?;
+
+        Ok(Self {
+            client,
+            api_key,
+            base_url,
+            provider_id,
+        })
+    }
+
+    /// Ollama has no authentication of its own, but is often put behind a proxy that does
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        send_with_retry(self.authorize(request), "Ollama").await
+    }
+
+    async fn convert_message(
+        &self,
+        message: &ChatMessage,
+        supports_vision: bool,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut content = Vec::new();
+        let mut images = Vec::new();
+
+        match &message.content {
+            MessageContent::Text(text) => content.push(text.clone()),
+            MessageContent::Multimodal(parts) => {
+                for part in parts {
+                    match part {
+                        ContentPart::Text(text) => content.push(text.clone()),
+                        ContentPart::FileReference(file_ref) => {
+                            match get_file_content_for_local_provider(file_ref, supports_vision).await {
+                                Ok(LocalProviderFileContent::ImageBase64(image)) => {
+                                    images.push(strip_data_url(&image).to_string());
+                                }
+                                Ok(LocalProviderFileContent::TextOnly(text)) => {
+                                    content.push(format!("File: {} - Content:\n{}", file_ref.filename, text.trim()));
+                                }
+                                Ok(LocalProviderFileContent::TextAndImages { text, images: pages }) => {
+                                    content.push(format!(
+                                        "File: {} - Document Text Content:\n{}",
+                                        file_ref.filename,
+                                        text.trim()
+                                    ));
+                                    if supports_vision {
+                                        images.extend(pages.iter().map(|page| strip_data_url(page).to_string()));
+                                    }
+                                }
+                                Ok(LocalProviderFileContent::FileInfo { filename, .. }) => {
+                                    content.push(format!("File: {} - File type not supported for content extraction", filename));
+                                }
+                                Err(e) => {
+                                    eprintln!("Warning: Failed to process file {}: {}", file_ref.filename, e);
+                                    content.push(format!("[File: {} - Could not process: {}]", file_ref.filename, e));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut ollama_message = json!({
+            "role": message.role,
+            "content": content.join("\n"),
+        });
+        if !images.is_empty() {
+            ollama_message["images"] = json!(images);
+        }
+        if let Some(tool_calls) = message.tool_calls.as_ref().filter(|calls| !calls.is_empty()) {
+            ollama_message["tool_calls"] = json!(tool_calls
+                .iter()
+                .map(|call| json!({
+                    "function": { "name": call.name, "arguments": call.arguments }
+                }))
+                .collect::<Vec<_>>());
+        }
+        Ok(ollama_message)
+    }
+
+    async fn build_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        // Capabilities are only needed to decide whether attached images can be sent
+        let has_attachments = request
+            .messages
+            .iter()
+            .any(|message| matches!(message.content, MessageContent::Multimodal(_)));
+        let supports_vision = if has_attachments {
+            match get_model_by_id(request.model_id).await {
+                Ok(Some(model)) => model.capabilities.and_then(|c| c.vision).unwrap_or(false),
+                _ => false,
+            }
+        } else {
+            false
+        };
+
+        let mut messages = Vec::with_capacity(request.messages.len());
+        for message in &request.messages {
+            messages.push(self.convert_message(message, supports_vision).await?);
+        }
+
+        let mut payload = json!({
+            "model": request.model_name,
+            "messages": messages,
+            "stream": stream,
+        });
+
+        let options = request_options(request);
+        if !options.is_empty() {
+            payload["options"] = Value::Object(options);
+        }
+
+        // Ollama has no thinking budget, only the switch
+        if let Some(params) = request
+            .parameters
+            .as_ref()
+            .filter(|p| p.enable_thinking.is_some() || p.thinking_budget.is_some())
+        {
+            payload["think"] = json!(params.thinking_enabled());
+        }
+
+        // Structured outputs take the JSON Schema itself as the format
+        if let Some(format) = &request.response_format {
+            payload["format"] = format.schema.clone();
+        }
+
+        // Ollama has no tool_choice, so tools are left out when the caller disables them
+        if let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) {
+            if request.tool_choice != Some(ToolChoice::None) {
+                payload["tools"] = json!(tools
+                    .iter()
+                    .map(|tool| json!({
+                        "type": "function",
+                        "function": {
+                            "name": tool.name,
+                            "description": tool.description.clone().unwrap_or_default(),
+                            "parameters": tool.parameters,
+                        }
+                    }))
+                    .collect::<Vec<_>>());
+            }
+        }
+
+        Ok(payload)
+    }
+
+    /// Turn the NDJSON body into a stream of chunks, merging every line in a network read.
+    /// Tool calls arrive whole and are held back until the final line.
+    fn parse_stream(response: reqwest::Response) -> StreamingResponse {
+        let buffer = Arc::new(Mutex::new(String::new()));
+        let tool_calls: Arc<Mutex<Vec<ToolCall>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let stream = response.bytes_stream().map(move |result| {
+            let bytes = result.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            let mut buffer_guard = buffer.lock().unwrap();
+            buffer_guard.push_str(&String::from_utf8_lossy(&bytes));
+            let mut tool_calls_guard = tool_calls.lock().unwrap();
+
+            let mut merged = StreamingChunk::empty();
+            while let Some(line_end) = buffer_guard.find('\n') {
+                let line = buffer_guard[..line_end].trim().to_string();
+                buffer_guard.drain(..=line_end);
+                if line.is_empty() {
+                    continue;
+                }
+
+                let stream_response = match serde_json::from_str::<OllamaChatResponse>(&line) {
+                    Ok(stream_response) => stream_response,
+                    Err(e) => {
+                        eprintln!("Failed to parse Ollama streaming response: {} for data: {}", e, line);
+                        continue;
+                    }
+                };
+                if let Some(error) = stream_response.error {
+                    return Err(format!("Ollama API error: {}", error).into());
+                }
+
+                let usage = stream_response.usage();
+                let mut chunk = StreamingChunk::empty();
+                if let Some(message) = stream_response.message {
+                    if !message.content.is_empty() {
+                        chunk.content = Some(message.content);
+                    }
+                    if !message.thinking.is_empty() {
+                        chunk.reasoning = Some(message.thinking);
+                    }
+                    tool_calls_guard.extend(message.tool_calls.unwrap_or_default().into_iter().map(ToolCall::from));
+                }
+                if stream_response.done {
+                    chunk.finish_reason = finish_reason(stream_response.done_reason, !tool_calls_guard.is_empty());
+                    if !tool_calls_guard.is_empty() {
+                        chunk.tool_calls = Some(std::mem::take(&mut *tool_calls_guard));
+                    }
+                    chunk.usage = usage;
+                }
+                merged.merge(chunk);
+            }
+
+            Ok(merged)
+        });
+
+        Box::pin(stream)
+    }
+
+    /// Ask the server which capabilities a model has; older servers do not report them
+    async fn model_capabilities(&self, name: &str) -> Option<Vec<String>> {
+        let response = self
+            .send(self.client.post(format!("{}/api/show", self.base_url)).json(&json!({ "model": name })))
+            .await
+            .ok()?;
+        let show: OllamaShowResponse = response.json().await.ok()?;
+        Some(show.capabilities).filter(|capabilities| !capabilities.is_empty())
+    }
+}
+
+#[async_trait]
+impl AIProvider for OllamaProvider {
+    async fn chat(
+        &self,
+        request: ChatRequest,
+    ) -> Result<ChatResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = self.build_request(&request, false).await?;
+        let response = self
+            .send(self.client.post(format!("{}/api/chat", self.base_url)).json(&payload))
+            .await?;
+
+        let api_response: OllamaChatResponse = response.json().await?;
+        if let Some(error) = api_response.error {
+            return Err(format!("Ollama API error: {}", error).into());
+        }
+
+        let usage = api_response.usage();
+        let message = api_response.message.ok_or("No message returned from Ollama API")?;
+        let tool_calls: Vec<ToolCall> = message
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+
+        Ok(ChatResponse {
+            content: message.content,
+            reasoning: Some(message.thinking).filter(|thinking| !thinking.is_empty()),
+            finish_reason: finish_reason(api_response.done_reason, !tool_calls.is_empty()),
+            tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+            usage,
+        })
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamingResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = self.build_request(&request, true).await?;
+        let response = self
+            .send(self.client.post(format!("{}/api/chat", self.base_url)).json(&payload))
+            .await?;
+
+        Ok(Self::parse_stream(response))
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        let tags: OllamaTagsResponse = self
+            .send(self.client.get(format!("{}/api/tags", self.base_url)))
+            .await?
+            .json()
+            .await?;
+
+        let mut models = Vec::with_capacity(tags.models.len());
+        for tag in tags.models {
+            let capabilities = self.model_capabilities(&tag.name).await;
+            // Embedding-only models cannot serve chat
+            if capabilities
+                .as_ref()
+                .is_some_and(|capabilities| !capabilities.iter().any(|c| c == "completion"))
+            {
+                continue;
+            }
+
+            let description = tag.details.map(|details| {
+                [details.family, details.parameter_size, details.quantization_level]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" · ")
+            });
+            models.push(DiscoveredModel {
+                name: tag.name,
+                display_name: None,
+                description: description.filter(|description| !description.is_empty()),
+                capabilities: capabilities.map(|capabilities| ModelCapabilities {
+                    vision: Some(capabilities.iter().any(|c| c == "vision")),
+                    tools: Some(capabilities.iter().any(|c| c == "tools")),
+                    ..Default::default()
+                }),
+            });
+        }
+
+        Ok(models)
+    }
+
+    async fn loaded_models(&self) -> Result<Vec<LoadedModel>, Box<dyn std::error::Error + Send + Sync>> {
+        let running: OllamaPsResponse = self
+            .send(self.client.get(format!("{}/api/ps", self.base_url)))
+            .await?
+            .json()
+            .await?;
+
+        Ok(running
+            .models
+            .into_iter()
+            .map(|model| LoadedModel {
+                name: model.name,
+                size_vram: model.size_vram,
+                expires_at: model.expires_at,
+            })
+            .collect())
+    }
+
+    async fn embed(
+        &self,
+        model_name: &str,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let response: OllamaEmbedResponse = self
+            .send(
+                self.client
+                    .post(format!("{}/api/embed", self.base_url))
+                    .json(&json!({ "model": model_name, "input": texts })),
+            )
+            .await?
+            .json()
+            .await?;
+        if response.embeddings.len() != texts.len() {
+            return Err(format!(
+                "Ollama returned {} embeddings for {} inputs",
+                response.embeddings.len(),
+                texts.len()
+            )
+            .into());
+        }
+
+        Ok(response.embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_helpers::spawn_mock_server;
+    use super::*;
+    use crate::database::models::model::ModelParameters;
+    use axum::{
+        extract::State,
+        routing::{get, post},
+        Json, Router,
+    };
+
+    type Requests = Arc<Mutex<Vec<Value>>>;
+
+    const STREAM_BODY: &str = concat!(
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n",
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"lo\"},\"done\":false}\n",
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",",
+        "\"prompt_eval_count\":12,\"eval_count\":2}\n",
+    );
+
+    async fn mock_ollama() -> (OllamaProvider, Requests) {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+
+        let router = Router::new()
+            .route(
+                "/api/chat",
+                post(|State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                    let stream = body["stream"].as_bool().unwrap_or(true);
+                    requests.lock().unwrap().push(body);
+                    if stream {
+                        STREAM_BODY.to_string()
+                    } else {
+                        json!({
+                            "message": {
+                                "role": "assistant",
+                                "content": "",
+                                "tool_calls": [{ "function": { "name": "get_weather", "arguments": { "city": "Paris" } } }]
+                            },
+                            "done": true,
+                            "done_reason": "stop",
+                            "prompt_eval_count": 20,
+                            "eval_count": 8,
+                        })
+                        .to_string()
+                    }
+                }),
+            )
+            .route(
+                "/api/tags",
+                get(|| async {
+                    Json(json!({ "models": [
+                        { "name": "llama3.2:3b", "details": { "family": "llama", "parameter_size": "3.2B", "quantization_level": "Q4_K_M" } },
+                        { "name": "nomic-embed-text:latest", "details": { "family": "nomic-bert" } },
+                    ] }))
+                }),
+            )
+            .route(
+                "/api/show",
+                post(|Json(body): Json<Value>| async move {
+                    let capabilities = if body["model"] == "llama3.2:3b" {
+                        json!(["completion", "tools"])
+                    } else {
+                        json!(["embedding"])
+                    };
+                    Json(json!({ "capabilities": capabilities }))
+                }),
+            )
+            .route(
+                "/api/ps",
+                get(|| async {
+                    Json(json!({ "models": [{ "name": "llama3.2:3b", "size_vram": 2048, "expires_at": "2026-01-01T00:05:00Z" }] }))
+                }),
+            )
+            .with_state(requests.clone());
+
+        let base_url = spawn_mock_server(router).await;
+        let provider = OllamaProvider::new(String::new(), Some(format!("{}/v1", base_url)), None, Uuid::nil()).unwrap();
+        (provider, requests)
+    }
+
+    fn chat_request() -> ChatRequest {
+        ChatRequest {
+            messages: vec![ChatMessage::text("user", "Hi")],
+            model_name: "llama3.2:3b".to_string(),
+            model_id: Uuid::nil(),
+            provider_id: Uuid::nil(),
+            stream: true,
+            parameters: Some(ModelParameters {
+                num_ctx: Some(8192),
+                temperature: Some(0.5),
+                ..Default::default()
+            }),
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_reads_ndjson() {
+        let (provider, requests) = mock_ollama().await;
+
+        let mut stream = provider.chat_stream(chat_request()).await.unwrap();
+        let mut merged = StreamingChunk::empty();
+        while let Some(chunk) = stream.next().await {
+            merged.merge(chunk.unwrap());
+        }
+
+        assert_eq!(merged.content.as_deref(), Some("Hello"));
+        assert_eq!(merged.finish_reason.as_deref(), Some("stop"));
+        let usage = merged.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, Some(12));
+        assert_eq!(usage.completion_tokens, Some(2));
+
+        let body = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(body["options"], json!({ "num_ctx": 8192, "temperature": 0.5 }));
+    }
+
+    #[tokio::test]
+    async fn test_chat_returns_tool_calls() {
+        let (provider, _) = mock_ollama().await;
+
+        let response = provider
+            .chat(ChatRequest {
+                stream: false,
+                ..chat_request()
+            })
+            .await
+            .unwrap();
+
+        let tool_calls = response.tool_calls.unwrap();
+        assert_eq!(tool_calls[0].name, "get_weather");
+        assert_eq!(tool_calls[0].arguments, json!({ "city": "Paris" }));
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(response.usage.unwrap().total_tokens, Some(28));
+    }
+
+    #[tokio::test]
+    async fn test_list_models_skips_embedding_models() {
+        let (provider, _) = mock_ollama().await;
+
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "llama3.2:3b");
+        assert_eq!(models[0].description.as_deref(), Some("llama · 3.2B · Q4_K_M"));
+        assert_eq!(models[0].capabilities.as_ref().unwrap().tools, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_loaded_models() {
+        let (provider, _) = mock_ollama().await;
+
+        let loaded = provider.loaded_models().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "llama3.2:3b");
+        assert_eq!(loaded[0].size_vram, Some(2048));
+    }
+}
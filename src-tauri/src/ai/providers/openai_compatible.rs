@@ -2,14 +2,21 @@ use async_trait::async_trait;
 use futures_util::StreamExt;
 use reqwest::Client;
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
-use crate::ai::core::provider_base::build_http_client;
+use crate::ai::core::provider_base::{build_http_client, send_with_retry};
 use crate::ai::core::providers::{
-    AIProvider, ChatRequest, ChatResponse, ProxyConfig, StreamingChunk, StreamingResponse, Usage,
+    AIProvider, ChatMessage, ChatRequest, ChatResponse, DiscoveredModel, ProxyConfig,
+    StreamingChunk, StreamingResponse, Usage,
 };
+use crate::ai::parameters::native_parameters;
+use crate::ai::providers::openai_types::ModelInfo;
+use crate::ai::structured_output::schema_instructions;
+use crate::database::models::chat::{ResponseFormat, ToolCall, ToolChoice, ToolDefinition};
+use crate::database::models::{ModelCapabilities, ModelParameters};
 
 #[derive(Debug, Clone)]
 pub struct OpenAICompatibleProvider {
@@ -34,7 +41,37 @@ struct OpenAICompatibleChoice {
 
 #[derive(Debug, Deserialize)]
 struct OpenAICompatibleMessage {
-    content: String,
+    content: Option<String>,
+    #[serde(flatten)]
+    reasoning: ReasoningFields,
+    tool_calls: Option<Vec<OpenAICompatibleToolCall>>,
+}
+
+/// Reasoning text is `reasoning_content` on DeepSeek and llama.cpp, `reasoning` on some others
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ReasoningFields {
+    reasoning_content: Option<String>,
+    reasoning: Option<String>,
+}
+
+impl ReasoningFields {
+    pub(crate) fn into_text(self) -> Option<String> {
+        self.reasoning_content
+            .or(self.reasoning)
+            .filter(|text| !text.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAICompatibleToolCall {
+    id: String,
+    function: OpenAICompatibleFunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAICompatibleFunctionCall {
+    name: String,
+    arguments: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -44,9 +81,29 @@ struct OpenAICompatibleUsage {
     total_tokens: Option<u32>,
 }
 
+impl From<OpenAICompatibleUsage> for Usage {
+    fn from(usage: OpenAICompatibleUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenAICompatibleStreamResponse {
+    #[serde(default)]
     choices: Vec<OpenAICompatibleStreamChoice>,
+    // Sent on the last chunk when stream_options.include_usage is set
+    usage: Option<OpenAICompatibleUsage>,
+    // Groq reports streaming usage under its own extension field
+    x_groq: Option<GroqStreamExtension>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroqStreamExtension {
+    usage: Option<OpenAICompatibleUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,6 +115,234 @@ struct OpenAICompatibleStreamChoice {
 #[derive(Debug, Deserialize)]
 struct OpenAICompatibleStreamDelta {
     content: Option<String>,
+    #[serde(flatten)]
+    reasoning: ReasoningFields,
+    tool_calls: Option<Vec<OpenAICompatibleToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAICompatibleModelList {
+    data: Vec<OpenAICompatibleModel>,
+}
+
+/// An entry of `GET /models`. Mistral adds a name, description and capabilities to the
+/// OpenAI fields.
+#[derive(Debug, Deserialize)]
+struct OpenAICompatibleModel {
+    #[serde(flatten)]
+    info: ModelInfo,
+    name: Option<String>,
+    description: Option<String>,
+    capabilities: Option<MistralModelCapabilities>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MistralModelCapabilities {
+    completion_chat: Option<bool>,
+    function_calling: Option<bool>,
+    vision: Option<bool>,
+}
+
+// Model ids of OpenAI and Groq endpoints that do not serve chat completions
+const NON_CHAT_MODEL_MARKERS: &[&str] = &[
+    "embedding",
+    "whisper",
+    "tts",
+    "dall-e",
+    "gpt-image",
+    "moderation",
+    "transcribe",
+    "realtime",
+];
+
+fn is_chat_model(model: &OpenAICompatibleModel) -> bool {
+    if let Some(capabilities) = &model.capabilities {
+        return capabilities.completion_chat.unwrap_or(true);
+    }
+    let id = model.info.id.to_lowercase();
+    !NON_CHAT_MODEL_MARKERS.iter().any(|marker| id.contains(marker))
+}
+
+impl From<OpenAICompatibleModel> for DiscoveredModel {
+    fn from(model: OpenAICompatibleModel) -> Self {
+        DiscoveredModel {
+            // Mistral repeats the id as the name, which is not a better display name
+            display_name: model.name.filter(|name| *name != model.info.id),
+            description: model.description.filter(|description| !description.is_empty()),
+            capabilities: model.capabilities.map(|capabilities| ModelCapabilities {
+                vision: capabilities.vision,
+                tools: capabilities.function_calling,
+                ..Default::default()
+            }),
+            name: model.info.id,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAICompatibleEmbeddings {
+    data: Vec<OpenAICompatibleEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAICompatibleEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Read an OpenAI `/embeddings` response, returning the vectors in input order
+pub(crate) async fn read_embeddings_response(
+    response: reqwest::Response,
+    expected: usize,
+) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut embeddings: OpenAICompatibleEmbeddings = response.json().await?;
+    if embeddings.data.len() != expected {
+        return Err(format!(
+            "Embedding API returned {} vectors for {} inputs",
+            embeddings.data.len(),
+            expected
+        )
+        .into());
+    }
+
+    embeddings.data.sort_by_key(|data| data.index);
+    Ok(embeddings.data.into_iter().map(|data| data.embedding).collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAICompatibleToolCallDelta {
+    index: usize,
+    id: Option<String>,
+    function: Option<OpenAICompatibleFunctionCallDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenAICompatibleFunctionCallDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+/// Accumulates streamed tool call fragments (keyed by index) until the stream finishes
+#[derive(Debug, Default)]
+pub(crate) struct ToolCallAccumulator {
+    calls: BTreeMap<usize, (String, String, String)>, // index -> (id, name, arguments)
+}
+
+impl ToolCallAccumulator {
+    pub(crate) fn push(&mut self, delta: OpenAICompatibleToolCallDelta) {
+        let entry = self.calls.entry(delta.index).or_default();
+        if let Some(id) = delta.id {
+            entry.0 = id;
+        }
+        if let Some(function) = delta.function {
+            if let Some(name) = function.name {
+                entry.1.push_str(&name);
+            }
+            if let Some(arguments) = function.arguments {
+                entry.2.push_str(&arguments);
+            }
+        }
+    }
+
+    /// Drain the accumulated calls, returning None when nothing was streamed
+    pub(crate) fn take(&mut self) -> Option<Vec<ToolCall>> {
+        if self.calls.is_empty() {
+            return None;
+        }
+        let calls = std::mem::take(&mut self.calls)
+            .into_values()
+            .map(|(id, name, arguments)| ToolCall::from_raw_arguments(id, name, &arguments))
+            .collect();
+        Some(calls)
+    }
+}
+
+/// Convert tool definitions into the OpenAI `tools` array
+pub(crate) fn tools_to_openai(tools: &[ToolDefinition]) -> Value {
+    json!(tools
+        .iter()
+        .map(|tool| json!({
+            "type": "function",
+            "function": {
+                "name": tool.name,
+                "description": tool.description.clone().unwrap_or_default(),
+                "parameters": tool.parameters,
+            }
+        }))
+        .collect::<Vec<_>>())
+}
+
+/// Convert a tool choice into the OpenAI `tool_choice` value
+pub(crate) fn tool_choice_to_openai(choice: &ToolChoice) -> Value {
+    match choice {
+        ToolChoice::Auto => json!("auto"),
+        ToolChoice::None => json!("none"),
+        ToolChoice::Required => json!("required"),
+        ToolChoice::Function { name } => json!({
+            "type": "function",
+            "function": { "name": name }
+        }),
+    }
+}
+
+/// Convert tool calls into the OpenAI assistant message `tool_calls` array
+pub(crate) fn tool_calls_to_openai(tool_calls: &[ToolCall]) -> Value {
+    json!(tool_calls
+        .iter()
+        .map(|call| json!({
+            "id": call.id,
+            "type": "function",
+            "function": {
+                "name": call.name,
+                "arguments": call.arguments_string(),
+            }
+        }))
+        .collect::<Vec<_>>())
+}
+
+/// Convert a chat message into an OpenAI-compatible message, including tool fields
+pub(crate) fn message_to_openai(message: &ChatMessage, content: Value) -> Value {
+    let mut openai_message = json!({
+        "role": message.role,
+        "content": content,
+    });
+    if let Some(tool_calls) = message.tool_calls.as_ref().filter(|calls| !calls.is_empty()) {
+        openai_message["tool_calls"] = tool_calls_to_openai(tool_calls);
+    }
+    if let Some(tool_call_id) = &message.tool_call_id {
+        openai_message["tool_call_id"] = json!(tool_call_id);
+    }
+    openai_message
+}
+
+/// Add `tools` and `tool_choice` to an OpenAI-compatible payload when present
+pub(crate) fn add_tools_to_payload(payload: &mut Value, request: &ChatRequest) {
+    if let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) {
+        payload["tools"] = tools_to_openai(tools);
+        if let Some(tool_choice) = &request.tool_choice {
+            payload["tool_choice"] = tool_choice_to_openai(tool_choice);
+        }
+    }
+}
+
+/// Convert a response format into the OpenAI `response_format` value
+pub(crate) fn response_format_to_openai(format: &ResponseFormat) -> Value {
+    let mut json_schema = json!({
+        "name": format.name,
+        "schema": format.schema,
+        "strict": format.strict,
+    });
+    if let Some(description) = &format.description {
+        json_schema["description"] = json!(description);
+    }
+    json!({ "type": "json_schema", "json_schema": json_schema })
+}
+
+/// Chat templates of hybrid reasoning models (Qwen3 and others) read this flag
+fn add_template_thinking_flag(payload: &mut Value, params: &ModelParameters) {
+    if params.enable_thinking.is_some() || params.thinking_budget.is_some() {
+        payload["chat_template_kwargs"] = json!({ "enable_thinking": params.thinking_enabled() });
+    }
 }
 
 impl OpenAICompatibleProvider {
@@ -81,43 +366,83 @@ impl OpenAICompatibleProvider {
     }
 
     fn build_request(&self, request: &ChatRequest, stream: bool) -> serde_json::Value {
-        let params = request.parameters.as_ref();
+        let mut messages = request
+            .messages
+            .iter()
+            .map(|message| message_to_openai(message, json!(message.content.to_plain_text())))
+            .collect::<Vec<_>>();
+
+        // Without schema support the format is described in the prompt instead
+        if let Some(format) = request.response_format.as_ref().filter(|_| !self.supports_json_schema()) {
+            messages.insert(0, json!({ "role": "system", "content": schema_instructions(format) }));
+        }
+
         let mut payload = json!({
             "model": request.model_name,
-            "messages": request.messages,
-            "temperature": params.and_then(|p| p.temperature).unwrap_or(0.7),
-            "max_tokens": params.and_then(|p| p.max_tokens).unwrap_or(4096),
-            "top_p": params.and_then(|p| p.top_p).unwrap_or(0.95),
-            "frequency_penalty": params.and_then(|p| p.frequency_penalty).unwrap_or(0.0),
-            "presence_penalty": params.and_then(|p| p.presence_penalty).unwrap_or(0.0),
+            "messages": messages,
             "stream": stream
         });
 
-        // Add optional parameters if present
-        if let Some(params) = params {
-            if let Some(seed) = params.seed {
-                payload["seed"] = json!(seed);
+        // Only parameters that were set are sent, so the server's own defaults apply otherwise
+        if let Some(params) = &request.parameters {
+            for (name, value) in native_parameters(params, self.provider_name) {
+                payload[name] = value;
             }
-            if let Some(stop) = &params.stop {
-                payload["stop"] = json!(stop);
+            if self.supports_template_thinking_flag() {
+                add_template_thinking_flag(&mut payload, params);
             }
         }
 
+        add_tools_to_payload(&mut payload, request);
+
+        if let Some(format) = &request.response_format {
+            payload["response_format"] = if self.supports_json_schema() {
+                response_format_to_openai(format)
+            } else {
+                json!({ "type": "json_object" })
+            };
+        }
+
+        if stream && self.supports_stream_usage() {
+            payload["stream_options"] = json!({ "include_usage": true });
+        }
+
         payload
     }
 
+    /// Whether the API accepts `stream_options.include_usage`. Other servers may reject the
+    /// field; Mistral and Groq report streaming usage without it.
+    fn supports_stream_usage(&self) -> bool {
+        matches!(self.provider_name, "openai" | "deepseek")
+    }
+
+    /// Whether the API accepts `response_format` of type `json_schema`. DeepSeek only has
+    /// the plain JSON mode.
+    fn supports_json_schema(&self) -> bool {
+        self.provider_name != "deepseek"
+    }
+
+    /// Whether the server renders the chat template itself and accepts template arguments
+    fn supports_template_thinking_flag(&self) -> bool {
+        self.provider_name == "llamacpp"
+    }
+
     fn get_endpoint_url(&self) -> String {
+        self.api_url("chat/completions")
+    }
+
+    fn api_url(&self, path: &str) -> String {
         // Handle different endpoint patterns
         if self.base_url.contains("/v1") || self.base_url.contains("/openai") {
-            format!("{}/chat/completions", self.base_url)
+            format!("{}/{}", self.base_url, path)
         } else {
-            format!("{}/v1/chat/completions", self.base_url)
+            format!("{}/v1/{}", self.base_url, path)
         }
     }
 
     fn should_include_auth(&self) -> bool {
-        // Custom providers might not need auth if running locally
-        self.provider_name != "custom" || !self.api_key.is_empty()
+        // Custom and llama.cpp providers might not need auth if running locally
+        !matches!(self.provider_name, "custom" | "llamacpp") || !self.api_key.is_empty()
     }
 }
 
@@ -140,24 +465,30 @@ impl AIProvider for OpenAICompatibleProvider {
             req_builder = req_builder.header("Authorization", format!("Bearer {}", self.api_key));
         }
 
-        let response = req_builder.send().await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("{} API error: {}", self.provider_name, error_text).into());
-        }
+        let response = send_with_retry(req_builder, self.provider_name).await?;
 
         let api_response: OpenAICompatibleResponse = response.json().await?;
 
         if let Some(choice) = api_response.choices.into_iter().next() {
+            let tool_calls = choice.message.tool_calls.map(|calls| {
+                calls
+                    .into_iter()
+                    .map(|call| {
+                        ToolCall::from_raw_arguments(
+                            call.id,
+                            call.function.name,
+                            &call.function.arguments,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            });
+
             Ok(ChatResponse {
-                content: choice.message.content,
+                content: choice.message.content.unwrap_or_default(),
+                reasoning: choice.message.reasoning.into_text(),
                 finish_reason: choice.finish_reason,
-                usage: api_response.usage.map(|u| Usage {
-                    prompt_tokens: u.prompt_tokens,
-                    completion_tokens: u.completion_tokens,
-                    total_tokens: u.total_tokens,
-                }),
+                usage: api_response.usage.map(Usage::from),
+                tool_calls: tool_calls.filter(|calls| !calls.is_empty()),
             })
         } else {
             Err(format!("No choices returned from {} API", self.provider_name).into())
@@ -181,27 +512,24 @@ impl AIProvider for OpenAICompatibleProvider {
             req_builder = req_builder.header("Authorization", format!("Bearer {}", self.api_key));
         }
 
-        let response = req_builder.send().await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("{} API error: {}", self.provider_name, error_text).into());
-        }
+        let response = send_with_retry(req_builder, self.provider_name).await?;
 
         // Create a buffer to accumulate partial SSE chunks
         let buffer = Arc::new(Mutex::new(String::new()));
+        let tool_calls = Arc::new(Mutex::new(ToolCallAccumulator::default()));
         let provider_name = self.provider_name;
 
         let stream = response.bytes_stream().map(move |result| {
             let buffer = buffer.clone();
+            let tool_calls = tool_calls.clone();
             match result {
                 Ok(bytes) => {
                     let chunk = String::from_utf8_lossy(&bytes);
                     let mut buffer_guard = buffer.lock().unwrap();
                     buffer_guard.push_str(&chunk);
 
-                    // Process complete lines from buffer
-                    let mut result = None;
+                    // Process complete lines from buffer, merging every event in this read
+                    let mut merged = StreamingChunk::empty();
                     while let Some(line_end) = buffer_guard.find('\n') {
                         let line = buffer_guard[..line_end].trim().to_string();
                         buffer_guard.drain(..=line_end);
@@ -213,13 +541,37 @@ impl AIProvider for OpenAICompatibleProvider {
                         if let Some(data) = line.strip_prefix("data: ") {
                             match serde_json::from_str::<OpenAICompatibleStreamResponse>(data) {
                                 Ok(stream_response) => {
+                                    let usage = stream_response
+                                        .usage
+                                        .or(stream_response.x_groq.and_then(|x| x.usage));
+                                    if let Some(usage) = usage {
+                                        merged.merge(StreamingChunk {
+                                            usage: Some(usage.into()),
+                                            ..StreamingChunk::empty()
+                                        });
+                                    }
+
                                     if let Some(choice) = stream_response.choices.into_iter().next()
                                     {
-                                        result = Some(Ok(StreamingChunk {
+                                        let mut tool_calls_guard = tool_calls.lock().unwrap();
+                                        for delta in choice.delta.tool_calls.unwrap_or_default() {
+                                            tool_calls_guard.push(delta);
+                                        }
+
+                                        // Tool calls are only complete once the choice finishes
+                                        let completed_tool_calls = if choice.finish_reason.is_some() {
+                                            tool_calls_guard.take()
+                                        } else {
+                                            None
+                                        };
+
+                                        merged.merge(StreamingChunk {
                                             content: choice.delta.content,
+                                            reasoning: choice.delta.reasoning.into_text(),
                                             finish_reason: choice.finish_reason,
-                                        }));
-                                        break;
+                                            tool_calls: completed_tool_calls,
+                                            usage: None,
+                                        });
                                     }
                                 }
                                 Err(e) => {
@@ -232,10 +584,7 @@ impl AIProvider for OpenAICompatibleProvider {
                         }
                     }
 
-                    result.unwrap_or(Ok(StreamingChunk {
-                        content: None,
-                        finish_reason: None,
-                    }))
+                    Ok(merged)
                 }
                 Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>),
             }
@@ -247,4 +596,54 @@ impl AIProvider for OpenAICompatibleProvider {
     fn provider_name(&self) -> &'static str {
         self.provider_name
     }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    async fn list_models(&self) -> Result<Vec<DiscoveredModel>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut req_builder = self.client.get(self.api_url("models"));
+        if self.should_include_auth() {
+            req_builder = req_builder.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+
+        let response = req_builder.send().await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("{} API error: {}", self.provider_name, error_text).into());
+        }
+
+        let models: OpenAICompatibleModelList = response.json().await?;
+        Ok(models
+            .data
+            .into_iter()
+            .filter(is_chat_model)
+            .map(DiscoveredModel::from)
+            .collect())
+    }
+
+    async fn embed(
+        &self,
+        model_name: &str,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut req_builder = self
+            .client
+            .post(self.api_url("embeddings"))
+            .header("Content-Type", "application/json")
+            .json(&json!({
+                "model": model_name,
+                "input": texts,
+            }));
+        if self.should_include_auth() {
+            req_builder = req_builder.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+
+        let response = send_with_retry(req_builder, self.provider_name).await?;
+        read_embeddings_response(response, texts.len()).await
+    }
 }
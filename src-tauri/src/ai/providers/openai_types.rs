@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
 /// OpenAI-compatible chat completion request
@@ -25,6 +25,11 @@ pub struct ChatCompletionRequest {
     pub presence_penalty: Option<f32>,
     #[serde(default)]
     pub user: Option<String>,
+    /// Accepted so the request parses; the gateway refuses tool calling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
 }
 
 fn default_model() -> String {
@@ -34,11 +39,38 @@ fn default_model() -> String {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String, // "system", "user", "assistant"
+    #[serde(default, deserialize_with = "deserialize_message_content")]
     pub content: String,
     #[serde(default)]
     pub name: Option<String>,
 }
 
+/// Accept content as a string or as an array of text parts. Other parts, such as images,
+/// are refused rather than dropped so the model never answers without them unnoticed.
+fn deserialize_message_content<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(text) => Ok(text),
+        serde_json::Value::Null => Ok(String::new()),
+        serde_json::Value::Array(parts) => {
+            let mut texts = Vec::with_capacity(parts.len());
+            for part in &parts {
+                match part.get("type").and_then(|kind| kind.as_str()) {
+                    Some("text") => texts.push(part.get("text").and_then(|text| text.as_str()).unwrap_or_default()),
+                    Some(kind) => {
+                        return Err(D::Error::custom(format!(
+                            "unsupported content part type '{}'; only text parts are supported",
+                            kind
+                        )))
+                    }
+                    None => return Err(D::Error::custom("content part is missing 'type'")),
+                }
+            }
+            Ok(texts.join("\n"))
+        }
+        other => Err(D::Error::custom(format!("invalid message content: {}", other))),
+    }
+}
+
 /// OpenAI-compatible chat completion response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatCompletionResponse {
@@ -80,6 +112,8 @@ pub struct ChatMessageDelta {
     pub role: Option<String>,
     #[serde(default)]
     pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,15 +160,70 @@ pub struct CompletionChoice {
     pub finish_reason: Option<String>,
 }
 
+/// Embeddings request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(default)]
+    pub encoding_format: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    Single(String),
+    Batch(Vec<String>),
+}
+
+impl EmbeddingInput {
+    pub fn into_texts(self) -> Vec<String> {
+        match self {
+            EmbeddingInput::Single(text) => vec![text],
+            EmbeddingInput::Batch(texts) => texts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    pub object: String,
+    pub data: Vec<EmbeddingData>,
+    pub model: String,
+    pub usage: EmbeddingUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingData {
+    pub object: String,
+    pub index: i32,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: i32,
+    pub total_tokens: i32,
+}
+
 /// Model information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     pub id: String,
+    // Vendors omit some of these fields, so they default when listing remote models
+    #[serde(default)]
     pub object: String,
+    #[serde(default)]
     pub created: i64,
+    #[serde(default)]
     pub owned_by: String,
+    #[serde(default)]
     pub permission: Vec<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
     pub root: String,
+    #[serde(default)]
     pub parent: Option<String>,
 }
 
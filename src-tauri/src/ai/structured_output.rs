@@ -0,0 +1,153 @@
+//! JSON Schema response formats
+//!
+//! Providers constrain generation to the schema where they can, but not all of them enforce
+//! it, so the final message is validated here before it is stored.
+
+use serde_json::Value;
+
+use crate::ai::core::ResponseFormat;
+
+/// Corrective requests sent after an answer that does not match the schema
+pub const MAX_SCHEMA_RETRIES: usize = 1;
+
+/// Check a response format before it is stored or sent to a provider
+pub fn check_response_format(format: &ResponseFormat) -> Result<(), String> {
+    let valid_name = !format.name.is_empty()
+        && format.name.len() <= 64
+        && format
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_name {
+        return Err(
+            "Response format name must be 1-64 letters, digits, underscores or dashes".to_string(),
+        );
+    }
+
+    // Tool-forcing and strict modes only accept an object at the root
+    if format.schema.get("type").and_then(Value::as_str) != Some("object") {
+        return Err("Response format schema must describe an object".to_string());
+    }
+
+    jsonschema::validator_for(&format.schema)
+        .map_err(|e| format!("Invalid response format schema: {}", e))?;
+    Ok(())
+}
+
+/// Validate a final message against the schema. Returns the JSON text to store, without any
+/// Markdown code fence the model wrapped it in, or the list of problems found.
+pub fn validate_response(format: &ResponseFormat, content: &str) -> Result<String, Vec<String>> {
+    let json_text = strip_code_fence(content);
+    let instance: Value = serde_json::from_str(json_text)
+        .map_err(|e| vec![format!("Response is not valid JSON: {}", e)])?;
+
+    let validator = jsonschema::validator_for(&format.schema)
+        .map_err(|e| vec![format!("Invalid response format schema: {}", e)])?;
+    let errors: Vec<String> = validator
+        .iter_errors(&instance)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            if path.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}: {}", path, error)
+            }
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(json_text.to_string())
+    } else {
+        Err(errors)
+    }
+}
+
+/// System prompt text describing the format, for providers without native schema support
+pub fn schema_instructions(format: &ResponseFormat) -> String {
+    let mut instructions = String::from(
+        "Respond only with a JSON object, without any other text, that matches this JSON Schema:\n",
+    );
+    instructions.push_str(&format.schema.to_string());
+    if let Some(description) = &format.description {
+        instructions.push_str("\n\n");
+        instructions.push_str(description);
+    }
+    instructions
+}
+
+/// Follow-up user message asking the model to correct an answer that failed validation
+pub fn correction_prompt(errors: &[String]) -> String {
+    format!(
+        "Your previous answer does not match the required JSON Schema:\n- {}\n\nRespond again with only the corrected JSON.",
+        errors.join("\n- ")
+    )
+}
+
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    let Some(inner) = trimmed
+        .strip_prefix("```")
+        .and_then(|inner| inner.strip_suffix("```"))
+    else {
+        return trimmed;
+    };
+
+    // Drop the language tag on the opening line
+    match inner.split_once('\n') {
+        Some((tag, rest)) if tag.trim().chars().all(|c| c.is_ascii_alphanumeric()) => rest.trim(),
+        _ => inner.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invoice_format() -> ResponseFormat {
+        ResponseFormat {
+            name: "invoice".to_string(),
+            description: None,
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "number": { "type": "string" },
+                    "total": { "type": "number" }
+                },
+                "required": ["number", "total"]
+            }),
+            strict: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_response_strips_code_fence() {
+        let content = "```json\n{\"number\": \"INV-7\", \"total\": 12.5}\n```";
+        assert_eq!(
+            validate_response(&invoice_format(), content).unwrap(),
+            "{\"number\": \"INV-7\", \"total\": 12.5}"
+        );
+    }
+
+    #[test]
+    fn test_validate_response_reports_schema_errors() {
+        let errors = validate_response(&invoice_format(), "{\"number\": 7}").unwrap_err();
+        assert_eq!(errors.len(), 2);
+
+        let errors = validate_response(&invoice_format(), "The total is 12.5").unwrap_err();
+        assert!(errors[0].starts_with("Response is not valid JSON"));
+    }
+
+    #[test]
+    fn test_check_response_format() {
+        assert!(check_response_format(&invoice_format()).is_ok());
+
+        let mut format = invoice_format();
+        format.name = "invoice fields".to_string();
+        assert!(check_response_format(&format).is_err());
+
+        let mut format = invoice_format();
+        format.schema = json!({ "type": "array" });
+        assert!(check_response_format(&format).is_err());
+    }
+}
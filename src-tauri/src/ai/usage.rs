@@ -0,0 +1,265 @@
+//! Token usage accounting and monthly quotas
+//!
+//! Usage reported by a provider is recorded for every chat and gateway request. When a provider
+//! does not report some of the counts they are estimated locally, so quotas still apply to
+//! providers without usage reporting.
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::ai::core::{ChatMessage, Usage};
+use crate::database::models::{RecordTokenUsageRequest, UsageTotals};
+use crate::database::queries::{usage, user_groups};
+
+// Formatting tokens added per message and to prime the reply, as in the OpenAI chat format
+const TOKENS_PER_MESSAGE: u32 = 4;
+const TOKENS_PER_REPLY: u32 = 3;
+// Average characters per token of a word for BPE tokenizers on English text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Prompt and completion tokens of one request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenCounts {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// Whether any count was estimated instead of reported by the provider
+    pub is_estimated: bool,
+}
+
+impl TokenCounts {
+    pub fn total_tokens(&self) -> u32 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// Estimate the tokens of a text without the model's tokenizer. Words cost one token per four
+/// characters, punctuation one token per character and non-Latin characters one token each.
+pub fn estimate_tokens(text: &str) -> u32 {
+    let mut tokens = 0usize;
+    let mut word_len = 0usize;
+
+    for c in text.chars() {
+        if c.is_alphanumeric() && c.is_ascii() {
+            word_len += 1;
+            continue;
+        }
+        tokens += word_len.div_ceil(CHARS_PER_TOKEN);
+        word_len = 0;
+        if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens += word_len.div_ceil(CHARS_PER_TOKEN);
+
+    tokens as u32
+}
+
+/// The longest prefix of `text` estimated to fit in `max_tokens`
+pub fn truncate_to_tokens(text: &str, max_tokens: u32) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+
+    // Binary search over character counts, as the estimate grows with the prefix
+    let chars: Vec<char> = text.chars().collect();
+    let (mut low, mut high) = (0, chars.len());
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        let prefix: String = chars[..mid].iter().collect();
+        if estimate_tokens(&prefix) <= max_tokens {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    chars[..low].iter().collect()
+}
+
+/// Estimate the tokens of one chat message, including its formatting
+pub fn estimate_message_tokens(message: &ChatMessage) -> u32 {
+    let tool_call_tokens: u32 = message
+        .tool_calls
+        .iter()
+        .flatten()
+        .map(|call| estimate_tokens(&call.name) + estimate_tokens(&call.arguments_string()))
+        .sum();
+    TOKENS_PER_MESSAGE
+        + estimate_tokens(&message.role)
+        + estimate_tokens(&message.content.to_plain_text())
+        + tool_call_tokens
+}
+
+/// Estimate the prompt tokens of a list of chat messages
+pub fn estimate_prompt_tokens(messages: &[ChatMessage]) -> u32 {
+    messages.iter().map(estimate_message_tokens).sum::<u32>() + TOKENS_PER_REPLY
+}
+
+/// Fill the counts a provider did not report with local estimates. The prompt estimate is
+/// taken up front because the request messages are moved into the provider call.
+pub fn resolve_token_counts(
+    reported: Option<&Usage>,
+    estimated_prompt_tokens: u32,
+    completion: &str,
+) -> TokenCounts {
+    let prompt_tokens = reported.and_then(|usage| usage.prompt_tokens);
+    // Some providers only report the total
+    let completion_tokens = reported.and_then(|usage| {
+        usage.completion_tokens.or_else(|| {
+            usage
+                .total_tokens
+                .zip(usage.prompt_tokens)
+                .map(|(total, prompt)| total.saturating_sub(prompt))
+        })
+    });
+
+    TokenCounts {
+        prompt_tokens: prompt_tokens.unwrap_or(estimated_prompt_tokens),
+        completion_tokens: completion_tokens.unwrap_or_else(|| estimate_tokens(completion)),
+        is_estimated: prompt_tokens.is_none() || completion_tokens.is_none(),
+    }
+}
+
+/// Record the tokens used by a provider request. Failures are logged and otherwise ignored
+/// so accounting never fails a response that was already generated.
+pub async fn record_token_usage(
+    user_id: Uuid,
+    message_id: Option<Uuid>,
+    provider_id: Uuid,
+    model_id: Uuid,
+    source: &'static str,
+    counts: TokenCounts,
+) {
+    let request = RecordTokenUsageRequest {
+        user_id,
+        message_id,
+        provider_id,
+        model_id,
+        source,
+        prompt_tokens: counts.prompt_tokens as i32,
+        completion_tokens: counts.completion_tokens as i32,
+        is_estimated: counts.is_estimated,
+    };
+
+    if let Err(e) = usage::record_token_usage(request).await {
+        eprintln!("Failed to record token usage for user {}: {}", user_id, e);
+    }
+}
+
+/// Start of the current quota period, the first day of the month in UTC
+pub fn quota_period_start() -> DateTime<Utc> {
+    let now = Utc::now();
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+fn exceeded(used: i64, limit: Option<i64>) -> bool {
+    limit.is_some_and(|limit| used >= limit)
+}
+
+/// Check the monthly quotas of the user's active groups before a provider request.
+/// Returns a message describing the first exceeded quota, or None when the request may proceed.
+pub async fn check_usage_quotas(user_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    let groups = user_groups::get_user_groups(user_id).await?;
+    let since = quota_period_start();
+    let mut user_totals: Option<UsageTotals> = None;
+
+    for group in groups {
+        let Some(quotas) = group.quotas.filter(|quotas| !quotas.is_empty()) else {
+            continue;
+        };
+
+        if quotas.user_monthly_tokens.is_some() || quotas.user_monthly_requests.is_some() {
+            let totals = match &user_totals {
+                Some(totals) => totals.clone(),
+                None => {
+                    let totals = usage::get_user_usage_totals(user_id, since).await?;
+                    user_totals = Some(totals.clone());
+                    totals
+                }
+            };
+            if exceeded(totals.total_tokens, quotas.user_monthly_tokens) {
+                return Ok(Some(format!(
+                    "Monthly token quota of group '{}' reached for this user",
+                    group.name
+                )));
+            }
+            if exceeded(totals.requests, quotas.user_monthly_requests) {
+                return Ok(Some(format!(
+                    "Monthly request quota of group '{}' reached for this user",
+                    group.name
+                )));
+            }
+        }
+
+        if quotas.monthly_tokens.is_some() || quotas.monthly_requests.is_some() {
+            let totals = usage::get_group_usage_totals(group.id, since).await?;
+            if exceeded(totals.total_tokens, quotas.monthly_tokens) {
+                return Ok(Some(format!(
+                    "Monthly token quota of group '{}' reached",
+                    group.name
+                )));
+            }
+            if exceeded(totals.requests, quotas.monthly_requests) {
+                return Ok(Some(format!(
+                    "Monthly request quota of group '{}' reached",
+                    group.name
+                )));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Hello, world!"), 6);
+        // Long words cost more than one token
+        assert_eq!(estimate_tokens("internationalization"), 5);
+        assert_eq!(estimate_tokens("你好"), 2);
+    }
+
+    #[test]
+    fn test_resolve_token_counts_prefers_reported_usage() {
+        let messages = vec![ChatMessage::text("user", "What is the capital of France?")];
+        let reported = Usage {
+            prompt_tokens: Some(14),
+            completion_tokens: Some(7),
+            total_tokens: Some(21),
+        };
+
+        let counts =
+            resolve_token_counts(Some(&reported), estimate_prompt_tokens(&messages), "Paris");
+        assert_eq!(counts.prompt_tokens, 14);
+        assert_eq!(counts.completion_tokens, 7);
+        assert!(!counts.is_estimated);
+    }
+
+    #[test]
+    fn test_resolve_token_counts_estimates_missing_usage() {
+        let messages = vec![ChatMessage::text("user", "What is the capital of France?")];
+
+        let counts = resolve_token_counts(None, estimate_prompt_tokens(&messages), "Paris");
+        assert_eq!(counts.completion_tokens, estimate_tokens("Paris"));
+        assert_eq!(counts.prompt_tokens, estimate_prompt_tokens(&messages));
+        assert!(counts.is_estimated);
+
+        // A reported total is split using the reported prompt tokens
+        let reported = Usage {
+            prompt_tokens: Some(14),
+            completion_tokens: None,
+            total_tokens: Some(20),
+        };
+        let counts =
+            resolve_token_counts(Some(&reported), estimate_prompt_tokens(&messages), "Paris");
+        assert_eq!(counts.completion_tokens, 6);
+        assert!(!counts.is_estimated);
+    }
+}
@@ -6,10 +6,15 @@ use axum::{
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::ai::context_window::ContextWindowSettings;
+use crate::ai::structured_output::check_response_format;
 use crate::api::middleware::AuthenticatedUser;
 use crate::database::{
-    models::{Assistant, AssistantListResponse, CreateAssistantRequest, UpdateAssistantRequest},
-    queries::assistants,
+    models::{
+        Assistant, AssistantListResponse, CreateAssistantRequest, FallbackTarget, RAGDatabase,
+        ResponseFormat, SetRAGDatabasesRequest, UpdateAssistantRequest,
+    },
+    queries::{assistants, models, rag_attachments},
 };
 
 #[derive(Debug, Deserialize)]
@@ -21,9 +26,12 @@ pub struct PaginationQuery {
 /// Create a new assistant
 pub async fn create_assistant(
     Extension(auth_user): Extension<AuthenticatedUser>,
-    Json(request): Json<CreateAssistantRequest>,
+    Json(mut request): Json<CreateAssistantRequest>,
 ) -> Result<Json<Assistant>, StatusCode> {
-    // Users can create their own assistants
+    // Users can create their own assistants, but fallback chains are for admins to define
+    request.fallback_chain = None;
+    validate_response_format(request.response_format.as_ref())?;
+    validate_context_window(request.context_window.as_ref())?;
     match assistants::create_assistant(request, Some(auth_user.user.id)).await {
         Ok(assistant) => Ok(Json(assistant)),
         Err(e) => {
@@ -40,6 +48,9 @@ pub async fn create_template_assistant(
 ) -> Result<Json<Assistant>, StatusCode> {
     // Only admins can create template assistants
     request.is_template = Some(true);
+    validate_fallback_chain(request.fallback_chain.as_deref()).await?;
+    validate_response_format(request.response_format.as_ref())?;
+    validate_context_window(request.context_window.as_ref())?;
     match assistants::create_assistant(request, Some(auth_user.user.id)).await {
         Ok(assistant) => Ok(Json(assistant)),
         Err(e) => {
@@ -117,8 +128,11 @@ pub async fn list_assistants_admin(
 pub async fn update_assistant(
     Extension(auth_user): Extension<AuthenticatedUser>,
     Path(assistant_id): Path<Uuid>,
-    Json(request): Json<UpdateAssistantRequest>,
+    Json(mut request): Json<UpdateAssistantRequest>,
 ) -> Result<Json<Assistant>, StatusCode> {
+    request.fallback_chain = None;
+    validate_response_format(request.response_format.as_ref())?;
+    validate_context_window(request.context_window.as_ref())?;
     match assistants::update_assistant(assistant_id, request, Some(auth_user.user.id), false).await
     {
         Ok(Some(assistant)) => Ok(Json(assistant)),
@@ -136,6 +150,9 @@ pub async fn update_assistant_admin(
     Path(assistant_id): Path<Uuid>,
     Json(request): Json<UpdateAssistantRequest>,
 ) -> Result<Json<Assistant>, StatusCode> {
+    validate_fallback_chain(request.fallback_chain.as_deref()).await?;
+    validate_response_format(request.response_format.as_ref())?;
+    validate_context_window(request.context_window.as_ref())?;
     match assistants::update_assistant(assistant_id, request, None, true).await {
         Ok(Some(assistant)) => Ok(Json(assistant)),
         Ok(None) => Err(StatusCode::NOT_FOUND),
@@ -189,3 +206,150 @@ pub async fn get_default_assistant(
         }
     }
 }
+
+/// Check that every requested id is an enabled RAG database, returning the deduplicated ids
+pub(crate) async fn validate_rag_database_ids(
+    mut rag_database_ids: Vec<Uuid>,
+) -> Result<Vec<Uuid>, StatusCode> {
+    rag_database_ids.sort();
+    rag_database_ids.dedup();
+
+    match rag_attachments::count_enabled_rag_databases(&rag_database_ids).await {
+        Ok(count) if count as usize == rag_database_ids.len() => Ok(rag_database_ids),
+        Ok(_) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            eprintln!("Error validating RAG databases: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Check that every fallback chain entry names an existing model of the given provider
+async fn validate_fallback_chain(chain: Option<&[FallbackTarget]>) -> Result<(), StatusCode> {
+    let Some(chain) = chain else {
+        return Ok(());
+    };
+
+    match models::fallback_chain_is_valid(chain).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            eprintln!("Error validating fallback chain: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+fn validate_response_format(format: Option<&ResponseFormat>) -> Result<(), StatusCode> {
+    match format.map(check_response_format) {
+        Some(Err(e)) => {
+            eprintln!("Rejected response format: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_context_window(settings: Option<&ContextWindowSettings>) -> Result<(), StatusCode> {
+    match settings.map(ContextWindowSettings::validate) {
+        Some(Err(e)) => {
+            eprintln!("Rejected context window settings: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn replace_assistant_rag_databases(
+    assistant_id: Uuid,
+    rag_database_ids: Vec<Uuid>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    let rag_database_ids = validate_rag_database_ids(rag_database_ids).await?;
+
+    if let Err(e) = rag_attachments::set_assistant_rag_databases(assistant_id, &rag_database_ids).await {
+        eprintln!("Error attaching RAG databases to assistant: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match rag_attachments::list_assistant_rag_databases(assistant_id).await {
+        Ok(databases) => Ok(Json(databases)),
+        Err(e) => {
+            eprintln!("Error listing assistant RAG databases: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// List RAG databases attached to an assistant
+pub async fn get_assistant_rag_databases(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Path(assistant_id): Path<Uuid>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    match assistants::get_assistant_by_id(assistant_id, Some(auth_user.user.id)).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Error getting assistant: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    match rag_attachments::list_assistant_rag_databases(assistant_id).await {
+        Ok(databases) => Ok(Json(databases)),
+        Err(e) => {
+            eprintln!("Error listing assistant RAG databases: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Replace the RAG databases attached to an assistant
+pub async fn set_assistant_rag_databases(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Path(assistant_id): Path<Uuid>,
+    Json(request): Json<SetRAGDatabasesRequest>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    // Users can only change their own assistants, not templates
+    match assistants::get_assistant_by_id(assistant_id, Some(auth_user.user.id)).await {
+        Ok(Some(assistant)) if assistant.created_by == Some(auth_user.user.id) && !assistant.is_template => {}
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Error getting assistant: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    replace_assistant_rag_databases(assistant_id, request.rag_database_ids).await
+}
+
+/// List RAG databases attached to an assistant (admin view)
+pub async fn get_assistant_rag_databases_admin(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+    Path(assistant_id): Path<Uuid>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    match rag_attachments::list_assistant_rag_databases(assistant_id).await {
+        Ok(databases) => Ok(Json(databases)),
+        Err(e) => {
+            eprintln!("Error listing assistant RAG databases (admin): {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Replace the RAG databases attached to an assistant (admin view)
+pub async fn set_assistant_rag_databases_admin(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+    Path(assistant_id): Path<Uuid>,
+    Json(request): Json<SetRAGDatabasesRequest>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    match assistants::get_assistant_by_id(assistant_id, None).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Error getting assistant (admin): {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    replace_assistant_rag_databases(assistant_id, request.rag_database_ids).await
+}
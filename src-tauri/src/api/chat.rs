@@ -5,35 +5,63 @@ use axum::{
   response::Sse,
   Extension, Json,
 };
+use chrono::{DateTime, Utc};
 use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::convert::Infallible;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use uuid::Uuid;
 
 use crate::ai::{
-  core::{AIProvider, ChatRequest, ProxyConfig},
+  context_window::{
+    effective_context_length, history_to_drop, leading_history, prompt_budget,
+    summary_metadata_key, summary_prompt, trim_history, CachedSummary, ContextStrategy,
+    ContextWindowReport, ContextWindowSettings, SUMMARY_MAX_TOKENS,
+  },
+  core::{
+    is_retryable_error, think_tags::ThinkTagSplitter, AIProvider, ChatMessage, ChatRequest,
+    ProxyConfig, ResponseFormat, ToolCall, ToolChoice, ToolDefinition, Usage,
+  },
+  parameters::{resolve_parameters, ResolvedParameters},
   providers::{
     anthropic::AnthropicProvider, custom::CustomProvider, deepseek::DeepSeekProvider,
-    gemini::GeminiProvider, groq::GroqProvider, local::LocalProvider,
-    mistral::MistralProvider, openai::OpenAIProvider,
+    gemini::GeminiProvider, groq::GroqProvider, llamacpp::LlamaCppProvider,
+    local::LocalProvider, mistral::MistralProvider, ollama::OllamaProvider,
+    openai::OpenAIProvider,
   },
+  structured_output::{
+    check_response_format, correction_prompt, validate_response, MAX_SCHEMA_RETRIES,
+  },
+  usage::{check_usage_quotas, estimate_prompt_tokens, record_token_usage, resolve_token_counts},
 };
 use crate::api::errors::ErrorCode;
 use crate::api::middleware::AuthenticatedUser;
-use crate::database::models::EditMessageRequest;
+use crate::database::models::{EditMessageRequest, FINISH_REASON_CANCELLED, USAGE_SOURCE_CHAT};
 use crate::database::{
   models::{
-    Conversation, ConversationListResponse, CreateConversationRequest,
-    Message, SaveMessageRequest, UpdateConversationRequest,
+    Conversation, ConversationListResponse, ConversationSearchFilters, ConversationSearchResponse,
+    CreateConversationRequest, FallbackTarget,
+    Message, Model, ModelParameters, Provider, RAGCitation, SaveMessageRequest,
+    SemanticSearchResponse, UpdateConversationRequest,
   },
   queries::{
     assistants::get_assistant_by_id,
-    chat,
+    chat, get_database_pool,
     models::{get_model_by_id, get_provider_by_model_id},
+    projects,
+    providers::get_provider_by_id,
+    user_group_providers::get_providers_for_user,
   },
 };
-use crate::utils::chat::{build_chat_messages, build_single_user_message};
+use crate::utils::cancellation::{
+  cancel_download, create_cancellation_token, remove_download_tracking, CancellationToken,
+};
+use crate::utils::chat::{build_chat_messages, build_single_user_message, ChatContext};
+
+/// Owners of in-flight assistant streams, keyed by the pre-allocated assistant message id
+static ACTIVE_STREAMS: once_cell::sync::Lazy<tokio::sync::RwLock<HashMap<Uuid, Uuid>>> =
+  once_cell::sync::Lazy::new(|| tokio::sync::RwLock::new(HashMap::new()));
 
 #[derive(Debug, Deserialize)]
 pub struct PaginationQuery {
@@ -48,6 +76,19 @@ pub struct SearchQuery {
   page: Option<i32>,
   per_page: Option<i32>,
   project_id: Option<String>,
+  /// Matches at or after this time
+  from: Option<DateTime<Utc>>,
+  /// Matches before this time
+  to: Option<DateTime<Utc>>,
+  assistant_id: Option<Uuid>,
+  model_id: Option<Uuid>,
+  role: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SemanticSearchQuery {
+  q: String,
+  limit: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,6 +98,71 @@ pub struct ChatMessageRequest {
   pub model_id: Uuid,
   pub assistant_id: Uuid,
   pub file_ids: Option<Vec<Uuid>>, // Optional file attachments
+  #[serde(default)]
+  pub tools: Option<Vec<ToolDefinition>>, // Client-side tools the model may call
+  #[serde(default)]
+  pub tool_choice: Option<ToolChoice>,
+  #[serde(default)]
+  pub response_format: Option<ResponseFormat>, // One-off structured output; replaces the assistant's
+  #[serde(default)]
+  pub parameters: Option<ModelParameters>, // Layered over the model's, assistant's and project's
+  #[serde(default)]
+  pub system_prompt_override: Option<String>, // Replaces the assistant's instructions
+}
+
+/// Most answers a single compare request may ask for
+const MAX_COMPARE_TARGETS: usize = 4;
+
+#[derive(Debug, Deserialize)]
+pub struct CompareTarget {
+  pub model_id: Uuid,
+  pub assistant_id: Uuid,
+  #[serde(default)]
+  pub parameters: Option<ModelParameters>,
+  #[serde(default)]
+  pub system_prompt_override: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareMessageRequest {
+  pub conversation_id: Uuid,
+  pub content: String,
+  pub file_ids: Option<Vec<Uuid>>,
+  pub targets: Vec<CompareTarget>, // Answered concurrently, each on its own branch
+  #[serde(default)]
+  pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RegenerateMessageRequest {
+  #[serde(default)]
+  pub model_id: Option<Uuid>, // Defaults to the model that wrote the answer
+  #[serde(default)]
+  pub assistant_id: Option<Uuid>, // Defaults to the conversation's assistant
+  #[serde(default)]
+  pub parameters: Option<ModelParameters>,
+  #[serde(default)]
+  pub system_prompt_override: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolResult {
+  pub tool_call_id: String,
+  pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolResultsRequest {
+  pub conversation_id: Uuid,
+  pub model_id: Uuid,
+  pub assistant_id: Uuid,
+  pub results: Vec<ToolResult>,
+  #[serde(default)]
+  pub tools: Option<Vec<ToolDefinition>>,
+  #[serde(default)]
+  pub tool_choice: Option<ToolChoice>,
+  #[serde(default)]
+  pub response_format: Option<ResponseFormat>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,6 +182,45 @@ pub struct StreamChunkData {
   pub message_id: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct StreamThinkingData {
+  pub delta: String,
+  pub message_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamMessageStartData {
+  pub message_id: String,
+  pub conversation_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamCitationsData {
+  pub message_id: String,
+  pub citations: Vec<RAGCitation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamFallbackData {
+  pub message_id: String,
+  pub provider_id: String,
+  pub model_id: String,
+  pub model_name: String,
+  pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamSchemaRetryData {
+  pub message_id: String,
+  pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamToolCallsData {
+  pub message_id: String,
+  pub tool_calls: Vec<ToolCall>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct StreamCompleteData {
   pub message_id: String,
@@ -86,6 +231,30 @@ pub struct StreamCompleteData {
   pub created_at: String,
   pub updated_at: String,
   pub total_tokens: Option<i32>,
+  pub finish_reason: Option<String>,
+  /// Parameters the provider was sent for this answer
+  pub parameters: ModelParameters,
+  /// Parameters that were set but are not supported by the provider
+  pub unsupported_parameters: Vec<String>,
+  /// History left out to fit the model's context window, when its context length is known
+  pub context: Option<ContextWindowReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamCompareStartData {
+  pub conversation_id: String,
+  pub user_message_id: String,
+  pub streams: Vec<CompareStreamData>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareStreamData {
+  /// SSE event id carried by every event of this stream
+  pub stream_id: String,
+  pub branch_id: String,
+  pub message_id: String,
+  pub model_id: String,
+  pub assistant_id: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -172,17 +341,32 @@ pub async fn delete_conversation(
   }
 }
 
+/// Where a streamed answer is saved. The default appends it to the active branch
+#[derive(Debug, Clone, Copy, Default)]
+struct AnswerTarget {
+  /// Branch the answer is read from and saved on
+  branch_id: Option<Uuid>,
+  /// Pre-allocated id for the answer
+  message_id: Option<Uuid>,
+  /// Answer this one is an alternative of
+  originated_from_id: Option<Uuid>,
+  /// Saved user message the answer replies to; it is sent with the request's files instead
+  /// of from the history
+  user_message_id: Option<Uuid>,
+}
+
 /// Common streaming function for AI responses
 async fn stream_ai_response(
   tx: tokio::sync::mpsc::UnboundedSender<Result<Event, Infallible>>,
   request: ChatMessageRequest,
   user_id: Uuid,
+  target: AnswerTarget,
 ) {
   // IMPORTANT: Capture the conversation's active branch immediately to prevent
   // race conditions if the user switches branches during streaming
-  let active_branch_id = match chat::get_conversation_by_id(request.conversation_id, user_id).await {
-    Ok(Some(conversation)) => match conversation.active_branch_id {
-      Some(branch_id) => branch_id,
+  let (active_branch_id, project_id) = match chat::get_conversation_by_id(request.conversation_id, user_id).await {
+    Ok(Some(conversation)) => match target.branch_id.or(conversation.active_branch_id) {
+      Some(branch_id) => (branch_id, conversation.project_id),
       None => {
         let _ = tx.send(Ok(Event::default().event("error").data(
           &serde_json::to_string(&StreamErrorData {
@@ -285,8 +469,8 @@ async fn stream_ai_response(
   };
 
   // Build chat messages for AI provider using utility function
-  let messages = match build_chat_messages(&request, user_id).await {
-    Ok(messages) => messages,
+  let ChatContext { messages, citations } = match build_chat_messages(&request, user_id, Some(active_branch_id), target.user_message_id).await {
+    Ok(context) => context,
     Err(e) => {
       let _ = tx.send(Ok(Event::default().event("error").data(
         &serde_json::to_string(&StreamErrorData {
@@ -300,20 +484,82 @@ async fn stream_ai_response(
   };
 
   // Get assistant parameters for model configuration
-  let assistant_params = if let Ok(Some(assistant)) =
-    get_assistant_by_id(request.assistant_id, Some(user_id)).await
-  {
-    assistant.parameters.clone()
-  } else {
-    None
+  let assistant = get_assistant_by_id(request.assistant_id, Some(user_id))
+    .await
+    .ok()
+    .flatten();
+  let assistant_params = assistant.as_ref().and_then(|assistant| assistant.parameters.clone());
+  let project_params = match project_id {
+    Some(project_id) => project_parameters(project_id, user_id).await,
+    None => None,
+  };
+
+  // A response format sent with the request replaces the assistant's
+  let response_format = match &request.response_format {
+    Some(format) => {
+      if let Err(e) = check_response_format(format) {
+        let _ = tx.send(Ok(Event::default().event("error").data(
+          &serde_json::to_string(&StreamErrorData {
+            error: e,
+            code: ErrorCode::ValidInvalidInput.as_str().to_string(),
+          })
+            .unwrap_or_default(),
+        )));
+        return;
+      }
+      Some(format.clone())
+    }
+    None => assistant.as_ref().and_then(|assistant| assistant.response_format.clone()),
+  };
+
+  if let Some(Err(e)) = request.parameters.as_ref().map(ModelParameters::validate) {
+    let _ = tx.send(Ok(Event::default().event("error").data(
+      &serde_json::to_string(&StreamErrorData {
+        error: format!("Invalid parameters: {}", e),
+        code: ErrorCode::ValidInvalidInput.as_str().to_string(),
+      })
+        .unwrap_or_default(),
+    )));
+    return;
+  }
+
+  // Models to fail over to when the provider is unavailable; an assistant's chain replaces the model's
+  let fallback_chain = match &assistant {
+    Some(assistant) if !assistant.fallback_chain.is_empty() => assistant.fallback_chain.clone(),
+    _ => model.fallback_chain.clone(),
   };
 
   // Check if this is a new conversation (count messages before moving them)
   // Count messages excluding system messages (assistant instructions)
   let user_and_assistant_messages = messages.iter().filter(|m| m.role != "system").count();
 
+  // Enforce the monthly quotas of the user's groups before anything reaches the provider
+  match check_usage_quotas(user_id).await {
+    Ok(None) => {}
+    Ok(Some(reason)) => {
+      let _ = tx.send(Ok(Event::default().event("error").data(
+        &serde_json::to_string(&StreamErrorData {
+          error: reason,
+          code: ErrorCode::QuotaExceeded.as_str().to_string(),
+        })
+          .unwrap_or_default(),
+      )));
+      return;
+    }
+    Err(e) => {
+      let _ = tx.send(Ok(Event::default().event("error").data(
+        &serde_json::to_string(&StreamErrorData {
+          error: format!("Error checking usage quotas: {}", e),
+          code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+        })
+          .unwrap_or_default(),
+      )));
+      return;
+    }
+  }
+
   // Create AI provider with model ID for Candle providers
-  let ai_provider =
+  let mut ai_provider =
     match create_ai_provider_with_model_id(&provider, Some(request.model_id)).await {
       Ok(provider) => provider,
       Err(e) => {
@@ -340,19 +586,36 @@ async fn stream_ai_response(
     return;
   }
 
-  // Merge parameters from model and assistant configurations
-  let (temperature, max_tokens, top_p, frequency_penalty, presence_penalty) =
-    merge_parameters(&model.parameters, &assistant_params);
+  // Layer the assistant's, the project's and the request's parameters over the model's
+  let parameter_overrides = [
+    assistant_params.as_ref(),
+    project_params.as_ref(),
+    request.parameters.as_ref(),
+  ];
+  let mut parameters = chat_parameters(&model, &provider, &parameter_overrides);
 
-  // Create ModelParameters from the merged values
-  let parameters = crate::database::models::model::ModelParameters {
-    temperature,
-    max_tokens,
-    top_p,
-    frequency_penalty,
-    presence_penalty,
-    ..Default::default()
+  // Leave out the oldest history that does not fit the model's context window
+  let context_settings = assistant
+    .as_ref()
+    .and_then(|assistant| assistant.context_window.clone())
+    .unwrap_or_default();
+  let summary_owner = SummaryOwner {
+    conversation_id: request.conversation_id,
+    branch_id: active_branch_id,
+    user_id,
   };
+  let (messages, context_report) = fit_context_window(
+    messages,
+    &context_settings,
+    &parameters.parameters,
+    &model,
+    &provider,
+    summary_owner,
+  )
+    .await;
+
+  // Estimated up front in case the provider does not report usage
+  let estimated_prompt_tokens = estimate_prompt_tokens(&messages);
 
   // Create chat request
   let chat_request = ChatRequest {
@@ -361,7 +624,10 @@ async fn stream_ai_response(
     model_id: model.id,
     provider_id: provider.id,
     stream: true,
-    parameters: Some(parameters),
+    parameters: Some(parameters.parameters.clone()),
+    tools: request.tools.clone(),
+    tool_choice: request.tool_choice.clone(),
+    response_format: response_format.clone(),
   };
 
   // If there's only 1 message (the user message we just added), this is a new conversation
@@ -375,57 +641,322 @@ async fn stream_ai_response(
         .await;
   }
 
-  // Call AI provider with streaming
-  match ai_provider.chat_stream(chat_request).await {
+  // Allocate the assistant message id up front and register the stream so that
+  // POST /api/chat/messages/{id}/cancel can stop it while it is generating
+  let assistant_message_id = target.message_id.unwrap_or_else(Uuid::new_v4);
+  let cancellation_token = register_message_stream(assistant_message_id, user_id).await;
+  let _ = tx.send(Ok(Event::default().event("message-start").data(
+    &serde_json::to_string(&StreamMessageStartData {
+      message_id: assistant_message_id.to_string(),
+      conversation_id: request.conversation_id.to_string(),
+    })
+      .unwrap_or_default(),
+  )));
+
+  // Send the RAG chunks injected into the prompt so the client can render the citations
+  if !citations.is_empty() {
+    let _ = tx.send(Ok(Event::default().event("citations").data(
+      &serde_json::to_string(&StreamCitationsData {
+        message_id: assistant_message_id.to_string(),
+        citations,
+      })
+        .unwrap_or_default(),
+    )));
+  }
+
+  // Call AI provider with streaming, failing over along the fallback chain on retryable errors
+  let mut provider = provider;
+  let mut model = model;
+  let mut stream_result = ai_provider.chat_stream(chat_request.clone()).await;
+  let mut fallback_targets = fallback_chain.into_iter();
+  let mut tried_model_ids = vec![model.id];
+  let mut allowed_provider_ids: Option<Vec<Uuid>> = None;
+
+  while let Err(e) = &stream_result {
+    if !is_retryable_error(e.as_ref()) {
+      break;
+    }
+
+    // Fallbacks are limited to the providers the user may use
+    if allowed_provider_ids.is_none() {
+      allowed_provider_ids = Some(match get_providers_for_user(user_id).await {
+        Ok(providers) => providers.into_iter().map(|provider| provider.id).collect(),
+        Err(e) => {
+          eprintln!("Failed to load providers for fallback: {}", e);
+          Vec::new()
+        }
+      });
+    }
+    let allowed = allowed_provider_ids.as_deref().unwrap_or_default();
+
+    let mut next = None;
+    for target in fallback_targets.by_ref() {
+      if tried_model_ids.contains(&target.model_id) || !allowed.contains(&target.provider_id) {
+        continue;
+      }
+      tried_model_ids.push(target.model_id);
+      if let Some(candidate) = resolve_fallback_target(&target).await {
+        next = Some(candidate);
+        break;
+      }
+    }
+    let Some((next_provider, next_model, next_ai_provider)) = next else {
+      break;
+    };
+
+    println!(
+      "Provider {} failed ({}), falling back to {} on {}",
+      provider.name, e, next_model.name, next_provider.name
+    );
+    let _ = tx.send(Ok(Event::default().event("fallback").data(
+      &serde_json::to_string(&StreamFallbackData {
+        message_id: assistant_message_id.to_string(),
+        provider_id: next_provider.id.to_string(),
+        model_id: next_model.id.to_string(),
+        model_name: next_model.alias.clone(),
+        reason: e.to_string(),
+      })
+        .unwrap_or_default(),
+    )));
+
+    parameters = chat_parameters(&next_model, &next_provider, &parameter_overrides);
+    let fallback_request = ChatRequest {
+      model_name: next_model.name.clone(),
+      model_id: next_model.id,
+      provider_id: next_provider.id,
+      parameters: Some(parameters.parameters.clone()),
+      ..chat_request.clone()
+    };
+    stream_result = next_ai_provider.chat_stream(fallback_request).await;
+    ai_provider = next_ai_provider;
+    provider = next_provider;
+    model = next_model;
+  }
+
+  match stream_result {
     Ok(mut stream) => {
       let mut full_content = String::new();
-
-      // Process the stream
-      while let Some(chunk_result) = stream.next().await {
-        match chunk_result {
-          Ok(chunk) => {
-            if let Some(content) = chunk.content {
+      let mut full_reasoning = String::new();
+      let mut tool_calls: Vec<ToolCall> = Vec::new();
+      let mut finish_reason: Option<String>;
+      let mut usage: Option<Usage>;
+      let mut token_counts;
+
+      // Pin the cancellation future once so its receiver survives across loop iterations
+      let cancelled = cancellation_token.cancelled();
+      tokio::pin!(cancelled);
+
+      // Each pass streams one answer. An answer that does not match the response format is
+      // rejected and the model is asked to correct it. Its content is held back until it is
+      // validated, so the client never shows a rejected answer; reasoning still streams.
+      let hold_content = response_format.is_some();
+      let mut schema_retries = 0;
+      let mut attempt_messages = chat_request.messages.clone();
+      let mut attempt_prompt_tokens = estimated_prompt_tokens;
+      let schema_errors = loop {
+        full_content.clear();
+        full_reasoning.clear();
+        tool_calls.clear();
+        finish_reason = None;
+        usage = None;
+        // Models without a reasoning parser inline their thinking as <think> tags
+        let mut think_splitter = ThinkTagSplitter::new();
+
+        // Process the stream
+        loop {
+          let chunk_result = tokio::select! {
+            _ = &mut cancelled => {
+              finish_reason = Some(FINISH_REASON_CANCELLED.to_string());
+              break;
+            }
+            next = stream.next() => match next {
+              Some(chunk_result) => chunk_result,
+              None => break,
+            },
+          };
+
+          match chunk_result {
+            Ok(chunk) => {
+              let (content, inline_reasoning) = match &chunk.content {
+                Some(content) => think_splitter.push(content),
+                None => (String::new(), String::new()),
+              };
+              let reasoning = chunk.reasoning.unwrap_or_default() + &inline_reasoning;
               full_content.push_str(&content);
-
-              // Send chunk to client
-              let _ = tx.send(Ok(Event::default().event("chunk").data(
-                &serde_json::to_string(&StreamChunkData {
-                  delta: content,
-                  message_id: None,
+              full_reasoning.push_str(&reasoning);
+
+              // A closed channel means the client went away
+              let streamed_content = if hold_content { "" } else { content.as_str() };
+              if !send_stream_deltas(&tx, assistant_message_id, streamed_content, &reasoning) {
+                finish_reason = Some(FINISH_REASON_CANCELLED.to_string());
+                break;
+              }
+
+              if let Some(calls) = chunk.tool_calls {
+                tool_calls.extend(calls);
+              }
+
+              if let Some(chunk_usage) = chunk.usage {
+                usage.get_or_insert_with(Usage::default).merge(chunk_usage);
+              }
+
+              // Some providers report usage after the finish reason, so read to the end of the stream
+              if chunk.finish_reason.is_some() {
+                finish_reason = chunk.finish_reason;
+              }
+            }
+            Err(e) => {
+              let _ = tx.send(Ok(Event::default().event("error").data(
+                &serde_json::to_string(&StreamErrorData {
+                  error: format!("Streaming error: {}", e),
+                  code: ErrorCode::SystemStreamingError.as_str().to_string(),
                 })
                   .unwrap_or_default(),
               )));
+              unregister_message_stream(assistant_message_id).await;
+              return;
             }
+          }
+        }
 
-            // Check if streaming is complete
-            if chunk.finish_reason.is_some() {
-              break;
-            }
+        let (content, reasoning) = think_splitter.finish();
+        let streamed_content = if hold_content { "" } else { content.as_str() };
+        send_stream_deltas(&tx, assistant_message_id, streamed_content, &reasoning);
+        full_content.push_str(&content);
+        full_reasoning.push_str(&reasoning);
+
+        // Reasoning tokens are billed as completion tokens
+        token_counts = resolve_token_counts(
+          usage.as_ref(),
+          attempt_prompt_tokens,
+          &format!("{}{}", full_reasoning, full_content),
+        );
+
+        // Tool calls and cancelled answers are not held to the format
+        let Some(format) = response_format.as_ref().filter(|_| {
+          tool_calls.is_empty() && finish_reason.as_deref() != Some(FINISH_REASON_CANCELLED)
+        }) else {
+          break None;
+        };
+        let errors = match validate_response(format, &full_content) {
+          Ok(json) => {
+            full_content = json;
+            break None;
           }
+          Err(errors) if schema_retries >= MAX_SCHEMA_RETRIES => break Some(errors),
+          Err(errors) => errors,
+        };
+
+        // The rejected answer still used tokens
+        schema_retries += 1;
+        record_token_usage(user_id, None, provider.id, model.id, USAGE_SOURCE_CHAT, token_counts)
+          .await;
+        let _ = tx.send(Ok(Event::default().event("schema-retry").data(
+          &serde_json::to_string(&StreamSchemaRetryData {
+            message_id: assistant_message_id.to_string(),
+            errors: errors.clone(),
+          })
+            .unwrap_or_default(),
+        )));
+
+        attempt_messages.push(ChatMessage::text("assistant", &full_content));
+        attempt_messages.push(ChatMessage::text("user", &correction_prompt(&errors)));
+        attempt_prompt_tokens = estimate_prompt_tokens(&attempt_messages);
+        let retry_request = ChatRequest {
+          messages: attempt_messages.clone(),
+          model_name: model.name.clone(),
+          model_id: model.id,
+          provider_id: provider.id,
+          parameters: Some(parameters.parameters.clone()),
+          ..chat_request.clone()
+        };
+        stream = match ai_provider.chat_stream(retry_request).await {
+          Ok(retry_stream) => retry_stream,
           Err(e) => {
+            unregister_message_stream(assistant_message_id).await;
             let _ = tx.send(Ok(Event::default().event("error").data(
               &serde_json::to_string(&StreamErrorData {
-                error: format!("Streaming error: {}", e),
-                code: ErrorCode::SystemStreamingError.as_str().to_string(),
+                error: format!("Error calling AI provider: {}", e),
+                code: ErrorCode::SystemExternalServiceError.as_str().to_string(),
               })
                 .unwrap_or_default(),
             )));
             return;
           }
-        }
+        };
+      };
+
+      // The accepted answer, or the last one when retries ran out, goes out in one chunk
+      if hold_content {
+        send_stream_deltas(&tx, assistant_message_id, &full_content, "");
       }
 
-      // Save the complete assistant message
+      // Dropping the stream closes the upstream connection so the provider stops generating
+      drop(stream);
+      unregister_message_stream(assistant_message_id).await;
+
+      // Save the complete assistant message (a tool_call message when the model requested tools)
       let assistant_message_req = SaveMessageRequest {
         conversation_id: request.conversation_id,
         content: full_content.clone(),
+        reasoning: if full_reasoning.is_empty() { None } else { Some(full_reasoning) },
         role: "assistant".to_string(),
-        model_id: request.model_id,
+        model_id: model.id,
         file_ids: None, // Assistant messages don't have file attachments
+        tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls.clone()) },
+        tool_call_id: None,
+        finish_reason: finish_reason.clone(),
+        provider_id: Some(provider.id),
+        parameters: Some(parameters.parameters.clone()),
+        system_prompt_override: request.system_prompt_override.clone(),
+        originated_from_id: target.originated_from_id,
       };
 
-      match chat::save_message(assistant_message_req, user_id, Some(active_branch_id)).await {
+      match chat::save_message_with_id(
+        assistant_message_id,
+        assistant_message_req,
+        user_id,
+        Some(active_branch_id),
+      )
+        .await
+      {
         Ok(assistant_message) => {
+          record_token_usage(
+            user_id,
+            Some(assistant_message.id),
+            provider.id,
+            model.id,
+            USAGE_SOURCE_CHAT,
+            token_counts,
+          )
+            .await;
+
+          // Index the exchange, including the user message or edit that started it
+          crate::rag::notify_semantic_indexer();
+
+          // The answer is kept, but the client gets a typed error instead of a completion
+          if let Some(errors) = schema_errors {
+            let _ = tx.send(Ok(Event::default().event("error").data(
+              &serde_json::to_string(&StreamErrorData {
+                error: format!("Response does not match the response format: {}", errors.join("; ")),
+                code: ErrorCode::ChatResponseSchemaMismatch.as_str().to_string(),
+              })
+                .unwrap_or_default(),
+            )));
+            return;
+          }
+
+          // Tell the client which tools to run; it answers via the tool-results endpoint
+          if !tool_calls.is_empty() {
+            let _ = tx.send(Ok(Event::default().event("tool-calls").data(
+              &serde_json::to_string(&StreamToolCallsData {
+                message_id: assistant_message.id.to_string(),
+                tool_calls,
+              })
+                .unwrap_or_default(),
+            )));
+          }
+
           // Send completion event
           let _ = tx.send(Ok(Event::default().event("complete").data(
             &serde_json::to_string(&StreamCompleteData {
@@ -438,12 +969,27 @@ async fn stream_ai_response(
               edit_count: assistant_message.edit_count,
               created_at: assistant_message.created_at.to_rfc3339(),
               updated_at: assistant_message.updated_at.to_rfc3339(),
-              total_tokens: None, // Token usage not available in streaming mode
+              total_tokens: Some(token_counts.total_tokens() as i32),
+              finish_reason,
+              parameters: parameters.parameters.clone(),
+              unsupported_parameters: parameters.unsupported.clone(),
+              context: context_report.clone(),
             })
               .unwrap_or_default(),
           )));
         }
         Err(e) => {
+          // The provider call still used tokens
+          record_token_usage(
+            user_id,
+            None,
+            provider.id,
+            model.id,
+            USAGE_SOURCE_CHAT,
+            token_counts,
+          )
+            .await;
+
           let _ = tx.send(Ok(Event::default().event("error").data(
             &serde_json::to_string(&StreamErrorData {
               error: format!("Error saving assistant message: {}", e),
@@ -455,6 +1001,7 @@ async fn stream_ai_response(
       }
     }
     Err(e) => {
+      unregister_message_stream(assistant_message_id).await;
       let _ = tx.send(Ok(Event::default().event("error").data(
         &serde_json::to_string(&StreamErrorData {
           error: format!("Error calling AI provider: {}", e),
@@ -466,6 +1013,91 @@ async fn stream_ai_response(
   }
 }
 
+/// Send reasoning and content deltas to the client. Returns false once the client has gone away
+fn send_stream_deltas(
+  tx: &tokio::sync::mpsc::UnboundedSender<Result<Event, Infallible>>,
+  message_id: Uuid,
+  content: &str,
+  reasoning: &str,
+) -> bool {
+  if !reasoning.is_empty() {
+    let sent = tx.send(Ok(Event::default().event("thinking").data(
+      &serde_json::to_string(&StreamThinkingData {
+        delta: reasoning.to_string(),
+        message_id: message_id.to_string(),
+      })
+        .unwrap_or_default(),
+    )));
+    if sent.is_err() {
+      return false;
+    }
+  }
+
+  if !content.is_empty() {
+    let sent = tx.send(Ok(Event::default().event("chunk").data(
+      &serde_json::to_string(&StreamChunkData {
+        delta: content.to_string(),
+        message_id: Some(message_id.to_string()),
+      })
+        .unwrap_or_default(),
+    )));
+    if sent.is_err() {
+      return false;
+    }
+  }
+
+  true
+}
+
+/// Send an error event to the client
+fn send_stream_error(
+  tx: &tokio::sync::mpsc::UnboundedSender<Result<Event, Infallible>>,
+  error: String,
+  code: ErrorCode,
+) {
+  let _ = tx.send(Ok(Event::default().event("error").data(
+    &serde_json::to_string(&StreamErrorData {
+      error,
+      code: code.as_str().to_string(),
+    })
+      .unwrap_or_default(),
+  )));
+}
+
+/// Track an in-flight assistant stream and create its cancellation token
+async fn register_message_stream(message_id: Uuid, user_id: Uuid) -> CancellationToken {
+  ACTIVE_STREAMS.write().await.insert(message_id, user_id);
+  create_cancellation_token(message_id).await
+}
+
+/// Stop tracking an assistant stream once it has finished or failed
+async fn unregister_message_stream(message_id: Uuid) {
+  ACTIVE_STREAMS.write().await.remove(&message_id);
+  remove_download_tracking(message_id).await;
+}
+
+/// Cancel an in-flight assistant response. The upstream provider request is aborted and
+/// the partial message is saved with finish_reason "cancelled"
+pub async fn cancel_message_stream(
+  Extension(auth_user): Extension<AuthenticatedUser>,
+  Path(message_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+  // Only the user who started the stream may cancel it
+  let owner_id = ACTIVE_STREAMS.read().await.get(&message_id).copied();
+  if owner_id != Some(auth_user.user.id) {
+    return Err(StatusCode::NOT_FOUND);
+  }
+
+  if cancel_download(message_id).await {
+    Ok(Json(serde_json::json!({
+      "success": true,
+      "message": "Generation cancelled"
+    })))
+  } else {
+    Err(StatusCode::NOT_FOUND)
+  }
+}
+
 /// Send a message with AI provider integration using SSE streaming
 pub async fn send_message_stream(
   Extension(auth_user): Extension<AuthenticatedUser>,
@@ -483,28 +1115,309 @@ pub async fn send_message_stream(
     let user_message_req = SaveMessageRequest {
       conversation_id: request.conversation_id,
       content: request.content.clone(),
+      reasoning: None,
       role: "user".to_string(),
       model_id: request.model_id,
       file_ids: request.file_ids.clone(),
+      tool_calls: None,
+      tool_call_id: None,
+      finish_reason: None,
+      provider_id: None,
+      parameters: None,
+      system_prompt_override: None,
+      originated_from_id: None,
+    };
+
+    let user_message = match chat::save_message(user_message_req, auth_user.user.id, None).await {
+      Ok(message) => message,
+      Err(e) => {
+        let _ = tx.send(Ok(Event::default().event("error").data(
+          &serde_json::to_string(&StreamErrorData {
+            error: format!("Error saving user message: {}", e),
+            code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+          })
+            .unwrap_or_default(),
+        )));
+        return;
+      }
+    };
+
+    let target = AnswerTarget {
+      user_message_id: Some(user_message.id),
+      ..AnswerTarget::default()
     };
+    stream_ai_response(tx, request, auth_user.user.id, target).await;
+  });
+
+  // Convert the receiver to a stream and return as SSE
+  let stream = UnboundedReceiverStream::new(rx);
+
+  Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
 
-    if let Err(e) = chat::save_message(user_message_req, auth_user.user.id, None).await {
+/// Send a message to several (model, assistant) pairs at once. Each answer is generated on a
+/// branch of its own and all of them are streamed over one connection, with the index of the
+/// target as the SSE event id. The first answer goes on the current branch
+pub async fn compare_message_stream(
+  Extension(auth_user): Extension<AuthenticatedUser>,
+  Json(request): Json<CompareMessageRequest>,
+) -> Result<Sse<impl Stream<Item=Result<Event, Infallible>>>, StatusCode> {
+  if request.targets.len() < 2 || request.targets.len() > MAX_COMPARE_TARGETS {
+    return Err(StatusCode::BAD_REQUEST);
+  }
+
+  // Create a channel for streaming events
+  let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+  tokio::spawn(async move {
+    let user_id = auth_user.user.id;
+    let CompareMessageRequest { conversation_id, content, file_ids, targets, response_format } = request;
+
+    // Send initial event
+    let _ = tx.send(Ok(Event::default().data("start")));
+
+    let branch_id = match chat::get_conversation_by_id(conversation_id, user_id).await {
+      Ok(Some(conversation)) => conversation.active_branch_id,
+      Ok(None) => None,
+      Err(e) => {
+        let _ = tx.send(Ok(Event::default().event("error").data(
+          &serde_json::to_string(&StreamErrorData {
+            error: format!("Error getting conversation: {}", e),
+            code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+          })
+            .unwrap_or_default(),
+        )));
+        return;
+      }
+    };
+    let Some(branch_id) = branch_id else {
       let _ = tx.send(Ok(Event::default().event("error").data(
         &serde_json::to_string(&StreamErrorData {
-          error: format!("Error saving user message: {}", e),
-          code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+          error: "Conversation not found".to_string(),
+          code: ErrorCode::ResourceNotFound.as_str().to_string(),
         })
           .unwrap_or_default(),
       )));
       return;
+    };
+
+    let user_message_req = SaveMessageRequest {
+      conversation_id,
+      content: content.clone(),
+      reasoning: None,
+      role: "user".to_string(),
+      model_id: targets[0].model_id,
+      file_ids: file_ids.clone(),
+      tool_calls: None,
+      tool_call_id: None,
+      finish_reason: None,
+      provider_id: None,
+      parameters: None,
+      system_prompt_override: None,
+      originated_from_id: None,
+    };
+
+    let user_message = match chat::save_message(user_message_req, user_id, Some(branch_id)).await {
+      Ok(message) => message,
+      Err(e) => {
+        let _ = tx.send(Ok(Event::default().event("error").data(
+          &serde_json::to_string(&StreamErrorData {
+            error: format!("Error saving user message: {}", e),
+            code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+          })
+            .unwrap_or_default(),
+        )));
+        return;
+      }
+    };
+
+    // The other answers go on copies of the branch that already hold the user message
+    let mut branch_ids = vec![branch_id];
+    for _ in 1..targets.len() {
+      match chat::fork_branch(conversation_id, branch_id, None, user_id).await {
+        Ok(Some(branch)) => branch_ids.push(branch.id),
+        Ok(None) => {
+          let _ = tx.send(Ok(Event::default().event("error").data(
+            &serde_json::to_string(&StreamErrorData {
+              error: "Branch not found".to_string(),
+              code: ErrorCode::ResourceNotFound.as_str().to_string(),
+            })
+              .unwrap_or_default(),
+          )));
+          return;
+        }
+        Err(e) => {
+          let _ = tx.send(Ok(Event::default().event("error").data(
+            &serde_json::to_string(&StreamErrorData {
+              error: format!("Error creating branch: {}", e),
+              code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+            })
+              .unwrap_or_default(),
+          )));
+          return;
+        }
+      }
     }
 
-    stream_ai_response(tx, request, auth_user.user.id).await;
+    // The answers share a lineage, so each lists the others as branches
+    let message_ids: Vec<Uuid> = targets.iter().map(|_| Uuid::new_v4()).collect();
+    let _ = tx.send(Ok(Event::default().event("compare-start").data(
+      &serde_json::to_string(&StreamCompareStartData {
+        conversation_id: conversation_id.to_string(),
+        user_message_id: user_message.id.to_string(),
+        streams: targets
+          .iter()
+          .enumerate()
+          .map(|(index, target)| CompareStreamData {
+            stream_id: index.to_string(),
+            branch_id: branch_ids[index].to_string(),
+            message_id: message_ids[index].to_string(),
+            model_id: target.model_id.to_string(),
+            assistant_id: target.assistant_id.to_string(),
+          })
+          .collect(),
+      })
+        .unwrap_or_default(),
+    )));
+
+    let streams = targets.into_iter().enumerate().map(|(index, target)| {
+      let tx = tx.clone();
+      let stream_request = ChatMessageRequest {
+        conversation_id,
+        content: content.clone(),
+        model_id: target.model_id,
+        assistant_id: target.assistant_id,
+        file_ids: file_ids.clone(),
+        tools: None,
+        tool_choice: None,
+        response_format: response_format.clone(),
+        parameters: target.parameters,
+        system_prompt_override: target.system_prompt_override,
+      };
+      let answer_target = AnswerTarget {
+        branch_id: Some(branch_ids[index]),
+        message_id: Some(message_ids[index]),
+        originated_from_id: Some(message_ids[0]),
+        user_message_id: Some(user_message.id),
+      };
+
+      async move {
+        let (stream_tx, mut stream_rx) = tokio::sync::mpsc::unbounded_channel();
+        let stream_id = index.to_string();
+
+        // Dropping the receiver when the client goes away cancels the answer like a single stream
+        let forward = async move {
+          while let Some(event) = stream_rx.recv().await {
+            if tx.send(event.map(|event| event.id(&stream_id))).is_err() {
+              break;
+            }
+          }
+        };
+
+        tokio::join!(
+          stream_ai_response(stream_tx, stream_request, user_id, answer_target),
+          forward,
+        );
+      }
+    });
+    futures_util::future::join_all(streams).await;
   });
 
   // Convert the receiver to a stream and return as SSE
   let stream = UnboundedReceiverStream::new(rx);
+  Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Submit results for tool calls requested by the model and stream its follow-up response
+pub async fn submit_tool_results_stream(
+  Extension(auth_user): Extension<AuthenticatedUser>,
+  Json(request): Json<ToolResultsRequest>,
+) -> Result<Sse<impl Stream<Item=Result<Event, Infallible>>>, StatusCode> {
+  if request.results.is_empty() {
+    return Err(StatusCode::BAD_REQUEST);
+  }
 
+  // The results must answer exactly the pending calls of the answer that requested them, since
+  // providers reject histories with unanswered or unknown tool calls
+  let previous_answer = chat::get_conversation_messages(request.conversation_id, auth_user.user.id)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .pop()
+    .filter(|message| message.role == "assistant")
+    .ok_or(StatusCode::BAD_REQUEST)?;
+  let mut pending_ids: Vec<&str> = previous_answer
+    .tool_calls
+    .iter()
+    .flatten()
+    .map(|call| call.id.as_str())
+    .collect();
+  let mut result_ids: Vec<&str> = request.results.iter().map(|result| result.tool_call_id.as_str()).collect();
+  pending_ids.sort_unstable();
+  result_ids.sort_unstable();
+  if pending_ids != result_ids {
+    return Err(StatusCode::BAD_REQUEST);
+  }
+
+  // Create a channel for streaming events
+  let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+  tokio::spawn(async move {
+    // Send initial event
+    let _ = tx.send(Ok(Event::default().data("start")));
+
+    // Save each tool result as its own message on the active branch, all or none
+    let tool_messages: Vec<_> = request
+      .results
+      .iter()
+      .map(|result| SaveMessageRequest {
+        conversation_id: request.conversation_id,
+        content: result.content.clone(),
+        reasoning: None,
+        role: "tool".to_string(),
+        model_id: request.model_id,
+        file_ids: None,
+        tool_calls: None,
+        tool_call_id: Some(result.tool_call_id.clone()),
+        finish_reason: None,
+        provider_id: None,
+        parameters: None,
+        system_prompt_override: None,
+        originated_from_id: None,
+      })
+      .collect();
+
+    if let Err(e) = chat::save_messages(request.conversation_id, &tool_messages, auth_user.user.id).await {
+      let _ = tx.send(Ok(Event::default().event("error").data(
+        &serde_json::to_string(&StreamErrorData {
+          error: format!("Error saving tool result: {}", e),
+          code: ErrorCode::SystemDatabaseError.as_str().to_string(),
+        })
+          .unwrap_or_default(),
+      )));
+      return;
+    }
+
+    // The follow-up answer is generated with the same settings as the tool calls were
+
+    // Continue the conversation without new user input
+    let continuation = ChatMessageRequest {
+      conversation_id: request.conversation_id,
+      content: String::new(),
+      model_id: request.model_id,
+      assistant_id: request.assistant_id,
+      file_ids: None,
+      tools: request.tools,
+      tool_choice: request.tool_choice,
+      response_format: request.response_format,
+      parameters: previous_answer.parameters,
+      system_prompt_override: previous_answer.system_prompt_override,
+    };
+
+    stream_ai_response(tx, continuation, auth_user.user.id, AnswerTarget::default()).await;
+  });
+
+  // Convert the receiver to a stream and return as SSE
+  let stream = UnboundedReceiverStream::new(rx);
   Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
 
@@ -527,7 +1440,7 @@ pub async fn edit_message_stream(
     };
 
     // Edit the message first
-    match chat::edit_message(message_id, edit_message, auth_user.user.id).await {
+    let edited_message_id = match chat::edit_message(message_id, edit_message, auth_user.user.id).await {
       Ok(Some(edit_response)) => {
         // send the edited message as a data event
         let _ = tx.send(Ok(Event::default().event("edited-message").data(
@@ -537,6 +1450,7 @@ pub async fn edit_message_stream(
         let _ = tx.send(Ok(Event::default().event("created-branch").data(
           &serde_json::to_string(&edit_response.branch).unwrap_or_default(),
         )));
+        edit_response.message.id
       }
       Ok(None) => {
         let _ = tx.send(Ok(Event::default().event("error").data(
@@ -558,12 +1472,17 @@ pub async fn edit_message_stream(
         )));
         return;
       }
-    }
+    };
 
+    let target = AnswerTarget {
+      user_message_id: Some(edited_message_id),
+      ..AnswerTarget::default()
+    };
     stream_ai_response(
       tx,
       request,
       auth_user.user.id,
+      target,
     ).await;
   });
 
@@ -572,6 +1491,143 @@ pub async fn edit_message_stream(
   Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
 
+/// Generate a new answer in place of an assistant message while keeping the old one. The
+/// answer is streamed on a branch forked at the user message it replies to, which becomes the
+/// active branch, and is listed with the old answer by `get_message_branches`
+pub async fn regenerate_message_stream(
+  Extension(auth_user): Extension<AuthenticatedUser>,
+  Path(message_id): Path<Uuid>,
+  request: Option<Json<RegenerateMessageRequest>>,
+) -> Result<Sse<impl Stream<Item=Result<Event, Infallible>>>, StatusCode> {
+  let request = request.map(|Json(request)| request).unwrap_or_default();
+
+  // Create a channel for streaming events
+  let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+  tokio::spawn(async move {
+    let user_id = auth_user.user.id;
+
+    // Send initial event
+    let _ = tx.send(Ok(Event::default().data("start")));
+
+    let origin = match chat::get_message_origin_branch(message_id, user_id).await {
+      Ok(Some(branch)) => branch,
+      Ok(None) => {
+        send_stream_error(&tx, "Message not found".to_string(), ErrorCode::ResourceNotFound);
+        return;
+      }
+      Err(e) => {
+        send_stream_error(&tx, format!("Error finding message: {}", e), ErrorCode::SystemDatabaseError);
+        return;
+      }
+    };
+    let conversation_id = origin.conversation_id;
+
+    let (conversation, messages) = match tokio::try_join!(
+      chat::get_conversation_by_id(conversation_id, user_id),
+      chat::get_conversation_messages_by_branch(conversation_id, origin.id, user_id),
+    ) {
+      Ok((Some(conversation), messages)) => (conversation, messages),
+      Ok((None, _)) => {
+        send_stream_error(&tx, "Conversation not found".to_string(), ErrorCode::ResourceNotFound);
+        return;
+      }
+      Err(e) => {
+        send_stream_error(&tx, format!("Error loading conversation: {}", e), ErrorCode::SystemDatabaseError);
+        return;
+      }
+    };
+
+    let Some(position) = messages.iter().position(|message| message.id == message_id) else {
+      send_stream_error(&tx, "Message not found".to_string(), ErrorCode::ResourceNotFound);
+      return;
+    };
+    let original = &messages[position];
+    if original.role != "assistant" {
+      send_stream_error(
+        &tx,
+        "Only assistant messages can be regenerated".to_string(),
+        ErrorCode::ValidInvalidInput,
+      );
+      return;
+    }
+    let Some(user_message) = messages[..position].iter().rev().find(|message| message.role == "user") else {
+      send_stream_error(
+        &tx,
+        "No user message precedes this answer".to_string(),
+        ErrorCode::ValidInvalidInput,
+      );
+      return;
+    };
+
+    // The answer's own settings only carry over when the same model and assistant answer again
+    let keeps_settings = request.model_id.is_none() && request.assistant_id.is_none();
+    let (Some(model_id), Some(assistant_id)) = (
+      request.model_id.or(original.model_id).or(conversation.model_id),
+      request.assistant_id.or(conversation.assistant_id),
+    ) else {
+      send_stream_error(
+        &tx,
+        "A model and an assistant are required to regenerate this answer".to_string(),
+        ErrorCode::ValidMissingRequiredField,
+      );
+      return;
+    };
+
+    let branch = match chat::fork_branch(conversation_id, origin.id, Some(user_message.id), user_id).await {
+      Ok(Some(branch)) => branch,
+      Ok(None) => {
+        send_stream_error(&tx, "Branch not found".to_string(), ErrorCode::ResourceNotFound);
+        return;
+      }
+      Err(e) => {
+        send_stream_error(&tx, format!("Error creating branch: {}", e), ErrorCode::SystemDatabaseError);
+        return;
+      }
+    };
+    if let Err(e) = chat::switch_conversation_branch(conversation_id, branch.id, user_id).await {
+      send_stream_error(&tx, format!("Error switching branch: {}", e), ErrorCode::SystemDatabaseError);
+      return;
+    }
+
+    // send the created branch as a data event
+    let _ = tx.send(Ok(Event::default().event("created-branch").data(
+      &serde_json::to_string(&branch).unwrap_or_default(),
+    )));
+
+    // The user message is sent again as it was the first time
+    let file_ids: Vec<Uuid> = user_message.files.iter().map(|file| file.id).collect();
+    let regeneration = ChatMessageRequest {
+      conversation_id,
+      content: user_message.content.clone(),
+      model_id,
+      assistant_id,
+      file_ids: if file_ids.is_empty() { None } else { Some(file_ids) },
+      tools: None,
+      tool_choice: None,
+      response_format: None,
+      parameters: request
+        .parameters
+        .or_else(|| original.parameters.clone().filter(|_| keeps_settings)),
+      system_prompt_override: request
+        .system_prompt_override
+        .or_else(|| original.system_prompt_override.clone().filter(|_| keeps_settings)),
+    };
+    let target = AnswerTarget {
+      branch_id: Some(branch.id),
+      message_id: None,
+      originated_from_id: Some(original.originated_from_id.unwrap_or(original.id)),
+      user_message_id: Some(user_message.id),
+    };
+
+    stream_ai_response(tx, regeneration, user_id, target).await;
+  });
+
+  // Convert the receiver to a stream and return as SSE
+  let stream = UnboundedReceiverStream::new(rx);
+  Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
 /// Edit a message (creates a new branch) - non-streaming version for backward compatibility
 pub async fn edit_message(
   Extension(auth_user): Extension<AuthenticatedUser>,
@@ -609,6 +1665,24 @@ pub async fn switch_conversation_branch(
   }
 }
 
+/// Keep one answer of a comparison by making the branch it was generated on the active branch
+pub async fn select_message_branch(
+  Extension(auth_user): Extension<AuthenticatedUser>,
+  Path(message_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+  match chat::select_message_branch(message_id, auth_user.user.id).await {
+    Ok(Some(branch_id)) => Ok(Json(serde_json::json!({
+      "success": true,
+      "branch_id": branch_id
+    }))),
+    Ok(None) => Err(StatusCode::NOT_FOUND),
+    Err(e) => {
+      eprintln!("Error selecting message branch: {}", e);
+      Err(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+  }
+}
+
 /// Get message branches for a specific message (all branches containing messages with same originated_from_id)
 pub async fn get_message_branches(
   Extension(auth_user): Extension<AuthenticatedUser>,
@@ -623,16 +1697,29 @@ pub async fn get_message_branches(
   }
 }
 
-/// Search conversations
+/// Search conversations by title and message content, ranked, with highlighted snippets
 pub async fn search_conversations(
   Extension(auth_user): Extension<AuthenticatedUser>,
   Query(params): Query<SearchQuery>,
-) -> Result<Json<ConversationListResponse>, StatusCode> {
-  let page = params.page.unwrap_or(1);
-  let per_page = params.per_page.unwrap_or(20);
+) -> Result<Json<ConversationSearchResponse>, StatusCode> {
+  let page = params.page.unwrap_or(1).max(1);
+  let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
 
-  let project_id = params.project_id.as_deref().map(|s| Uuid::parse_str(s).ok()).flatten();
-  match chat::search_conversations(auth_user.user.id, &params.q, page, per_page, project_id).await {
+  if let Some(role) = &params.role {
+    if !["user", "assistant", "system", "tool"].contains(&role.as_str()) {
+      return Err(StatusCode::BAD_REQUEST);
+    }
+  }
+
+  let filters = ConversationSearchFilters {
+    project_id: params.project_id.as_deref().map(|s| Uuid::parse_str(s).ok()).flatten(),
+    from: params.from,
+    to: params.to,
+    assistant_id: params.assistant_id,
+    model_id: params.model_id,
+    role: params.role,
+  };
+  match chat::search_conversations(auth_user.user.id, &params.q, page, per_page, &filters).await {
     Ok(response) => Ok(Json(response)),
     Err(e) => {
       eprintln!("Error searching conversations: {}", e);
@@ -641,8 +1728,37 @@ pub async fn search_conversations(
   }
 }
 
+/// Find the user's messages and files closest in meaning to the query, most similar first
+pub async fn semantic_search_conversations(
+  Extension(auth_user): Extension<AuthenticatedUser>,
+  Query(params): Query<SemanticSearchQuery>,
+) -> Result<Json<SemanticSearchResponse>, StatusCode> {
+  let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+  match crate::rag::semantic::is_semantic_search_enabled().await {
+    Ok(true) => {}
+    Ok(false) => return Err(StatusCode::SERVICE_UNAVAILABLE),
+    Err(e) => {
+      eprintln!("Error loading semantic search settings: {}", e);
+      return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+  }
+
+  if params.q.trim().is_empty() {
+    return Ok(Json(SemanticSearchResponse { results: Vec::new() }));
+  }
+
+  match crate::rag::semantic_search(auth_user.user.id, params.q.trim(), limit).await {
+    Ok(results) => Ok(Json(SemanticSearchResponse { results })),
+    Err(e) => {
+      eprintln!("Error in semantic search: {}", e);
+      Err(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+  }
+}
+
 /// Helper function to create proxy configuration from model provider settings
-fn create_proxy_config(
+pub fn create_proxy_config(
   proxy_settings: &crate::database::models::ProviderProxySettings,
 ) -> Option<ProxyConfig> {
   if proxy_settings.enabled {
@@ -745,6 +1861,24 @@ pub async fn create_ai_provider_with_model_id(
       )?;
       Ok(Box::new(custom_provider))
     }
+    "ollama" => {
+      let ollama_provider = OllamaProvider::new(
+        provider.api_key.as_ref().unwrap_or(&String::new()).clone(),
+        provider.base_url.clone(),
+        proxy_config,
+        provider.id,
+      )?;
+      Ok(Box::new(ollama_provider))
+    }
+    "llamacpp" => {
+      let llamacpp_provider = LlamaCppProvider::new(
+        provider.api_key.as_ref().unwrap_or(&String::new()).clone(),
+        provider.base_url.clone(),
+        proxy_config,
+        provider.id,
+      )?;
+      Ok(Box::new(llamacpp_provider))
+    }
     "local" => {
       // For Candle providers, we need model information to get the port
       let model_id = model_id.ok_or("Model ID is required for Candle providers")?;
@@ -814,6 +1948,7 @@ async fn generate_and_update_conversation_title(
     );
 
     let chat_messages = build_single_user_message(title_prompt);
+    let estimated_prompt_tokens = estimate_prompt_tokens(&chat_messages);
 
     // Create AI provider instance
     let ai_provider = create_ai_provider_with_model_id(provider, Some(model.id)).await?;
@@ -826,6 +1961,10 @@ async fn generate_and_update_conversation_title(
       top_p: Some(0.9),
       frequency_penalty: None,
       presence_penalty: None,
+      // Keep the model's context size so servers do not reload the model for the title
+      num_ctx: model.parameters.as_ref().and_then(|p| p.num_ctx),
+      // Reasoning would use up the small token budget before the title is written
+      enable_thinking: Some(false),
       ..Default::default()
     };
 
@@ -836,11 +1975,30 @@ async fn generate_and_update_conversation_title(
       provider_id: provider.id,
       stream: false,
       parameters: Some(title_parameters),
+      tools: None,
+      tool_choice: None,
+      response_format: None,
     };
 
     // Call AI provider to generate title
     match ai_provider.chat(chat_request).await {
       Ok(response) => {
+        // Title generation counts towards the user's usage like any other request
+        let token_counts = resolve_token_counts(
+          response.usage.as_ref(),
+          estimated_prompt_tokens,
+          &response.content,
+        );
+        record_token_usage(
+          user_id,
+          None,
+          provider.id,
+          model.id,
+          USAGE_SOURCE_CHAT,
+          token_counts,
+        )
+          .await;
+
         let generated_title = response.content.trim().to_string();
 
         // Clean up the title (remove quotes, limit length)
@@ -883,67 +2041,239 @@ async fn generate_and_update_conversation_title(
   Ok(())
 }
 
-/// Merge model and assistant parameters with assistant parameters taking priority
-/// Only include parameters that are actually defined (not null)
-fn merge_parameters(
-  model_params: &Option<crate::database::models::ModelParameters>,
-  assistant_params: &Option<crate::database::models::ModelParameters>,
-) -> (
-  Option<f32>, // temperature
-  Option<u32>, // max_tokens
-  Option<f32>, // top_p
-  Option<f32>, // frequency_penalty
-  Option<f32>, // presence_penalty
-) {
-  let mut temperature = None;
-  let mut max_tokens = None;
-  let mut top_p = None;
-  let mut frequency_penalty = None;
-  let mut presence_penalty = None;
+/// Inference parameters for a model: the model's own with each override layered on top, in
+/// order, then narrowed to the fields the provider supports
+fn chat_parameters(
+  model: &Model,
+  provider: &Provider,
+  overrides: &[Option<&ModelParameters>],
+) -> ResolvedParameters {
+  let mut layers = vec![model.parameters.as_ref()];
+  layers.extend_from_slice(overrides);
+  resolve_parameters(&layers, &provider.provider_type)
+}
 
-  // First, extract from model parameters
-  if let Some(model_params) = model_params {
-    if let Some(temp) = model_params.temperature {
-      temperature = Some(temp);
-    }
-    if let Some(m_tokens) = model_params.max_tokens {
-      max_tokens = Some(m_tokens);
-    }
-    if let Some(top_p_val) = model_params.top_p {
-      top_p = Some(top_p_val);
+/// Parameters configured on the conversation's project
+async fn project_parameters(project_id: Uuid, user_id: Uuid) -> Option<ModelParameters> {
+  let pool = match get_database_pool() {
+    Ok(pool) => pool,
+    Err(e) => {
+      eprintln!("Failed to get database pool for project parameters: {}", e);
+      return None;
     }
-    if let Some(freq_pen) = model_params.frequency_penalty {
-      frequency_penalty = Some(freq_pen);
+  };
+  match projects::get_project_by_id(&pool, project_id, user_id).await {
+    Ok(project) => project.and_then(|project| project.parameters),
+    Err(e) => {
+      eprintln!("Failed to load project {}: {}", project_id, e);
+      None
     }
-    if let Some(pres_pen) = model_params.presence_penalty {
-      presence_penalty = Some(pres_pen);
+  }
+}
+
+/// The conversation branch a rolling summary is cached for, and the user it is generated for
+#[derive(Debug, Clone, Copy)]
+struct SummaryOwner {
+  conversation_id: Uuid,
+  branch_id: Uuid,
+  user_id: Uuid,
+}
+
+/// Leave out the oldest history that does not fit the model's context window, following the
+/// assistant's strategy. Nothing is left out when the model's context length is unknown.
+/// When a summary cannot be written the summarized turns are dropped instead.
+async fn fit_context_window(
+  messages: Vec<ChatMessage>,
+  settings: &ContextWindowSettings,
+  parameters: &ModelParameters,
+  model: &Model,
+  provider: &Provider,
+  owner: SummaryOwner,
+) -> (Vec<ChatMessage>, Option<ContextWindowReport>) {
+  let Some(context_length) = effective_context_length(model, parameters) else {
+    return (messages, None);
+  };
+
+  let summarize = settings.strategy == ContextStrategy::Summary;
+  let summary_tokens = if summarize { SUMMARY_MAX_TOKENS } else { 0 };
+  let budget = prompt_budget(context_length, parameters);
+  let dropped = history_to_drop(&messages, settings, budget, summary_tokens);
+
+  let mut summary = None;
+  if summarize && dropped > 0 {
+    let history = leading_history(&messages, dropped);
+    match rolling_summary(history, settings, model, provider, owner).await {
+      Ok(text) => summary = Some(text),
+      Err(e) => eprintln!(
+        "Failed to summarize conversation {}, dropping the oldest messages instead: {}",
+        owner.conversation_id, e
+      ),
     }
   }
 
-  // Then, override with assistant parameters (higher priority)
-  if let Some(assistant_params) = assistant_params {
-    if let Some(temp) = assistant_params.temperature {
-      temperature = Some(temp);
+  let summarized_messages = if summary.is_some() { dropped } else { 0 };
+  let messages = trim_history(messages, dropped, summary.as_deref());
+  let report = ContextWindowReport {
+    strategy: settings.strategy,
+    context_length,
+    estimated_prompt_tokens: estimate_prompt_tokens(&messages),
+    dropped_messages: dropped,
+    summarized_messages,
+  };
+  (messages, Some(report))
+}
+
+/// Summary of the given leading history of a branch. The cached summary is extended with the
+/// messages dropped since it was written; branches never change their earlier messages, so
+/// the cache only has to be rebuilt when it covers messages that are sent again.
+async fn rolling_summary(
+  history: &[ChatMessage],
+  settings: &ContextWindowSettings,
+  model: &Model,
+  provider: &Provider,
+  owner: SummaryOwner,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+  let key = summary_metadata_key(owner.branch_id);
+  let cached = chat::get_conversation_metadata(owner.conversation_id, &key)
+    .await?
+    .and_then(|value| serde_json::from_value::<CachedSummary>(value).ok())
+    .filter(|cached| cached.covered_messages <= history.len());
+
+  let (previous, new_messages) = match &cached {
+    Some(cached) if cached.covered_messages == history.len() => return Ok(cached.summary.clone()),
+    Some(cached) => (Some(cached.summary.as_str()), &history[cached.covered_messages..]),
+    None => (None, history),
+  };
+
+  let (summary_provider, summary_model) = resolve_summary_model(settings, model, provider, owner.user_id).await;
+  let chat_messages = build_single_user_message(summary_prompt(previous, new_messages));
+  let estimated_prompt_tokens = estimate_prompt_tokens(&chat_messages);
+  let ai_provider = create_ai_provider_with_model_id(&summary_provider, Some(summary_model.id)).await?;
+
+  // Like title generation, an internal request that ignores the assistant's parameters
+  let summary_parameters = ModelParameters {
+    temperature: Some(0.2),
+    max_tokens: Some(SUMMARY_MAX_TOKENS),
+    num_ctx: summary_model.parameters.as_ref().and_then(|p| p.num_ctx),
+    enable_thinking: Some(false),
+    ..Default::default()
+  };
+
+  let response = ai_provider
+    .chat(ChatRequest {
+      messages: chat_messages,
+      model_name: summary_model.name.clone(),
+      model_id: summary_model.id,
+      provider_id: summary_provider.id,
+      stream: false,
+      parameters: Some(summary_parameters),
+      tools: None,
+      tool_choice: None,
+      response_format: None,
+    })
+    .await?;
+
+  // Summaries count towards the user's usage like any other request
+  let token_counts = resolve_token_counts(
+    response.usage.as_ref(),
+    estimated_prompt_tokens,
+    &response.content,
+  );
+  record_token_usage(
+    owner.user_id,
+    None,
+    summary_provider.id,
+    summary_model.id,
+    USAGE_SOURCE_CHAT,
+    token_counts,
+  )
+    .await;
+
+  let summary = response.content.trim().to_string();
+  if summary.is_empty() {
+    return Err("The summary model returned an empty summary".into());
+  }
+
+  let cached = CachedSummary {
+    summary: summary.clone(),
+    covered_messages: history.len(),
+  };
+  if let Err(e) =
+    chat::set_conversation_metadata(owner.conversation_id, &key, &serde_json::to_value(&cached)?).await
+  {
+    eprintln!("Failed to cache summary of conversation {}: {}", owner.conversation_id, e);
+  }
+
+  Ok(summary)
+}
+
+/// The model that writes rolling summaries: the assistant's summary model when it is enabled
+/// and on a provider the user may use, else the conversation's model
+async fn resolve_summary_model(
+  settings: &ContextWindowSettings,
+  model: &Model,
+  provider: &Provider,
+  user_id: Uuid,
+) -> (Provider, Model) {
+  let Some(summary_model_id) = settings.summary_model_id.filter(|id| *id != model.id) else {
+    return (provider.clone(), model.clone());
+  };
+
+  let candidate = match get_model_by_id(summary_model_id).await {
+    Ok(Some(summary_model)) if summary_model.enabled && !summary_model.is_deprecated => {
+      let summary_provider = get_provider_by_id(summary_model.provider_id).await.ok().flatten();
+      let allowed = get_providers_for_user(user_id)
+        .await
+        .map(|providers| providers.iter().any(|p| p.id == summary_model.provider_id))
+        .unwrap_or(false);
+      summary_provider
+        .filter(|summary_provider| summary_provider.enabled && allowed)
+        .map(|summary_provider| (summary_provider, summary_model))
     }
-    if let Some(max_tok) = assistant_params.max_tokens {
-      max_tokens = Some(max_tok);
+    Ok(_) => None,
+    Err(e) => {
+      eprintln!("Failed to load summary model {}: {}", summary_model_id, e);
+      None
     }
-    if let Some(top_p_val) = assistant_params.top_p {
-      top_p = Some(top_p_val);
+  };
+
+  candidate.unwrap_or_else(|| {
+    eprintln!(
+      "Summary model {} is not available, summarizing with {}",
+      summary_model_id, model.name
+    );
+    (provider.clone(), model.clone())
+  })
+}
+
+/// Load a fallback chain entry, skipping disabled or deprecated models and disabled providers
+async fn resolve_fallback_target(
+  target: &FallbackTarget,
+) -> Option<(Provider, Model, Box<dyn AIProvider>)> {
+  let model = match get_model_by_id(target.model_id).await {
+    Ok(Some(model)) if model.enabled && !model.is_deprecated && model.provider_id == target.provider_id => model,
+    Ok(_) => return None,
+    Err(e) => {
+      eprintln!("Failed to load fallback model {}: {}", target.model_id, e);
+      return None;
     }
-    if let Some(freq_pen) = assistant_params.frequency_penalty {
-      frequency_penalty = Some(freq_pen);
+  };
+
+  let provider = match get_provider_by_id(target.provider_id).await {
+    Ok(Some(provider)) if provider.enabled => provider,
+    Ok(_) => return None,
+    Err(e) => {
+      eprintln!("Failed to load fallback provider {}: {}", target.provider_id, e);
+      return None;
     }
-    if let Some(pres_pen) = assistant_params.presence_penalty {
-      presence_penalty = Some(pres_pen);
+  };
+
+  match create_ai_provider_with_model_id(&provider, Some(model.id)).await {
+    Ok(ai_provider) if ai_provider.supports_streaming() => Some((provider, model, ai_provider)),
+    Ok(_) => None,
+    Err(e) => {
+      eprintln!("Failed to create fallback provider {}: {}", provider.name, e);
+      None
     }
   }
-
-  (
-    temperature,
-    max_tokens,
-    top_p,
-    frequency_penalty,
-    presence_penalty,
-  )
-}
\ No newline at end of file
+}
@@ -1,14 +1,16 @@
 use crate::api::middleware::AuthenticatedUser;
 use crate::database::queries::configuration::{
-    get_default_language, get_proxy_no_proxy, get_proxy_password, get_proxy_url,
+    get_default_language, get_proxy_no_proxy, get_semantic_search_model_id, get_proxy_password, get_proxy_url,
     get_proxy_username, is_host_ssl, is_peer_ssl, is_proxy_enabled, is_proxy_host_ssl,
     is_proxy_ignore_ssl_certificates, is_proxy_ssl, is_user_registration_enabled,
     set_default_language, set_host_ssl, set_peer_ssl, set_proxy_enabled, set_proxy_host_ssl,
     set_proxy_ignore_ssl_certificates, set_proxy_no_proxy, set_proxy_password, set_proxy_ssl,
-    set_proxy_url, set_proxy_username, set_user_registration_enabled,
+    set_proxy_url, set_proxy_username, set_semantic_search_model_id, set_user_registration_enabled,
 };
+use crate::database::queries::models::get_model_by_id;
 use axum::{http::StatusCode, response::Json, Extension};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize)]
 pub struct UserRegistrationStatusResponse {
@@ -30,6 +32,16 @@ pub struct UpdateDefaultLanguageRequest {
     pub language: String,
 }
 
+#[derive(Serialize)]
+pub struct SemanticSearchSettingsResponse {
+    pub embedding_model_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateSemanticSearchSettingsRequest {
+    pub embedding_model_id: Option<Uuid>,
+}
+
 #[derive(Serialize)]
 pub struct ProxySettingsResponse {
     pub enabled: bool,
@@ -141,6 +153,45 @@ pub async fn update_default_language(
     }
 }
 
+// Admin endpoint to get the embedding model used for semantic search
+pub async fn get_semantic_search_settings(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+) -> Result<Json<SemanticSearchSettingsResponse>, StatusCode> {
+    match get_semantic_search_model_id().await {
+        Ok(embedding_model_id) => Ok(Json(SemanticSearchSettingsResponse { embedding_model_id })),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Admin endpoint to set the embedding model used for semantic search.
+// Changing the model re-indexes conversations and files in the background.
+pub async fn update_semantic_search_settings(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+    Json(request): Json<UpdateSemanticSearchSettingsRequest>,
+) -> Result<Json<SemanticSearchSettingsResponse>, StatusCode> {
+    if let Some(model_id) = request.embedding_model_id {
+        match get_model_by_id(model_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(StatusCode::BAD_REQUEST),
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+        // Chat-only models and providers without embeddings cannot index anything
+        if let Err(e) = crate::rag::check_embedding_model(model_id).await {
+            eprintln!("Model {} cannot be used for semantic search: {}", model_id, e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    if let Err(_) = set_semantic_search_model_id(request.embedding_model_id).await {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    crate::rag::notify_semantic_indexer();
+
+    Ok(Json(SemanticSearchSettingsResponse {
+        embedding_model_id: request.embedding_model_id,
+    }))
+}
+
 // Admin endpoint to get proxy settings
 pub async fn get_proxy_settings(
     Extension(_auth_user): Extension<AuthenticatedUser>,
@@ -216,6 +216,7 @@ pub async fn delete_download(
 #[derive(Debug, Clone, Serialize)]
 pub struct DownloadProgressUpdate {
     pub id: String,
+    pub download_type: String,
     pub status: String,
     pub phase: Option<String>,
     pub current: Option<i64>,
@@ -230,6 +231,7 @@ impl From<&DownloadInstance> for DownloadProgressUpdate {
     fn from(download: &DownloadInstance) -> Self {
         DownloadProgressUpdate {
             id: download.id.to_string(),
+            download_type: download.download_type.clone(),
             status: download.status.as_str().to_string(),
             phase: download
                 .progress_data
@@ -0,0 +1,112 @@
+use axum::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ai::providers::openai_types::EmbeddingInput;
+use crate::ai::usage::{check_usage_quotas, estimate_tokens, record_token_usage, TokenCounts};
+use crate::api::chat::create_ai_provider_with_model_id;
+use crate::api::errors::{ApiResult, AppError, ErrorCode};
+use crate::api::middleware::AuthenticatedUser;
+use crate::database::{
+    models::USAGE_SOURCE_EMBEDDINGS,
+    queries::{
+        models::get_model_by_id, providers::get_provider_by_id,
+        user_group_providers::get_providers_for_user,
+    },
+};
+
+/// Largest number of texts accepted in one request
+pub(crate) const MAX_EMBEDDING_INPUTS: usize = 2048;
+
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingsRequest {
+    pub model_id: Uuid,
+    /// A single text or a batch of texts
+    pub input: EmbeddingInput,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingsResponse {
+    pub model_id: Uuid,
+    /// One vector per input text, in input order
+    pub embeddings: Vec<Vec<f32>>,
+    pub dimensions: usize,
+    /// Estimated, since not every provider reports token counts for embeddings
+    pub prompt_tokens: u32,
+}
+
+/// Embed texts with a model of a provider the user's groups give access to
+pub async fn create_embeddings(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Json(request): Json<EmbeddingsRequest>,
+) -> ApiResult<Json<EmbeddingsResponse>> {
+    let texts = request.input.into_texts();
+    if texts.is_empty() {
+        return Err(AppError::new(
+            ErrorCode::ValidMissingRequiredField,
+            "At least one input text is required",
+        ));
+    }
+    if texts.len() > MAX_EMBEDDING_INPUTS {
+        return Err(AppError::new(
+            ErrorCode::ValidInvalidInput,
+            format!("At most {} input texts are allowed per request", MAX_EMBEDDING_INPUTS),
+        ));
+    }
+
+    let model = get_model_by_id(request.model_id)
+        .await?
+        .filter(|model| model.enabled)
+        .ok_or_else(|| AppError::new(ErrorCode::ResourceModelNotFound, "Model not found"))?;
+    let provider = get_provider_by_id(model.provider_id)
+        .await?
+        .ok_or_else(|| AppError::new(ErrorCode::ResourceProviderNotFound, "Provider not found"))?;
+    if !provider.enabled {
+        return Err(AppError::new(
+            ErrorCode::ResourceProviderDisabled,
+            "Provider is disabled",
+        ));
+    }
+
+    let allowed_providers = get_providers_for_user(auth_user.user_id).await?;
+    if !allowed_providers.iter().any(|allowed| allowed.id == provider.id) {
+        return Err(AppError::new(
+            ErrorCode::AuthzInsufficientPermissions,
+            "You do not have access to this model's provider",
+        ));
+    }
+
+    if let Some(reason) = check_usage_quotas(auth_user.user_id).await? {
+        return Err(AppError::new(ErrorCode::QuotaExceeded, reason));
+    }
+
+    let ai_provider = create_ai_provider_with_model_id(&provider, Some(model.id))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::SystemInternalError, e.to_string()))?;
+    let embeddings = ai_provider
+        .embed(&model.name, &texts)
+        .await
+        .map_err(|e| AppError::new(ErrorCode::SystemExternalServiceError, e.to_string()))?;
+
+    let prompt_tokens: u32 = texts.iter().map(|text| estimate_tokens(text)).sum();
+    record_token_usage(
+        auth_user.user_id,
+        None,
+        provider.id,
+        model.id,
+        USAGE_SOURCE_EMBEDDINGS,
+        TokenCounts {
+            prompt_tokens,
+            completion_tokens: 0,
+            is_estimated: true,
+        },
+    )
+    .await;
+
+    Ok(Json(EmbeddingsResponse {
+        model_id: model.id,
+        dimensions: embeddings.first().map_or(0, Vec::len),
+        embeddings,
+        prompt_tokens,
+    }))
+}
@@ -55,6 +55,12 @@ pub enum ErrorCode {
     UserRootCreationFailed,
     UserUpdateFailed,
     UserDeletionFailed,
+
+    // Usage errors (QUOTA_xxx)
+    QuotaExceeded,
+
+    // Chat errors (CHAT_xxx)
+    ChatResponseSchemaMismatch,
 }
 
 impl ErrorCode {
@@ -99,6 +105,12 @@ impl ErrorCode {
             ErrorCode::UserRootCreationFailed => "USER_ROOT_CREATION_FAILED",
             ErrorCode::UserUpdateFailed => "USER_UPDATE_FAILED",
             ErrorCode::UserDeletionFailed => "USER_DELETION_FAILED",
+
+            // Usage
+            ErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+
+            // Chat
+            ErrorCode::ChatResponseSchemaMismatch => "CHAT_RESPONSE_SCHEMA_MISMATCH",
         }
     }
 
@@ -136,6 +148,12 @@ impl ErrorCode {
                 StatusCode::CONFLICT
             }
 
+            // 429 Too Many Requests
+            ErrorCode::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+
+            // 502 Bad Gateway: the model's answer was unusable
+            ErrorCode::ChatResponseSchemaMismatch => StatusCode::BAD_GATEWAY,
+
             // 500 Internal Server Error
             ErrorCode::AuthTokenGenerationFailed
             | ErrorCode::AuthTokenStorageFailed
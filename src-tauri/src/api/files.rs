@@ -169,6 +169,7 @@ async fn process_file_upload(
     };
 
     let file = files::create_file(file_create_data).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    crate::rag::notify_semantic_indexer();
 
     Ok(Json(UploadFileResponse { file }))
 }
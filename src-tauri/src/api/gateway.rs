@@ -0,0 +1,508 @@
+//! OpenAI-compatible gateway
+//!
+//! Serves `/v1/chat/completions`, `/v1/models` and `/v1/embeddings` so tools that speak the
+//! OpenAI protocol can use the providers configured in the app. Models are exposed to a user
+//! through the same group-to-provider assignments as the rest of the app.
+
+use axum::{
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
+    Extension, Json,
+};
+use futures_util::StreamExt;
+use std::convert::Infallible;
+use uuid::Uuid;
+
+use crate::ai::core::providers::{ChatMessage, ChatRequest, Usage};
+use crate::ai::providers::openai_types as openai;
+use crate::ai::usage::{
+    check_usage_quotas, estimate_prompt_tokens, estimate_tokens, record_token_usage,
+    resolve_token_counts, TokenCounts,
+};
+use crate::api::chat::create_ai_provider_with_model_id;
+use crate::api::embeddings::MAX_EMBEDDING_INPUTS;
+use crate::api::middleware::AuthenticatedUser;
+use crate::database::{
+    models::{Model, ModelParameters, Provider, USAGE_SOURCE_GATEWAY},
+    queries::{models::get_models_by_provider_id, user_group_providers::get_providers_for_user},
+};
+
+/// Error returned in the OpenAI error format
+pub struct GatewayError {
+    status: StatusCode,
+    body: openai::ErrorResponse,
+}
+
+impl GatewayError {
+    fn invalid_request(message: &str) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            body: openai::ErrorResponse::invalid_request(message),
+        }
+    }
+
+    fn model_not_found(model: &str) -> Self {
+        let mut body = openai::ErrorResponse::invalid_request(&format!(
+            "The model '{}' does not exist or you do not have access to it",
+            model
+        ));
+        body.error.param = Some("model".to_string());
+        body.error.code = Some("model_not_found".to_string());
+        Self {
+            status: StatusCode::NOT_FOUND,
+            body,
+        }
+    }
+
+    fn provider_error(message: &str) -> Self {
+        Self {
+            status: StatusCode::BAD_GATEWAY,
+            body: openai::ErrorResponse::server_error(message),
+        }
+    }
+
+    fn quota_exceeded(message: &str) -> Self {
+        let mut body = openai::ErrorResponse::new(message, "insufficient_quota");
+        body.error.code = Some("insufficient_quota".to_string());
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            body,
+        }
+    }
+
+    fn server_error(message: &str) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            body: openai::ErrorResponse::server_error(message),
+        }
+    }
+}
+
+impl IntoResponse for GatewayError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body)).into_response()
+    }
+}
+
+pub type GatewayResult<T> = Result<T, GatewayError>;
+
+/// A model the user may call through the gateway
+struct GatewayModel {
+    /// Id exposed to clients, `{provider}/{model name}`
+    id: String,
+    provider: Provider,
+    model: Model,
+}
+
+fn provider_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Enabled models of the enabled providers assigned to the user's groups
+async fn list_gateway_models(user_id: Uuid) -> GatewayResult<Vec<GatewayModel>> {
+    let providers = get_providers_for_user(user_id)
+        .await
+        .map_err(|e| GatewayError::server_error(&format!("Failed to load providers: {}", e)))?;
+
+    let mut gateway_models = Vec::new();
+    for provider in providers.into_iter().filter(|provider| provider.enabled) {
+        let models = get_models_by_provider_id(provider.id)
+            .await
+            .map_err(|e| GatewayError::server_error(&format!("Failed to load models: {}", e)))?;
+
+        let slug = provider_slug(&provider.name);
+        for model in models.into_iter().filter(|model| model.enabled) {
+            gateway_models.push(GatewayModel {
+                id: format!("{}/{}", slug, model.name),
+                provider: provider.clone(),
+                model,
+            });
+        }
+    }
+
+    Ok(gateway_models)
+}
+
+/// Resolve a requested model by gateway id, model id, or a model name or alias that is unique
+/// among the user's models
+async fn resolve_gateway_model(user_id: Uuid, requested: &str) -> GatewayResult<GatewayModel> {
+    let mut models = list_gateway_models(user_id).await?;
+
+    if let Some(position) = models
+        .iter()
+        .position(|m| m.id == requested || m.model.id.to_string() == requested)
+    {
+        return Ok(models.swap_remove(position));
+    }
+
+    let mut by_name: Vec<GatewayModel> = models
+        .into_iter()
+        .filter(|m| m.model.name == requested || m.model.alias == requested)
+        .collect();
+    match by_name.len() {
+        1 => Ok(by_name.remove(0)),
+        0 => Err(GatewayError::model_not_found(requested)),
+        _ => Err(GatewayError::invalid_request(&format!(
+            "The model '{}' is ambiguous; use one of: {}",
+            requested,
+            by_name.iter().map(|m| m.id.as_str()).collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+/// Request parameters override the defaults configured on the model
+fn request_parameters(model: &Model, request: &openai::ChatCompletionRequest) -> ModelParameters {
+    let mut parameters = model.parameters.clone().unwrap_or_default();
+    if request.temperature.is_some() {
+        parameters.temperature = request.temperature;
+    }
+    if request.top_p.is_some() {
+        parameters.top_p = request.top_p;
+    }
+    if let Some(top_k) = request.top_k {
+        parameters.top_k = Some(top_k.max(0) as u32);
+    }
+    if let Some(max_tokens) = request.max_tokens {
+        parameters.max_tokens = Some(max_tokens.max(0) as u32);
+    }
+    if request.frequency_penalty.is_some() {
+        parameters.frequency_penalty = request.frequency_penalty;
+    }
+    if request.presence_penalty.is_some() {
+        parameters.presence_penalty = request.presence_penalty;
+    }
+    if request.stop.is_some() {
+        parameters.stop = request.stop.clone();
+    }
+    parameters
+}
+
+/// Reject the request when one of the user's group quotas is used up
+async fn enforce_usage_quotas(user_id: Uuid) -> GatewayResult<()> {
+    match check_usage_quotas(user_id).await {
+        Ok(None) => Ok(()),
+        Ok(Some(reason)) => Err(GatewayError::quota_exceeded(&reason)),
+        Err(e) => Err(GatewayError::server_error(&format!("Failed to check usage quotas: {}", e))),
+    }
+}
+
+fn completion_id() -> String {
+    format!("chatcmpl-{}", Uuid::new_v4().simple())
+}
+
+/// Refuse what the gateway cannot pass on to providers instead of silently ignoring it
+fn check_supported_features(request: &openai::ChatCompletionRequest) -> GatewayResult<()> {
+    let has_tools = request
+        .tools
+        .as_ref()
+        .is_some_and(|tools| !tools.is_null() && tools.as_array().is_none_or(|tools| !tools.is_empty()));
+    if has_tools || request.tool_choice.as_ref().is_some_and(|choice| !choice.is_null()) {
+        return Err(GatewayError::invalid_request("Tool calling is not supported by the gateway"));
+    }
+    if request.messages.iter().any(|message| message.role == "tool" || message.role == "function") {
+        return Err(GatewayError::invalid_request("Tool messages are not supported by the gateway"));
+    }
+    Ok(())
+}
+
+/// SSE event carrying one `chat.completion.chunk`
+fn chunk_event(
+    id: &str,
+    created: i64,
+    model: &str,
+    delta: openai::ChatMessageDelta,
+    finish_reason: Option<String>,
+) -> Event {
+    let chunk = openai::ChatCompletionChunk {
+        id: id.to_string(),
+        object: "chat.completion.chunk".to_string(),
+        created,
+        model: model.to_string(),
+        choices: vec![openai::ChatChoiceDelta {
+            index: 0,
+            delta,
+            finish_reason,
+        }],
+    };
+    Event::default().data(serde_json::to_string(&chunk).unwrap_or_default())
+}
+
+pub async fn list_models(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+) -> GatewayResult<Json<openai::ModelsResponse>> {
+    let models = list_gateway_models(auth_user.user_id).await?;
+
+    Ok(Json(openai::ModelsResponse {
+        object: "list".to_string(),
+        data: models
+            .into_iter()
+            .map(|m| openai::ModelInfo {
+                id: m.id,
+                object: "model".to_string(),
+                created: m.model.created_at.timestamp(),
+                owned_by: m.provider.name,
+                permission: Vec::new(),
+                root: m.model.name,
+                parent: None,
+            })
+            .collect(),
+    }))
+}
+
+pub async fn chat_completions(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    request: Result<Json<openai::ChatCompletionRequest>, JsonRejection>,
+) -> GatewayResult<Response> {
+    // Malformed requests get an OpenAI-style 400 rather than axum's plain-text rejection
+    let Json(request) = request.map_err(|rejection| GatewayError::invalid_request(&rejection.body_text()))?;
+    if request.messages.is_empty() {
+        return Err(GatewayError::invalid_request("'messages' must contain at least one message"));
+    }
+    check_supported_features(&request)?;
+
+    let GatewayModel { id: model_id, provider, model } =
+        resolve_gateway_model(auth_user.user_id, &request.model).await?;
+    enforce_usage_quotas(auth_user.user_id).await?;
+
+    let ai_provider = create_ai_provider_with_model_id(&provider, Some(model.id))
+        .await
+        .map_err(|e| GatewayError::provider_error(&format!("Error creating AI provider: {}", e)))?;
+
+    let chat_request = ChatRequest {
+        messages: request
+            .messages
+            .iter()
+            .map(|message| ChatMessage::text(&message.role, &message.content))
+            .collect(),
+        model_name: model.name.clone(),
+        model_id: model.id,
+        provider_id: provider.id,
+        stream: request.stream,
+        parameters: Some(request_parameters(&model, &request)),
+        tools: None,
+        tool_choice: None,
+        response_format: None,
+    };
+    let estimated_prompt_tokens = estimate_prompt_tokens(&chat_request.messages);
+
+    println!(
+        "Gateway chat completion for user {} with model {}",
+        auth_user.user_id, model_id
+    );
+
+    if !request.stream || !ai_provider.supports_streaming() {
+        let response = ai_provider
+            .chat(chat_request)
+            .await
+            .map_err(|e| GatewayError::provider_error(&e.to_string()))?;
+
+        let token_counts =
+            resolve_token_counts(response.usage.as_ref(), estimated_prompt_tokens, &response.content);
+        record_token_usage(
+            auth_user.user_id,
+            None,
+            provider.id,
+            model.id,
+            USAGE_SOURCE_GATEWAY,
+            token_counts,
+        )
+        .await;
+
+        if request.stream {
+            // The provider cannot stream, so the whole answer goes out as a single chunk
+            let id = completion_id();
+            let created = chrono::Utc::now().timestamp();
+            let events = vec![
+                chunk_event(
+                    &id,
+                    created,
+                    &model_id,
+                    openai::ChatMessageDelta {
+                        role: Some("assistant".to_string()),
+                        content: Some(response.content),
+                        reasoning_content: response.reasoning,
+                    },
+                    None,
+                ),
+                chunk_event(
+                    &id,
+                    created,
+                    &model_id,
+                    openai::ChatMessageDelta { role: None, content: None, reasoning_content: None },
+                    response.finish_reason.or_else(|| Some("stop".to_string())),
+                ),
+                Event::default().data("[DONE]"),
+            ];
+            let stream = futures_util::stream::iter(events.into_iter().map(Ok::<_, Infallible>));
+            return Ok(Sse::new(stream).into_response());
+        }
+
+        return Ok(Json(openai::ChatCompletionResponse {
+            id: completion_id(),
+            object: "chat.completion".to_string(),
+            created: chrono::Utc::now().timestamp(),
+            model: model_id,
+            choices: vec![openai::ChatChoice {
+                index: 0,
+                message: openai::ChatMessage {
+                    role: "assistant".to_string(),
+                    content: response.content,
+                    name: None,
+                },
+                finish_reason: response.finish_reason.or_else(|| Some("stop".to_string())),
+            }],
+            usage: openai::Usage {
+                prompt_tokens: token_counts.prompt_tokens as i32,
+                completion_tokens: token_counts.completion_tokens as i32,
+                total_tokens: token_counts.total_tokens() as i32,
+            },
+        })
+        .into_response());
+    }
+
+    let mut provider_stream = ai_provider
+        .chat_stream(chat_request)
+        .await
+        .map_err(|e| GatewayError::provider_error(&e.to_string()))?;
+
+    let id = completion_id();
+    let created = chrono::Utc::now().timestamp();
+    let (user_id, provider_id, model_uuid) = (auth_user.user_id, provider.id, model.id);
+    let stream_chunk = move |delta: openai::ChatMessageDelta, finish_reason: Option<String>| {
+        chunk_event(&id, created, &model_id, delta, finish_reason)
+    };
+
+    let stream = async_stream::stream! {
+        yield Ok::<_, Infallible>(stream_chunk(
+            openai::ChatMessageDelta { role: Some("assistant".to_string()), content: None, reasoning_content: None },
+            None,
+        ));
+
+        let mut finished = false;
+        let mut content = String::new();
+        let mut usage: Option<Usage> = None;
+        while let Some(item) = provider_stream.next().await {
+            match item {
+                Ok(chunk) => {
+                    if let Some(chunk_usage) = chunk.usage {
+                        usage.get_or_insert_with(Usage::default).merge(chunk_usage);
+                    }
+                    if chunk.content.is_none() && chunk.reasoning.is_none() && chunk.finish_reason.is_none() {
+                        continue;
+                    }
+                    if let Some(delta) = &chunk.content {
+                        content.push_str(delta);
+                    }
+                    finished |= chunk.finish_reason.is_some();
+                    yield Ok(stream_chunk(
+                        openai::ChatMessageDelta {
+                            role: None,
+                            content: chunk.content,
+                            reasoning_content: chunk.reasoning,
+                        },
+                        chunk.finish_reason,
+                    ));
+                }
+                Err(e) => {
+                    let error = openai::ErrorResponse::server_error(&e.to_string());
+                    yield Ok(Event::default().data(serde_json::to_string(&error).unwrap_or_default()));
+                    finished = true;
+                    break;
+                }
+            }
+        }
+
+        if !finished {
+            yield Ok(stream_chunk(
+                openai::ChatMessageDelta { role: None, content: None, reasoning_content: None },
+                Some("stop".to_string()),
+            ));
+        }
+        yield Ok(Event::default().data("[DONE]"));
+
+        let token_counts = resolve_token_counts(usage.as_ref(), estimated_prompt_tokens, &content);
+        record_token_usage(user_id, None, provider_id, model_uuid, USAGE_SOURCE_GATEWAY, token_counts).await;
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response())
+}
+
+pub async fn embeddings(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    request: Result<Json<openai::EmbeddingRequest>, JsonRejection>,
+) -> GatewayResult<Json<openai::EmbeddingResponse>> {
+    let Json(request) = request.map_err(|rejection| GatewayError::invalid_request(&rejection.body_text()))?;
+    if request
+        .encoding_format
+        .as_deref()
+        .is_some_and(|format| format != "float")
+    {
+        return Err(GatewayError::invalid_request("Only the 'float' encoding format is supported"));
+    }
+
+    let texts = request.input.into_texts();
+    if texts.is_empty() {
+        return Err(GatewayError::invalid_request("'input' must not be empty"));
+    }
+    if texts.len() > MAX_EMBEDDING_INPUTS {
+        return Err(GatewayError::invalid_request(&format!(
+            "'input' must contain at most {} texts",
+            MAX_EMBEDDING_INPUTS
+        )));
+    }
+
+    let GatewayModel { id: model_id, provider, model } =
+        resolve_gateway_model(auth_user.user_id, &request.model).await?;
+    enforce_usage_quotas(auth_user.user_id).await?;
+
+    let ai_provider = create_ai_provider_with_model_id(&provider, Some(model.id))
+        .await
+        .map_err(|e| GatewayError::provider_error(&format!("Error creating AI provider: {}", e)))?;
+    let vectors = ai_provider
+        .embed(&model.name, &texts)
+        .await
+        .map_err(|e| GatewayError::provider_error(&e.to_string()))?;
+
+    // Embedding providers do not report token counts through this path
+    let prompt_tokens: u32 = texts.iter().map(|text| estimate_tokens(text)).sum();
+    let token_counts = TokenCounts {
+        prompt_tokens,
+        completion_tokens: 0,
+        is_estimated: true,
+    };
+    record_token_usage(
+        auth_user.user_id,
+        None,
+        provider.id,
+        model.id,
+        USAGE_SOURCE_GATEWAY,
+        token_counts,
+    )
+    .await;
+
+    Ok(Json(openai::EmbeddingResponse {
+        object: "list".to_string(),
+        data: vectors
+            .into_iter()
+            .enumerate()
+            .map(|(index, embedding)| openai::EmbeddingData {
+                object: "embedding".to_string(),
+                index: index as i32,
+                embedding,
+            })
+            .collect(),
+        model: model_id,
+        usage: openai::EmbeddingUsage {
+            prompt_tokens: prompt_tokens as i32,
+            total_tokens: prompt_tokens as i32,
+        },
+    }))
+}
@@ -8,15 +8,18 @@ use axum::{
 use crate::api::app::is_desktop_app;
 use crate::api::permissions::{check_permission, permissions};
 use crate::auth::AuthService;
-use crate::database::models::User;
+use crate::database::models::{User, API_KEY_PREFIX};
 
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub user_id: uuid::Uuid,
     pub user: User,
+    /// Personal API key the request was authenticated with, if any
+    pub api_key_id: Option<uuid::Uuid>,
 }
 
-/// Authentication middleware that validates JWT token and adds user to request extensions
+/// Authentication middleware that validates a JWT token or personal API key and adds the user
+/// to request extensions
 pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, StatusCode> {
     // Extract token from Authorization header
     let auth_header = req
@@ -31,6 +34,22 @@ pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, S
 
     let auth_service = AuthService::default();
 
+    // Personal API keys are checked on desktop too, so their scopes still apply
+    if token.starts_with(API_KEY_PREFIX) {
+        return match auth_service.get_user_by_api_key(token).await {
+            Ok(Some((user, api_key_id))) => {
+                req.extensions_mut().insert(AuthenticatedUser {
+                    user_id: user.id,
+                    user,
+                    api_key_id: Some(api_key_id),
+                });
+                Ok(next.run(req).await)
+            }
+            Ok(None) => Err(StatusCode::UNAUTHORIZED),
+            Err(_err) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        };
+    }
+
     // For desktop app, get or create default admin user
     if is_desktop_app() {
         match auth_service.get_default_admin_user().await {
@@ -38,6 +57,7 @@ pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, S
                 req.extensions_mut().insert(AuthenticatedUser {
                     user_id: user.id,
                     user,
+                    api_key_id: None,
                 });
                 return Ok(next.run(req).await);
             }
@@ -51,6 +71,7 @@ pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, S
             req.extensions_mut().insert(AuthenticatedUser {
                 user_id: user.id,
                 user,
+                api_key_id: None,
             });
             Ok(next.run(req).await)
         }
@@ -316,6 +337,34 @@ pub async fn config_proxy_edit_middleware(
     Ok(next.run(req).await)
 }
 
+/// Middleware that checks for config::semantic-search::read permission
+pub async fn config_semantic_search_read_middleware(
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user = get_authenticated_user(&req)?;
+
+    if !check_permission(user, permissions::CONFIG_SEMANTIC_SEARCH_READ) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Middleware that checks for config::semantic-search::edit permission
+pub async fn config_semantic_search_edit_middleware(
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user = get_authenticated_user(&req)?;
+
+    if !check_permission(user, permissions::CONFIG_SEMANTIC_SEARCH_EDIT) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(req).await)
+}
+
 /// Middleware that checks for config::repositories::read permission
 pub async fn repositories_read_middleware(
     req: Request,
@@ -363,3 +412,38 @@ pub async fn repositories_delete_middleware(
     }
     Ok(next.run(req).await)
 }
+
+/// Middleware that checks for config::rag-databases::read permission
+pub async fn rag_databases_read_middleware(
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user = get_authenticated_user(&req)?;
+    if !check_permission(user, permissions::RAG_DATABASES_READ) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(next.run(req).await)
+}
+
+/// Middleware that checks for config::rag-databases::edit permission
+pub async fn rag_databases_edit_middleware(
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user = get_authenticated_user(&req)?;
+    if !check_permission(user, permissions::RAG_DATABASES_EDIT) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(next.run(req).await)
+}
+
+/// Middleware that checks for usage::read permission
+pub async fn usage_read_middleware(req: Request, next: Next) -> Result<Response, StatusCode> {
+    let user = get_authenticated_user(&req)?;
+
+    if !check_permission(user, permissions::USAGE_READ) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(req).await)
+}
@@ -5,8 +5,10 @@ pub mod chat;
 pub mod configuration;
 
 pub mod download_instances;
+pub mod embeddings;
 pub mod errors;
 pub mod files;
+pub mod gateway;
 pub mod hub;
 pub mod middleware;
 pub mod model_uploads;
@@ -17,6 +19,8 @@ pub mod providers;
 pub mod rag_providers;
 pub mod rag_repositories;
 pub mod repositories;
+pub mod usage;
 pub mod user;
+pub mod user_api_keys;
 pub mod user_groups;
 pub mod user_settings;
@@ -114,6 +114,7 @@ pub struct CreateModelWithFilesRequest {
     pub capabilities: Option<ModelCapabilities>,
     pub parameters: Option<ModelParameters>,
     pub settings: Option<ModelSettings>,
+    pub context_length: Option<i32>,
 }
 
 /// Shared model creation and file processing logic
@@ -266,6 +267,8 @@ async fn create_model_with_files(request: CreateModelWithFilesRequest) -> Result
             .or_else(|| Some(ModelCapabilities::new())),
         parameters: request.parameters,
         settings: request.settings,
+        fallback_chain: None,
+        context_length: request.context_length,
     };
 
     // Create the model record with the pre-generated ID
@@ -492,6 +495,8 @@ pub struct DownloadFromRepositoryRequest {
     pub capabilities: Option<ModelCapabilities>,
     pub parameters: Option<ModelParameters>,
     pub settings: Option<ModelSettings>,
+    #[serde(default)]
+    pub context_length: Option<i32>, // From the hub entry, when the model comes from the hub
 }
 
 /// Upload multiple model files and auto-commit as a model
@@ -750,6 +755,7 @@ pub async fn upload_multiple_files_and_commit(
         capabilities,
         parameters: None, // No parameters available in upload request
         settings,
+        context_length: None,
     })
     .await
     .map_err(|e| {
@@ -790,6 +796,7 @@ pub async fn initiate_repository_download(
             parameters: request.parameters.clone(),
             settings: request.settings.clone(),
         },
+        download_type: None,
     };
 
     let download_instance =
@@ -1144,6 +1151,7 @@ pub async fn initiate_repository_download(
                     capabilities: request.capabilities,
                     parameters: request.parameters,
                     settings: request.settings,
+                    context_length: request.context_length,
                 })
                 .await
                 {
@@ -5,11 +5,12 @@ use axum::{
 };
 use uuid::Uuid;
 
-use crate::ai::DeviceType;
+use crate::ai::{AIProvider, DeviceType, LoadedModel};
+use crate::api::chat::create_ai_provider_with_model_id;
 use crate::api::errors::{ApiResult, AppError, ErrorCode};
 use crate::api::middleware::AuthenticatedUser;
 use crate::database::{
-    models::{CreateModelRequest, Model, UpdateModelRequest},
+    models::{CreateModelRequest, FallbackTarget, Model, ModelSyncResponse, UpdateModelRequest},
     queries::{models, providers, user_group_providers},
 };
 
@@ -19,6 +20,9 @@ pub async fn create_model(
     Path(provider_id): Path<Uuid>,
     Json(request): Json<CreateModelRequest>,
 ) -> ApiResult<Json<Model>> {
+    validate_fallback_chain(request.fallback_chain.as_deref()).await?;
+    validate_context_length(request.context_length)?;
+
     match models::create_model(provider_id, request).await {
         Ok(model) => Ok(Json(model)),
         Err(e) => {
@@ -40,6 +44,10 @@ pub async fn update_model(
     Path(model_id): Path<Uuid>,
     Json(request): Json<UpdateModelRequest>,
 ) -> ApiResult<Json<Model>> {
+    validate_fallback_chain(request.fallback_chain.as_deref()).await?;
+    // 0 clears the context length
+    validate_context_length(request.context_length.filter(|length| *length != 0))?;
+
     match models::update_model(model_id, request).await {
         Ok(Some(model)) => Ok(Json(model)),
         Ok(None) => Err(AppError::not_found("Resource")),
@@ -50,6 +58,35 @@ pub async fn update_model(
     }
 }
 
+fn validate_context_length(context_length: Option<i32>) -> ApiResult<()> {
+    match context_length {
+        Some(length) if length <= 0 => Err(AppError::new(
+            ErrorCode::ValidInvalidInput,
+            "context_length must be a positive number of tokens",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reject fallback chains that reference unknown models or pair a model with the wrong provider
+async fn validate_fallback_chain(chain: Option<&[FallbackTarget]>) -> ApiResult<()> {
+    let Some(chain) = chain else {
+        return Ok(());
+    };
+
+    match models::fallback_chain_is_valid(chain).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(AppError::new(
+            ErrorCode::ValidInvalidInput,
+            "Every fallback chain entry must reference an existing model of the given provider",
+        )),
+        Err(e) => {
+            eprintln!("Failed to validate fallback chain: {}", e);
+            Err(AppError::internal_error("Database operation failed"))
+        }
+    }
+}
+
 #[axum::debug_handler]
 pub async fn delete_model(
     Extension(_auth_user): Extension<AuthenticatedUser>,
@@ -462,6 +499,8 @@ pub async fn enable_model(
             is_active: None,
             capabilities: None,
             settings: None,
+            fallback_chain: None,
+            context_length: None,
         },
     )
     .await
@@ -491,6 +530,8 @@ pub async fn disable_model(
             is_active: None,
             capabilities: None,
             settings: None,
+            fallback_chain: None,
+            context_length: None,
         },
     )
     .await
@@ -566,4 +607,90 @@ pub async fn list_enabled_provider_models(
 ) -> ApiResult<Json<Vec<Model>>> {
     let models = list_provider_models_base(&auth_user, provider_id, true).await?;
     Ok(Json(models))
-}
\ No newline at end of file
+}
+
+/// Create the AI provider for a remote provider, rejecting local providers whose models
+/// are managed by the app
+async fn create_remote_ai_provider(provider_id: Uuid) -> ApiResult<Box<dyn AIProvider>> {
+    let provider = match providers::get_provider_by_id(provider_id).await {
+        Ok(Some(provider)) => provider,
+        Ok(None) => return Err(AppError::not_found("Model provider")),
+        Err(e) => {
+            eprintln!("Failed to get model provider {}: {}", provider_id, e);
+            return Err(AppError::internal_error("Database operation failed"));
+        }
+    };
+
+    if provider.provider_type == "local" {
+        return Err(AppError::new(
+            ErrorCode::ValidInvalidInput,
+            "Only available for remote providers",
+        ));
+    }
+
+    create_ai_provider_with_model_id(&provider, None)
+        .await
+        .map_err(|e| {
+            eprintln!("Failed to create AI provider {}: {}", provider_id, e);
+            AppError::internal_error("Failed to create AI provider")
+        })
+}
+
+/// Fetch the vendor's model list for an API provider and sync it into the models table
+pub async fn sync_provider_models(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+    Path(provider_id): Path<Uuid>,
+) -> ApiResult<Json<ModelSyncResponse>> {
+    let ai_provider = create_remote_ai_provider(provider_id).await?;
+
+    let discovered = ai_provider.list_models().await.map_err(|e| {
+        eprintln!("Failed to list models for provider {}: {}", provider_id, e);
+        AppError::new(
+            ErrorCode::SystemExternalServiceError,
+            format!("Failed to fetch models from provider: {}", e),
+        )
+    })?;
+
+    let requests: Vec<CreateModelRequest> = discovered
+        .into_iter()
+        .map(|model| CreateModelRequest {
+            provider_id,
+            alias: model.display_name.unwrap_or_else(|| model.name.clone()),
+            name: model.name,
+            description: model.description,
+            enabled: Some(false),
+            capabilities: model.capabilities,
+            parameters: None,
+            settings: None,
+            fallback_chain: None,
+            context_length: None,
+        })
+        .collect();
+
+    match models::sync_provider_models(provider_id, &requests).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            eprintln!("Failed to sync models for provider {}: {}", provider_id, e);
+            Err(AppError::internal_error("Database operation failed"))
+        }
+    }
+}
+
+/// List the models a self-hosted server (Ollama, llama.cpp) currently has loaded
+pub async fn list_loaded_models(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+    Path(provider_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<LoadedModel>>> {
+    let ai_provider = create_remote_ai_provider(provider_id).await?;
+
+    match ai_provider.loaded_models().await {
+        Ok(loaded) => Ok(Json(loaded)),
+        Err(e) => {
+            eprintln!("Failed to get loaded models for provider {}: {}", provider_id, e);
+            Err(AppError::new(
+                ErrorCode::SystemExternalServiceError,
+                format!("Failed to get load state from provider: {}", e),
+            ))
+        }
+    }
+}
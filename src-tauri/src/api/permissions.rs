@@ -21,6 +21,8 @@ pub mod permissions {
     pub const CONFIG_APPEARANCE_EDIT: &str = "config::appearance::edit";
     pub const CONFIG_PROXY_READ: &str = "config::proxy::read";
     pub const CONFIG_PROXY_EDIT: &str = "config::proxy::edit";
+    pub const CONFIG_SEMANTIC_SEARCH_READ: &str = "config::semantic-search::read";
+    pub const CONFIG_SEMANTIC_SEARCH_EDIT: &str = "config::semantic-search::edit";
 
     // User settings permissions
     pub const SETTINGS_READ: &str = "settings::read";
@@ -39,8 +41,49 @@ pub mod permissions {
     pub const REPOSITORIES_DELETE: &str = "config::repositories::delete";
     pub const REPOSITORIES_CREATE: &str = "config::repositories::create";
 
+    // RAG database index permissions
+    pub const RAG_DATABASES_READ: &str = "config::rag-databases::read";
+    pub const RAG_DATABASES_EDIT: &str = "config::rag-databases::edit";
+
+    // Usage permissions
+    pub const USAGE_READ: &str = "usage::read";
+
     // Wildcard permissions
     pub const ALL: &str = "*";
+
+    /// Every grantable permission, excluding wildcards
+    pub const ALL_PERMISSIONS: &[&str] = &[
+        USERS_READ,
+        USERS_EDIT,
+        USERS_DELETE,
+        USERS_CREATE,
+        GROUPS_READ,
+        GROUPS_EDIT,
+        GROUPS_DELETE,
+        GROUPS_CREATE,
+        CONFIG_USER_REGISTRATION_READ,
+        CONFIG_USER_REGISTRATION_EDIT,
+        CONFIG_APPEARANCE_READ,
+        CONFIG_APPEARANCE_EDIT,
+        CONFIG_PROXY_READ,
+        CONFIG_PROXY_EDIT,
+        CONFIG_SEMANTIC_SEARCH_READ,
+        CONFIG_SEMANTIC_SEARCH_EDIT,
+        SETTINGS_READ,
+        SETTINGS_EDIT,
+        SETTINGS_DELETE,
+        PROVIDERS_READ,
+        PROVIDERS_EDIT,
+        PROVIDERS_DELETE,
+        PROVIDERS_CREATE,
+        REPOSITORIES_READ,
+        REPOSITORIES_EDIT,
+        REPOSITORIES_DELETE,
+        REPOSITORIES_CREATE,
+        RAG_DATABASES_READ,
+        RAG_DATABASES_EDIT,
+        USAGE_READ,
+    ];
 }
 
 /// Check if the authenticated user has a specific permission
@@ -83,6 +126,20 @@ pub fn check_permission(user: &User, permission: &str) -> bool {
     false
 }
 
+/// Limit a user to the scoped permissions they actually hold, e.g. for a scoped API key.
+/// Group memberships are kept so group-assigned providers stay available.
+pub fn restrict_permissions(mut user: User, scopes: &[String]) -> User {
+    let granted: Vec<&String> = scopes
+        .iter()
+        .filter(|scope| check_permission(&user, scope))
+        .collect();
+
+    for group in user.groups.iter_mut() {
+        group.permissions = serde_json::json!(granted);
+    }
+    user
+}
+
 /// Extract the category from a permission string (e.g., "users::read" -> "users")
 fn get_permission_category(permission: &str) -> Option<&str> {
     permission.split("::").next()
@@ -103,6 +160,7 @@ mod tests {
             description: Some("Test group".to_string()),
             permissions: permissions_json,
             provider_ids: vec![],
+            quotas: None,
             is_protected: false,
             is_active: true,
             created_at: chrono::Utc::now(),
@@ -163,6 +221,21 @@ mod tests {
         assert_eq!(get_permission_category("invalid"), Some("invalid"));
     }
 
+    #[test]
+    fn test_restrict_permissions() {
+        let user = create_test_user_with_permissions(vec!["users::*"]);
+        let scopes = vec![
+            permissions::USERS_READ.to_string(),
+            permissions::GROUPS_READ.to_string(),
+        ];
+
+        let user = restrict_permissions(user, &scopes);
+        assert!(check_permission(&user, permissions::USERS_READ));
+        assert!(!check_permission(&user, permissions::USERS_EDIT));
+        // Scopes cannot grant permissions the user does not hold
+        assert!(!check_permission(&user, permissions::GROUPS_READ));
+    }
+
     #[test]
     fn test_inactive_group_permissions() {
         let permissions_json = json!(vec![permissions::USERS_READ]);
@@ -172,6 +245,7 @@ mod tests {
             description: Some("Test group".to_string()),
             permissions: permissions_json,
             provider_ids: vec![],
+            quotas: None,
             is_protected: false,
             is_active: false, // Inactive group
             created_at: chrono::Utc::now(),
@@ -10,9 +10,10 @@ use crate::{
     api::middleware::AuthenticatedUser,
     database::{
         models::{
-            CreateProjectRequest, ProjectDetailResponse, ProjectListResponse, UpdateProjectRequest,
+            CreateProjectRequest, ProjectDetailResponse, ProjectListResponse, RAGDatabase,
+            SetRAGDatabasesRequest, UpdateProjectRequest, validate_file_context,
         },
-        queries::{get_database_pool, projects},
+        queries::{get_database_pool, projects, rag_attachments},
     },
 };
 
@@ -52,6 +53,21 @@ pub async fn create_project(
         return Err(StatusCode::BAD_REQUEST);
     }
 
+    if let Some(parameters) = &request.parameters {
+        if let Err(e) = parameters.validate() {
+            eprintln!("Invalid project parameters: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    if let Err(e) = validate_file_context(
+        request.file_context_mode.as_deref(),
+        request.file_context_budget,
+    ) {
+        eprintln!("Invalid project file context: {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     match projects::create_project(&pool, user.user_id, &request).await {
         Ok(project) => Ok(Json(project)),
         Err(e) => {
@@ -111,6 +127,21 @@ pub async fn update_project(
         }
     }
 
+    if let Some(parameters) = &request.parameters {
+        if let Err(e) = parameters.validate() {
+            eprintln!("Invalid project parameters: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    if let Err(e) = validate_file_context(
+        request.file_context_mode.as_deref(),
+        request.file_context_budget,
+    ) {
+        eprintln!("Invalid project file context: {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     match projects::update_project(&pool, project_id, user.user_id, &request).await {
         Ok(Some(project)) => Ok(Json(project)),
         Ok(None) => Err(StatusCode::NOT_FOUND),
@@ -180,3 +211,62 @@ pub async fn unlink_conversation(
         }
     }
 }
+
+// List RAG databases attached to project
+pub async fn get_project_rag_databases(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    let pool = get_database_pool().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match projects::get_project_by_id(&pool, project_id, user.user_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get project: {:?}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    match rag_attachments::list_project_rag_databases(project_id).await {
+        Ok(databases) => Ok(Json(databases)),
+        Err(e) => {
+            eprintln!("Failed to list project RAG databases: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Replace RAG databases attached to project
+pub async fn set_project_rag_databases(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(project_id): Path<Uuid>,
+    Json(request): Json<SetRAGDatabasesRequest>,
+) -> Result<Json<Vec<RAGDatabase>>, StatusCode> {
+    let pool = get_database_pool().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match projects::get_project_by_id(&pool, project_id, user.user_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get project: {:?}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    let rag_database_ids =
+        crate::api::assistants::validate_rag_database_ids(request.rag_database_ids).await?;
+
+    if let Err(e) = rag_attachments::set_project_rag_databases(project_id, &rag_database_ids).await {
+        eprintln!("Failed to attach RAG databases to project: {:?}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match rag_attachments::list_project_rag_databases(project_id).await {
+        Ok(databases) => Ok(Json(databases)),
+        Err(e) => {
+            eprintln!("Failed to list project RAG databases: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
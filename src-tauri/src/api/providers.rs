@@ -123,6 +123,8 @@ pub async fn create_provider(
         "groq",
         "gemini",
         "mistral",
+        "ollama",
+        "llamacpp",
         "custom",
     ];
     if !valid_types.contains(&request.provider_type.as_str()) {
@@ -136,7 +138,9 @@ pub async fn create_provider(
     if let Some(true) = request.enabled {
         if request.provider_type != "local" {
             // Check API key
-            if request.api_key.is_none() || request.api_key.as_ref().unwrap().trim().is_empty() {
+            if requires_api_key(&request.provider_type)
+                && (request.api_key.is_none() || request.api_key.as_ref().unwrap().trim().is_empty())
+            {
                 eprintln!("Cannot create enabled provider: API key is required");
                 return Err(AppError::new(
                     crate::api::errors::ErrorCode::ValidInvalidInput,
@@ -180,6 +184,11 @@ fn is_valid_url(url: &str) -> bool {
     reqwest::Url::parse(url).is_ok()
 }
 
+/// Self-hosted servers usually run without authentication
+fn requires_api_key(provider_type: &str) -> bool {
+    !matches!(provider_type, "ollama" | "llamacpp")
+}
+
 pub async fn update_provider(
     Extension(_auth_user): Extension<AuthenticatedUser>,
     Path(provider_id): Path<Uuid>,
@@ -197,7 +206,9 @@ pub async fn update_provider(
                         .api_key
                         .as_ref()
                         .or(current_provider.api_key.as_ref());
-                    if api_key.is_none() || api_key.unwrap().trim().is_empty() {
+                    if requires_api_key(&current_provider.provider_type)
+                        && (api_key.is_none() || api_key.unwrap().trim().is_empty())
+                    {
                         eprintln!(
                             "Cannot enable provider {}: API key is required",
                             provider_id
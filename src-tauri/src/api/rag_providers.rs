@@ -1,6 +1,7 @@
 use axum::{
     extract::{Path, Query},
-    http::StatusCode,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use serde::Deserialize;
@@ -13,8 +14,9 @@ use crate::database::{
         CreateRAGProviderRequest, RAGProvider, RAGProviderListResponse, UpdateRAGProviderRequest,
         CreateRAGDatabaseRequest, RAGDatabase, UpdateRAGDatabaseRequest,
         RAGRepositoryConnectionTestResponse, DownloadRAGDatabaseFromRepositoryRequest,
+        IngestRAGFilesRequest, RAGDatabaseFile, RAGDatabaseStatus, RAGSearchResult,
     },
-    queries::{rag_providers, rag_repositories},
+    queries::{rag_chunks, rag_providers, rag_repositories},
 };
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +25,15 @@ pub struct PaginationQuery {
     per_page: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RAGSearchRequest {
+    pub query: String,
+    pub top_k: Option<usize>,
+    /// Metadata fields results must match (vector store databases with metadata filtering)
+    pub filter: Option<serde_json::Map<String, serde_json::Value>>,
+    pub similarity_threshold: Option<f32>,
+}
+
 // RAG Provider endpoints
 pub async fn list_rag_providers(
     Extension(_user): Extension<AuthenticatedUser>,
@@ -76,6 +87,32 @@ pub async fn clone_rag_provider(
     Ok(Json(provider))
 }
 
+pub async fn list_rag_provider_collections(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path(provider_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<String>>> {
+    let provider = rag_providers::get_rag_provider_by_id(provider_id)
+        .await?
+        .ok_or(AppError::not_found("RAG provider"))?;
+
+    if !crate::rag::providers::is_vector_store_provider(&provider.provider_type) {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidInvalidInput,
+            "Only vector store RAG providers have collections"
+        ));
+    }
+
+    let vector_store = crate::rag::providers::create_rag_provider(&provider)
+        .map_err(|e| AppError::internal_error(format!("Failed to create vector store client: {}", e)))?;
+    let collections = vector_store
+        .list_collections()
+        .await
+        .map_err(|e| AppError::new(crate::api::errors::ErrorCode::SystemExternalServiceError,
+            format!("Failed to list collections: {}", e)
+        ))?;
+
+    Ok(Json(collections))
+}
+
 // RAG Database endpoints
 pub async fn list_rag_provider_databases(
     Extension(_user): Extension<AuthenticatedUser>,
@@ -119,6 +156,7 @@ pub async fn delete_rag_database(
     Path(database_id): Path<Uuid>,
 ) -> ApiResult<StatusCode> {
     rag_providers::delete_rag_database(database_id).await?;
+    crate::rag::index::unload_index(database_id).await;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -126,7 +164,60 @@ pub async fn start_rag_database(
     Extension(_user): Extension<AuthenticatedUser>,
     Path(database_id): Path<Uuid>,
 ) -> ApiResult<StatusCode> {
-    // First check if the database belongs to a local provider
+    get_local_rag_database(database_id).await?;
+
+    crate::rag::manager::start_rag_database(database_id)
+        .await
+        .map_err(|e| AppError::internal_error(format!("Failed to start RAG database: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn stop_rag_database(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path(database_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    get_local_rag_database(database_id).await?;
+
+    crate::rag::manager::stop_rag_database(database_id)
+        .await
+        .map_err(|e| AppError::internal_error(format!("Failed to stop RAG database: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_rag_database_status(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path(database_id): Path<Uuid>,
+) -> ApiResult<Json<RAGDatabaseStatus>> {
+    let database = get_local_rag_database(database_id).await?;
+
+    let stats = crate::rag::index::index_stats(database_id).await;
+    Ok(Json(RAGDatabaseStatus {
+        database_id,
+        is_active: database.is_active,
+        index_loaded: stats.is_some(),
+        vector_count: stats.map(|(count, _)| count).unwrap_or(0),
+        dimension: stats.and_then(|(_, dimension)| dimension),
+    }))
+}
+
+pub async fn enable_rag_database(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path(database_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    rag_providers::set_rag_database_enabled(database_id, true).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn disable_rag_database(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path(database_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    rag_providers::set_rag_database_enabled(database_id, false).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+// RAG Database index endpoints
+/// Load a RAG database and ensure it belongs to a local provider (the only kind indexed in-process)
+async fn get_local_rag_database(database_id: Uuid) -> ApiResult<RAGDatabase> {
     let database = rag_providers::get_rag_database_by_id(database_id)
         .await?
         .ok_or(AppError::not_found("RAG database"))?;
@@ -137,19 +228,15 @@ pub async fn start_rag_database(
 
     if provider.provider_type != "local" {
         return Err(AppError::new(crate::api::errors::ErrorCode::ValidInvalidInput,
-            "Only local RAG providers support start/stop operations"
+            "Only local RAG providers support this operation"
         ));
     }
 
-    rag_providers::set_rag_database_active(database_id, true).await?;
-    Ok(StatusCode::NO_CONTENT)
+    Ok(database)
 }
 
-pub async fn stop_rag_database(
-    Extension(_user): Extension<AuthenticatedUser>,
-    Path(database_id): Path<Uuid>,
-) -> ApiResult<StatusCode> {
-    // First check if the database belongs to a local provider
+/// Load a RAG database with its provider, ensuring the provider is local or a vector store
+async fn get_indexed_rag_database(database_id: Uuid) -> ApiResult<(RAGDatabase, RAGProvider)> {
     let database = rag_providers::get_rag_database_by_id(database_id)
         .await?
         .ok_or(AppError::not_found("RAG database"))?;
@@ -158,28 +245,134 @@ pub async fn stop_rag_database(
         .await?
         .ok_or(AppError::not_found("RAG provider"))?;
 
-    if provider.provider_type != "local" {
+    if provider.provider_type != "local" && !crate::rag::providers::is_vector_store_provider(&provider.provider_type) {
         return Err(AppError::new(crate::api::errors::ErrorCode::ValidInvalidInput,
-            "Only local RAG providers support start/stop operations"
+            format!("RAG provider type {} does not support indexing operations", provider.provider_type)
         ));
     }
 
-    rag_providers::set_rag_database_active(database_id, false).await?;
-    Ok(StatusCode::NO_CONTENT)
+    Ok((database, provider))
 }
 
-pub async fn enable_rag_database(
+pub async fn list_rag_database_files(
     Extension(_user): Extension<AuthenticatedUser>,
     Path(database_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<RAGDatabaseFile>>> {
+    let files = rag_chunks::list_rag_database_files(database_id).await?;
+    Ok(Json(files))
+}
+
+/// Queue files for ingestion. Only files owned by the caller can be ingested, since their text
+/// can be read back through search and export.
+pub async fn ingest_rag_database_files(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(database_id): Path<Uuid>,
+    Json(request): Json<IngestRAGFilesRequest>,
+) -> ApiResult<Json<Vec<RAGDatabaseFile>>> {
+    let (database, provider) = get_indexed_rag_database(database_id).await?;
+
+    if database.embedding_model.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidMissingRequiredField,
+            "RAG database has no embedding model configured"
+        ));
+    }
+
+    let mut file_ids = request.file_ids.unwrap_or_default();
+    file_ids.sort();
+    file_ids.dedup();
+    if rag_chunks::get_user_file_ids(user.user_id, &file_ids).await?.len() != file_ids.len() {
+        return Err(AppError::not_found("File"));
+    }
+
+    if let Some(project_id) = request.project_id {
+        file_ids.extend(rag_chunks::get_project_file_ids(user.user_id, project_id).await?);
+        file_ids.sort();
+        file_ids.dedup();
+    }
+
+    if file_ids.is_empty() {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidMissingRequiredField,
+            "No files to ingest"
+        ));
+    }
+
+    let queued = crate::rag::queue_file_ingestion(provider, database, file_ids)
+        .await
+        .map_err(|e| AppError::internal_error(format!("Failed to queue ingestion: {}", e)))?;
+
+    Ok(Json(queued))
+}
+
+pub async fn delete_rag_database_file(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path((database_id, file_id)): Path<(Uuid, Uuid)>,
 ) -> ApiResult<StatusCode> {
-    rag_providers::set_rag_database_enabled(database_id, true).await?;
+    let (database, provider) = get_indexed_rag_database(database_id).await?;
+    let chunk_ids = rag_chunks::list_rag_file_chunk_ids(database_id, file_id).await?;
+
+    if !rag_chunks::delete_rag_database_file(database_id, file_id).await? {
+        return Err(AppError::not_found("RAG database file"));
+    }
+    crate::rag::index::remove_file_vectors(database_id, file_id).await;
+
+    if crate::rag::providers::is_vector_store_provider(&provider.provider_type) {
+        let ids: Vec<String> = chunk_ids.iter().map(Uuid::to_string).collect();
+        let result = match crate::rag::providers::create_rag_provider(&provider) {
+            Ok(vector_store) => vector_store.delete(&crate::rag::providers::collection_name(&database), &ids).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to remove file {} from vector store of RAG database {}: {}", file_id, database_id, e);
+        }
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn disable_rag_database(
+pub async fn search_rag_database(
     Extension(_user): Extension<AuthenticatedUser>,
     Path(database_id): Path<Uuid>,
-) -> ApiResult<StatusCode> {
-    rag_providers::set_rag_database_enabled(database_id, false).await?;
-    Ok(StatusCode::NO_CONTENT)
-}
\ No newline at end of file
+    Json(request): Json<RAGSearchRequest>,
+) -> ApiResult<Json<Vec<RAGSearchResult>>> {
+    let (database, provider) = get_indexed_rag_database(database_id).await?;
+
+    let results = crate::rag::search_rag_database(
+        &provider,
+        &database,
+        &request.query,
+        request.top_k.unwrap_or(5),
+        request.filter,
+        request.similarity_threshold,
+    )
+        .await
+        .map_err(|e| AppError::new(crate::api::errors::ErrorCode::SystemExternalServiceError,
+            format!("RAG search failed: {}", e)
+        ))?;
+
+    Ok(Json(results))
+}
+
+pub async fn export_rag_database(
+    Extension(_user): Extension<AuthenticatedUser>,
+    Path(database_id): Path<Uuid>,
+) -> ApiResult<Response> {
+    let database = get_local_rag_database(database_id).await?;
+
+    let package = crate::rag::export_rag_database_package(&database)
+        .await
+        .map_err(|e| AppError::internal_error(format!("Failed to export RAG database: {}", e)))?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/gzip".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{}.{}\"",
+                database.alias,
+                crate::rag::package::RAG_PACKAGE_EXTENSION
+            ),
+        ),
+    ];
+
+    Ok((headers, package).into_response())
+}
@@ -12,7 +12,7 @@ use crate::database::{
     models::{
         CreateRAGRepositoryRequest, RAGRepository, RAGRepositoryListResponse, UpdateRAGRepositoryRequest,
         RAGRepositoryConnectionTestResponse, DownloadRAGDatabaseFromRepositoryRequest,
-        RAGDatabase,
+        RAGDatabase, DownloadInstance,
     },
     queries::{rag_repositories, rag_providers},
 };
@@ -105,35 +105,46 @@ pub async fn list_rag_repository_databases(
 pub async fn download_rag_database_from_repository(
     Extension(_user): Extension<AuthenticatedUser>,
     Json(request): Json<DownloadRAGDatabaseFromRepositoryRequest>,
-) -> ApiResult<Json<RAGDatabase>> {
+) -> ApiResult<Json<DownloadInstance>> {
     // Validate that the target provider exists
-    let _provider = rag_providers::get_rag_provider_by_id(request.target_provider_id)
+    let provider = rag_providers::get_rag_provider_by_id(request.target_provider_id)
         .await?
         .ok_or(AppError::not_found("Target RAG provider"))?;
 
+    // Packages are imported into the local index
+    if provider.provider_type != "local" {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidInvalidInput,
+            "RAG database packages can only be imported into local RAG providers"
+        ));
+    }
+
     // Validate that the repository exists
-    let _repository = rag_repositories::get_rag_repository_by_id(request.repository_id)
+    let repository = rag_repositories::get_rag_repository_by_id(request.repository_id)
         .await?
         .ok_or(AppError::not_found("RAG repository"))?;
 
-    // TODO: Implement actual download logic
-    // For now, create a placeholder database
-    let database_name = request.database_name.unwrap_or_else(|| "Downloaded Database".to_string());
-    let database_alias = request.database_alias.unwrap_or_else(|| "downloaded-db".to_string());
-
-    let create_request = crate::database::models::CreateRAGDatabaseRequest {
-        name: database_name,
-        alias: database_alias,
-        description: Some("Downloaded from repository".to_string()),
-        enabled: Some(true),
-        collection_name: None,
-        embedding_model: None,
-        chunk_size: Some(1000),
-        chunk_overlap: Some(200),
-        capabilities: None,
-        settings: None,
-    };
-
-    let database = rag_providers::create_rag_database(request.target_provider_id, create_request).await?;
-    Ok(Json(database))
-}
\ No newline at end of file
+    if !repository.enabled {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidInvalidInput,
+            "RAG repository is disabled"
+        ));
+    }
+
+    if request.database_id.trim().is_empty() {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidMissingRequiredField,
+            "database_id is required"
+        ));
+    }
+
+    if !crate::rag::download::is_valid_database_id(&request.database_id) {
+        return Err(AppError::new(crate::api::errors::ErrorCode::ValidInvalidInput,
+            "database_id must be a package path inside the repository"
+        ));
+    }
+
+    // Download and import run in the background; progress is tracked by the download instance
+    let download_instance = crate::rag::start_rag_database_download(repository, request)
+        .await
+        .map_err(|e| AppError::internal_error(format!("Failed to start RAG database download: {}", e)))?;
+
+    Ok(Json(download_instance))
+}
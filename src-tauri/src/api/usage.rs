@@ -0,0 +1,71 @@
+use axum::{extract::Query, Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::ai::usage::quota_period_start;
+use crate::api::errors::{ApiResult, AppError, ErrorCode};
+use crate::api::middleware::AuthenticatedUser;
+use crate::database::{
+    models::{GroupQuotaStatus, UsageAggregateResponse, UsageGroupBy, UserUsageResponse},
+    queries::{usage, user_groups},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UsageAggregateQuery {
+    pub group_by: UsageGroupBy,
+    /// Defaults to the start of the current month
+    pub from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Token usage of the current user this month and the quotas of their groups
+pub async fn get_my_usage(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+) -> ApiResult<Json<UserUsageResponse>> {
+    let period_start = quota_period_start();
+    let usage_totals = usage::get_user_usage_totals(auth_user.user_id, period_start).await?;
+
+    let mut quotas = Vec::new();
+    for group in user_groups::get_user_groups(auth_user.user_id).await? {
+        let Some(group_quotas) = group.quotas.filter(|quotas| !quotas.is_empty()) else {
+            continue;
+        };
+        quotas.push(GroupQuotaStatus {
+            group_id: group.id,
+            group_name: group.name,
+            quotas: group_quotas,
+            group_usage: usage::get_group_usage_totals(group.id, period_start).await?,
+        });
+    }
+
+    Ok(Json(UserUsageResponse {
+        period_start,
+        usage: usage_totals,
+        quotas,
+    }))
+}
+
+/// Token usage of all users, totalled per user, group, provider or model
+pub async fn get_usage_aggregates(
+    Extension(_auth_user): Extension<AuthenticatedUser>,
+    Query(params): Query<UsageAggregateQuery>,
+) -> ApiResult<Json<UsageAggregateResponse>> {
+    let from = params.from.unwrap_or_else(quota_period_start);
+    let to = params.to.unwrap_or_else(Utc::now);
+    if from >= to {
+        return Err(AppError::new(
+            ErrorCode::ValidInvalidInput,
+            "'from' must be before 'to'",
+        ));
+    }
+
+    let items = usage::aggregate_usage(params.group_by, from, to).await?;
+
+    Ok(Json(UsageAggregateResponse {
+        group_by: params.group_by,
+        from,
+        to,
+        items,
+    }))
+}
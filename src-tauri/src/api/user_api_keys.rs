@@ -0,0 +1,138 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use uuid::Uuid;
+
+use crate::api::errors::{ApiResult, AppError, ErrorCode};
+use crate::api::middleware::AuthenticatedUser;
+use crate::api::permissions::{check_permission, permissions};
+use crate::auth::AuthService;
+use crate::database::{
+    models::{CreateUserApiKeyRequest, CreateUserApiKeyResponse, UserApiKey},
+    queries::api_keys,
+};
+
+/// List the current user's API keys
+pub async fn list_api_keys(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+) -> ApiResult<Json<Vec<UserApiKey>>> {
+    let api_keys = api_keys::list_user_api_keys(auth_user.user_id).await?;
+    Ok(Json(api_keys))
+}
+
+/// Create an API key for the current user. The key is only returned by this call.
+pub async fn create_api_key(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Json(request): Json<CreateUserApiKeyRequest>,
+) -> ApiResult<Json<CreateUserApiKeyResponse>> {
+    ensure_not_api_key(&auth_user)?;
+
+    if request.name.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::ValidMissingRequiredField,
+            "API key name is required",
+        ));
+    }
+
+    if request
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= chrono::Utc::now())
+    {
+        return Err(AppError::new(
+            ErrorCode::ValidInvalidInput,
+            "Expiry must be in the future",
+        ));
+    }
+
+    if let Some(scopes) = &request.scopes {
+        if let Some(unknown) = scopes
+            .iter()
+            .find(|scope| !permissions::ALL_PERMISSIONS.contains(&scope.as_str()))
+        {
+            return Err(AppError::new(
+                ErrorCode::ValidInvalidInput,
+                format!("Unknown permission scope: {}", unknown),
+            ));
+        }
+
+        // A key cannot grant more than its owner has
+        if let Some(missing) = scopes
+            .iter()
+            .find(|scope| !check_permission(&auth_user.user, scope))
+        {
+            return Err(AppError::new(
+                ErrorCode::AuthzInsufficientPermissions,
+                format!("You do not have the permission {}", missing),
+            ));
+        }
+    }
+
+    let (key, key_hash) = AuthService::default().generate_api_key();
+    // Enough of the key to recognise it in the list without revealing it
+    let key_prefix: String = key.chars().take(11).collect();
+
+    let api_key =
+        api_keys::create_user_api_key(auth_user.user_id, &request, &key_prefix, &key_hash)
+            .await?;
+
+    Ok(Json(CreateUserApiKeyResponse { api_key, key }))
+}
+
+/// Keys can only be created from a signed-in session. Otherwise a leaked scoped key could
+/// mint an unscoped key carrying all of its owner's permissions.
+fn ensure_not_api_key(auth_user: &AuthenticatedUser) -> ApiResult<()> {
+    if auth_user.api_key_id.is_some() {
+        return Err(AppError::new(
+            ErrorCode::AuthzInsufficientPermissions,
+            "API keys cannot be used to create API keys",
+        ));
+    }
+    Ok(())
+}
+
+/// Revoke one of the current user's API keys
+pub async fn revoke_api_key(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Path(key_id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    if !api_keys::delete_user_api_key(auth_user.user_id, key_id).await? {
+        return Err(AppError::not_found("API key"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{User, UserServices};
+
+    fn authenticated_user(api_key_id: Option<Uuid>) -> AuthenticatedUser {
+        let user = User {
+            id: Uuid::new_v4(),
+            username: "testuser".to_string(),
+            profile: None,
+            is_active: true,
+            is_protected: false,
+            last_login_at: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            groups: vec![],
+            emails: vec![],
+            services: UserServices::default(),
+        };
+        AuthenticatedUser {
+            user_id: user.id,
+            user,
+            api_key_id,
+        }
+    }
+
+    #[test]
+    fn test_session_can_create_api_keys() {
+        assert!(ensure_not_api_key(&authenticated_user(None)).is_ok());
+    }
+
+    #[test]
+    fn test_api_key_cannot_create_api_keys() {
+        assert!(ensure_not_api_key(&authenticated_user(Some(Uuid::new_v4()))).is_err());
+    }
+}
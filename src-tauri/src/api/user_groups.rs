@@ -26,8 +26,13 @@ pub async fn create_user_group(
     Extension(_auth_user): Extension<AuthenticatedUser>,
     Json(request): Json<CreateUserGroupRequest>,
 ) -> Result<Json<crate::database::models::UserGroup>, StatusCode> {
-    match user_groups::create_user_group(request.name, request.description, request.permissions)
-        .await
+    match user_groups::create_user_group(
+        request.name,
+        request.description,
+        request.permissions,
+        request.quotas,
+    )
+    .await
     {
         Ok(group) => {
             // If provider_ids are provided, assign them to the group
@@ -146,6 +151,7 @@ pub async fn update_user_group(
         request.description,
         request.permissions,
         request.is_active,
+        request.quotas,
     )
     .await
     {
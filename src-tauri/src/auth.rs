@@ -2,9 +2,11 @@ use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::database::models::*;
+use crate::database::queries::api_keys;
 use crate::database::queries::repositories;
 use crate::database::queries::users;
 use crate::utils::password;
@@ -81,6 +83,44 @@ impl AuthService {
         hex::encode(token)
     }
 
+    /// Get the active user owning a personal API key, limited to the key's scopes, with the key id
+    pub async fn get_user_by_api_key(&self, key: &str) -> Result<Option<(User, Uuid)>, String> {
+        let Some(api_key) = api_keys::get_api_key_by_hash(&hash_api_key(key))
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+
+        let Some(user) = users::get_user_by_id(api_key.user_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|user| user.is_active)
+        else {
+            return Ok(None);
+        };
+
+        // Last-used tracking must not slow down or fail the request
+        tokio::spawn(async move {
+            if let Err(e) = api_keys::touch_api_key_last_used(api_key.id).await {
+                eprintln!("Failed to update API key last used time: {}", e);
+            }
+        });
+
+        let user = match &api_key.scopes {
+            Some(scopes) => crate::api::permissions::restrict_permissions(user, scopes),
+            None => user,
+        };
+        Ok(Some((user, api_key.id)))
+    }
+
+    /// Generate a new personal API key, returning the key and the hash to store
+    pub fn generate_api_key(&self) -> (String, String) {
+        let key = format!("{}{}", API_KEY_PREFIX, self.generate_login_token());
+        let key_hash = hash_api_key(&key);
+        (key, key_hash)
+    }
+
     /// Authenticate user with username/email and password
     pub async fn authenticate_user(
         &self,
@@ -250,3 +290,8 @@ impl AuthService {
         })
     }
 }
+
+/// Keys are random, so a plain SHA-256 is enough to store them without a salt
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
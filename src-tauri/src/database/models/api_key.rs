@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row};
+use uuid::Uuid;
+
+/// Prefix of every personal API key, used to tell keys apart from session tokens
+pub const API_KEY_PREFIX: &str = "zk-";
+
+/// A personal API key. The key itself is only shown once at creation; its hash is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    /// Permissions the key is limited to; None grants all permissions of the owner
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for UserApiKey {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        let scopes_json: Option<serde_json::Value> = row.try_get("scopes")?;
+        let scopes = match scopes_json {
+            Some(json) if !json.is_null() => Some(serde_json::from_value(json).map_err(|e| {
+                sqlx::Error::ColumnDecode {
+                    index: "scopes".into(),
+                    source: Box::new(e),
+                }
+            })?),
+            _ => None,
+        };
+
+        Ok(UserApiKey {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            name: row.try_get("name")?,
+            key_prefix: row.try_get("key_prefix")?,
+            key_hash: row.try_get("key_hash")?,
+            scopes,
+            expires_at: row.try_get("expires_at")?,
+            last_used_at: row.try_get("last_used_at")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateUserApiKeyRequest {
+    pub name: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub scopes: Option<Vec<String>>,
+}
+
+/// Returned once when a key is created; `key` cannot be retrieved again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateUserApiKeyResponse {
+    pub api_key: UserApiKey,
+    pub key: String,
+}
@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row};
 use uuid::Uuid;
 
-use super::model::ModelParameters;
+use super::chat::ResponseFormat;
+use crate::ai::context_window::ContextWindowSettings;
+use super::model::{parse_fallback_chain, FallbackTarget, ModelParameters};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Assistant {
@@ -12,6 +14,13 @@ pub struct Assistant {
     pub description: Option<String>,
     pub instructions: Option<String>,
     pub parameters: Option<ModelParameters>,
+    /// Models to fail over to, in order; takes precedence over the chosen model's own chain
+    #[serde(default)]
+    pub fallback_chain: Vec<FallbackTarget>,
+    /// JSON Schema every answer must match, for data-extraction assistants
+    pub response_format: Option<ResponseFormat>,
+    /// How conversations longer than the model's context are shortened
+    pub context_window: Option<ContextWindowSettings>,
     pub created_by: Option<Uuid>,
     pub is_template: bool,
     pub is_default: bool,
@@ -44,6 +53,13 @@ impl FromRow<'_, sqlx::postgres::PgRow> for Assistant {
             description: row.try_get("description")?,
             instructions: row.try_get("instructions")?,
             parameters,
+            fallback_chain: parse_fallback_chain(row)?,
+            response_format: row
+                .try_get::<Option<serde_json::Value>, _>("response_format")?
+                .and_then(|v| serde_json::from_value(v).ok()),
+            context_window: row
+                .try_get::<Option<serde_json::Value>, _>("context_window")?
+                .and_then(|v| serde_json::from_value(v).ok()),
             created_by: row.try_get("created_by")?,
             is_template: row.try_get("is_template")?,
             is_default: row.try_get("is_default")?,
@@ -60,6 +76,10 @@ pub struct CreateAssistantRequest {
     pub description: Option<String>,
     pub instructions: Option<String>,
     pub parameters: Option<ModelParameters>,
+    pub fallback_chain: Option<Vec<FallbackTarget>>,
+    pub response_format: Option<ResponseFormat>,
+    #[serde(default)]
+    pub context_window: Option<ContextWindowSettings>,
     pub is_template: Option<bool>,
     pub is_default: Option<bool>,
 }
@@ -70,6 +90,10 @@ pub struct UpdateAssistantRequest {
     pub description: Option<String>,
     pub instructions: Option<String>,
     pub parameters: Option<ModelParameters>,
+    pub fallback_chain: Option<Vec<FallbackTarget>>,
+    pub response_format: Option<ResponseFormat>,
+    #[serde(default)]
+    pub context_window: Option<ContextWindowSettings>,
     pub is_template: Option<bool>,
     pub is_default: Option<bool>,
     pub is_active: Option<bool>,
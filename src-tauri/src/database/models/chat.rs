@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row};
 use uuid::Uuid;
-use crate::database::models::File;
+use crate::database::models::{File, ModelParameters};
 
 // Main unified structures
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,12 +40,21 @@ pub struct Message {
     pub conversation_id: Uuid,
     pub role: String,
     pub content: String,
+    pub reasoning: Option<String>,        // Thinking the model produced before the answer
     pub originated_from_id: Option<Uuid>, // ID of the original message this was edited from
     pub edit_count: Option<i32>,          // Number of times this message lineage has been edited
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub metadata: Option<Vec<MessageMetadata>>,
-    pub files: Vec<File>
+    pub files: Vec<File>,
+    pub message_type: String,               // "text", "tool_call" or "tool_result"
+    pub tool_calls: Option<Vec<ToolCall>>,  // Tool calls requested by the assistant (tool_call messages)
+    pub tool_call_id: Option<String>,       // Tool call this message answers (tool_result messages)
+    pub finish_reason: Option<String>,      // Why generation stopped ("cancelled" when stopped by the user)
+    pub provider_id: Option<Uuid>,          // Provider that generated the message, after any failover
+    pub model_id: Option<Uuid>,             // Model that generated the message, after any failover
+    pub parameters: Option<ModelParameters>, // Parameters the provider was sent for a generated message
+    pub system_prompt_override: Option<String>, // Replacement for the assistant's instructions, if one was used
 }
 
 impl FromRow<'_, sqlx::postgres::PgRow> for Message {
@@ -55,12 +64,25 @@ impl FromRow<'_, sqlx::postgres::PgRow> for Message {
             conversation_id: row.try_get("conversation_id")?,
             role: row.try_get("role")?,
             content: row.try_get("content")?,
+            reasoning: row.try_get("reasoning")?,
             originated_from_id: row.try_get("originated_from_id")?,
             edit_count: row.try_get("edit_count")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
             metadata: None, // This is loaded separately via joins when needed
             files: vec![], // This is loaded separately via joins when needed
+            message_type: row.try_get("message_type")?,
+            tool_calls: row
+                .try_get::<Option<serde_json::Value>, _>("tool_calls")?
+                .and_then(|v| serde_json::from_value(v).ok()),
+            tool_call_id: row.try_get("tool_call_id")?,
+            finish_reason: row.try_get("finish_reason")?,
+            provider_id: row.try_get("provider_id")?,
+            model_id: row.try_get("model_id")?,
+            parameters: row
+                .try_get::<Option<serde_json::Value>, _>("parameters")?
+                .and_then(|v| serde_json::from_value(v).ok()),
+            system_prompt_override: row.try_get("system_prompt_override")?,
         })
     }
 }
@@ -164,11 +186,53 @@ pub struct UpdateConversationRequest {
 pub struct SaveMessageRequest {
     pub conversation_id: Uuid,
     pub content: String,
+    /// Reasoning streamed alongside the content; set for generated messages only
+    #[serde(default)]
+    pub reasoning: Option<String>,
     pub role: String,
     pub model_id: Uuid,
     pub file_ids: Option<Vec<Uuid>>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+    /// Provider that generated the message; set for generated messages only
+    #[serde(default)]
+    pub provider_id: Option<Uuid>,
+    /// Effective parameters of a generated message
+    #[serde(default)]
+    pub parameters: Option<ModelParameters>,
+    /// System prompt that replaced the assistant's instructions for a generated message
+    #[serde(default)]
+    pub system_prompt_override: Option<String>,
+    /// Message this one is an alternative of, so both are listed as branches of it.
+    /// A new message starts its own lineage when None
+    #[serde(default)]
+    pub originated_from_id: Option<Uuid>,
+}
+
+impl SaveMessageRequest {
+    /// Message kind derived from the tool fields: tool results take precedence over tool calls
+    pub fn message_type(&self) -> &'static str {
+        if self.tool_call_id.is_some() {
+            MESSAGE_TYPE_TOOL_RESULT
+        } else if self.tool_calls.as_ref().map(|calls| !calls.is_empty()).unwrap_or(false) {
+            MESSAGE_TYPE_TOOL_CALL
+        } else {
+            MESSAGE_TYPE_TEXT
+        }
+    }
 }
 
+pub const MESSAGE_TYPE_TEXT: &str = "text";
+pub const MESSAGE_TYPE_TOOL_CALL: &str = "tool_call";
+pub const MESSAGE_TYPE_TOOL_RESULT: &str = "tool_result";
+
+/// finish_reason recorded when the user stops generation
+pub const FINISH_REASON_CANCELLED: &str = "cancelled";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditMessageRequest {
     pub content: String,
@@ -226,6 +290,55 @@ impl FromRow<'_, sqlx::postgres::PgRow> for ConversationSummary {
     }
 }
 
+/// Filters of a conversation search; dates, model and role apply to the matching message
+#[derive(Debug, Clone, Default)]
+pub struct ConversationSearchFilters {
+    /// Conversations of this project, or conversations outside projects when None
+    pub project_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub assistant_id: Option<Uuid>,
+    pub model_id: Option<Uuid>,
+    /// Only messages of this role; title matches are left out when set
+    pub role: Option<String>,
+}
+
+/// A conversation matching a search, with its best match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSearchResult {
+    #[serde(flatten)]
+    pub conversation: ConversationSummary,
+    pub rank: f32,
+    /// Matching text with the matched terms wrapped in `<mark>` tags
+    pub snippet: String,
+    /// Matching message, None when the title matched best
+    pub message_id: Option<Uuid>,
+    /// Branch holding the matching message, the active branch when it is on it
+    pub branch_id: Option<Uuid>,
+    pub role: Option<String>,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for ConversationSearchResult {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(ConversationSearchResult {
+            conversation: ConversationSummary::from_row(row)?,
+            rank: row.try_get("rank")?,
+            snippet: row.try_get("snippet")?,
+            message_id: row.try_get("message_id")?,
+            branch_id: row.try_get("branch_id")?,
+            role: row.try_get("matched_role")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationSearchResponse {
+    pub conversations: Vec<ConversationSearchResult>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatResponse {
     pub message: Message,
@@ -316,10 +429,99 @@ impl MessageContent {
     }
 }
 
+impl MessageContent {
+    /// Flatten the content to plain text, describing file references by name
+    pub fn to_plain_text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Multimodal(parts) => parts
+                .iter()
+                .map(|part| match part {
+                    ContentPart::Text(text) => text.clone(),
+                    ContentPart::FileReference(file_ref) => format!("File: {}", file_ref.filename),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// A function the model is allowed to call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    /// JSON Schema describing the function arguments
+    pub parameters: serde_json::Value,
+}
+
+/// JSON Schema the final assistant message has to conform to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResponseFormat {
+    /// Name passed to the provider: letters, digits, underscores and dashes
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub schema: serde_json::Value,
+    /// Ask providers with a strict mode to enforce the schema exactly
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// How the model should choose between answering and calling tools
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    Auto,
+    None,
+    Required,
+    Function { name: String },
+}
+
+/// A tool invocation requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// Parsed JSON arguments (falls back to a JSON string if the model produced invalid JSON)
+    pub arguments: serde_json::Value,
+}
+
+impl ToolCall {
+    /// Build a tool call from the raw argument string returned by a provider
+    pub fn from_raw_arguments(id: String, name: String, raw_arguments: &str) -> Self {
+        let arguments = if raw_arguments.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(raw_arguments)
+                .unwrap_or_else(|_| serde_json::Value::String(raw_arguments.to_string()))
+        };
+        Self {
+            id,
+            name,
+            arguments,
+        }
+    }
+
+    /// Arguments encoded as a JSON string (the OpenAI wire format)
+    pub fn arguments_string(&self) -> String {
+        match &self.arguments {
+            serde_json::Value::String(raw) => raw.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
     pub content: MessageContent,
+    /// Tool calls made by an assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Tool call answered by a "tool" role message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
 }
 
 impl ChatMessage {
@@ -327,6 +529,28 @@ impl ChatMessage {
         Self {
             role: role.to_string(),
             content: MessageContent::Text(content.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Assistant message carrying tool calls (content may be empty)
+    pub fn tool_calls(content: &str, tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: MessageContent::Text(content.to_string()),
+            tool_calls: Some(tool_calls),
+            tool_call_id: None,
+        }
+    }
+
+    /// Tool result message answering a previous tool call
+    pub fn tool_result(tool_call_id: &str, content: &str) -> Self {
+        Self {
+            role: "tool".to_string(),
+            content: MessageContent::Text(content.to_string()),
+            tool_calls: None,
+            tool_call_id: Some(tool_call_id.to_string()),
         }
     }
 }
@@ -339,24 +563,85 @@ pub struct ChatRequest {
     pub provider_id: Uuid,
     pub stream: bool,
     pub parameters: Option<crate::database::models::model::ModelParameters>,
+    pub tools: Option<Vec<ToolDefinition>>,
+    pub tool_choice: Option<ToolChoice>,
+    /// Structured output the provider should constrain the answer to
+    pub response_format: Option<ResponseFormat>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIProviderChatResponse {
     pub content: String,
+    /// Reasoning the model produced before its answer, kept out of `content`
+    pub reasoning: Option<String>,
     pub finish_reason: Option<String>,
     pub usage: Option<Usage>,
+    pub tool_calls: Option<Vec<ToolCall>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
     pub prompt_tokens: Option<u32>,
     pub completion_tokens: Option<u32>,
     pub total_tokens: Option<u32>,
 }
 
+impl Usage {
+    /// Take the counts reported by a later event; providers may report prompt and
+    /// completion tokens in separate stream events
+    pub fn merge(&mut self, other: Usage) {
+        if other.prompt_tokens.is_some() {
+            self.prompt_tokens = other.prompt_tokens;
+        }
+        if other.completion_tokens.is_some() {
+            self.completion_tokens = other.completion_tokens;
+        }
+        if other.total_tokens.is_some() {
+            self.total_tokens = other.total_tokens;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingChunk {
     pub content: Option<String>,
+    /// Reasoning ("thinking") delta, streamed separately from the answer
+    pub reasoning: Option<String>,
     pub finish_reason: Option<String>,
+    /// Complete tool calls, emitted once the provider has finished streaming their arguments
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Token usage, when the provider reports it in the stream
+    pub usage: Option<Usage>,
+}
+
+impl StreamingChunk {
+    /// A chunk carrying nothing (used when a network read contained no complete event)
+    pub fn empty() -> Self {
+        Self {
+            content: None,
+            reasoning: None,
+            finish_reason: None,
+            tool_calls: None,
+            usage: None,
+        }
+    }
+
+    /// Fold a later chunk into this one so several events from one network read are not dropped
+    pub fn merge(&mut self, other: StreamingChunk) {
+        if let Some(content) = other.content {
+            self.content.get_or_insert_with(String::new).push_str(&content);
+        }
+        if let Some(reasoning) = other.reasoning {
+            self.reasoning.get_or_insert_with(String::new).push_str(&reasoning);
+        }
+        if other.finish_reason.is_some() {
+            self.finish_reason = other.finish_reason;
+        }
+        if let Some(tool_calls) = other.tool_calls {
+            self.tool_calls.get_or_insert_with(Vec::new).extend(tool_calls);
+        }
+        if let Some(usage) = other.usage {
+            self.usage.get_or_insert_with(Usage::default).merge(usage);
+        }
+    }
 }
\ No newline at end of file
@@ -51,6 +51,11 @@ pub struct DownloadRequestData {
     pub settings: Option<ModelSettings>,
 }
 
+/// Download of a model from a model repository
+pub const DOWNLOAD_TYPE_MODEL: &str = "model";
+/// Download of a RAG database package from a RAG repository
+pub const DOWNLOAD_TYPE_RAG_DATABASE: &str = "rag_database";
+
 /// Download instance status enum
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -98,6 +103,8 @@ pub struct DownloadInstance {
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub model_id: Option<Uuid>, // Filled when download completes
+    pub download_type: String, // model or rag_database
+    pub rag_database_id: Option<Uuid>, // Filled when a RAG database download completes
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -149,6 +156,8 @@ impl FromRow<'_, sqlx::postgres::PgRow> for DownloadInstance {
             started_at: row.try_get("started_at")?,
             completed_at: row.try_get("completed_at")?,
             model_id: row.try_get("model_id")?,
+            download_type: row.try_get("download_type")?,
+            rag_database_id: row.try_get("rag_database_id")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
@@ -162,6 +171,8 @@ pub struct CreateDownloadInstanceRequest {
     pub provider_id: Uuid,
     pub repository_id: Uuid,
     pub request_data: DownloadRequestData,
+    #[serde(default)]
+    pub download_type: Option<String>, // Defaults to model
 }
 
 /// Request to update download instance progress
@@ -1,3 +1,4 @@
+pub mod api_key;
 pub mod assistant;
 pub mod chat;
 pub mod config;
@@ -7,13 +8,17 @@ pub mod model;
 pub mod project;
 pub mod provider;
 pub mod proxy;
+pub mod rag_chunk;
 pub mod rag_database;
 pub mod rag_provider;
 pub mod rag_repository;
 pub mod repository;
+pub mod semantic_chunk;
+pub mod usage;
 pub mod user;
 
 // Re-export all structures for convenience
+pub use api_key::*;
 pub use assistant::*;
 pub use chat::*;
 pub use config::*;
@@ -23,8 +28,11 @@ pub use model::*;
 pub use project::*;
 pub use provider::*;
 pub use proxy::*;
+pub use rag_chunk::*;
 pub use rag_database::*;
 pub use rag_provider::*;
 pub use rag_repository::*;
 pub use repository::*;
+pub use semantic_chunk::*;
+pub use usage::*;
 pub use user::*;
\ No newline at end of file
@@ -75,6 +75,14 @@ pub struct ModelParameters {
     pub seed: Option<i32>,
     /// Stop sequences to terminate generation
     pub stop: Option<Vec<String>>,
+    /// Context window to allocate, for servers that size it per request (Ollama `num_ctx`)
+    pub num_ctx: Option<u32>,
+
+    // Reasoning control
+    /// Let reasoning models think before answering
+    pub enable_thinking: Option<bool>,
+    /// Maximum tokens to spend on reasoning; setting it turns thinking on
+    pub thinking_budget: Option<u32>,
 }
 
 impl ModelParameters {
@@ -97,6 +105,9 @@ impl ModelParameters {
             frequency_penalty: Some(0.0),
             seed: None,
             stop: None,
+            num_ctx: None,
+            enable_thinking: None,
+            thinking_budget: None,
         }
     }
 
@@ -114,9 +125,38 @@ impl ModelParameters {
             frequency_penalty: Some(0.1),
             seed: None,
             stop: None,
+            num_ctx: None,
+            enable_thinking: None,
+            thinking_budget: None,
         }
     }
 
+    /// Copy of these parameters with every field set in `overrides` replaced
+    pub fn merged_with(&self, overrides: &ModelParameters) -> Self {
+        Self {
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+            temperature: overrides.temperature.or(self.temperature),
+            top_k: overrides.top_k.or(self.top_k),
+            top_p: overrides.top_p.or(self.top_p),
+            min_p: overrides.min_p.or(self.min_p),
+            repeat_last_n: overrides.repeat_last_n.or(self.repeat_last_n),
+            repeat_penalty: overrides.repeat_penalty.or(self.repeat_penalty),
+            presence_penalty: overrides.presence_penalty.or(self.presence_penalty),
+            frequency_penalty: overrides.frequency_penalty.or(self.frequency_penalty),
+            seed: overrides.seed.or(self.seed),
+            stop: overrides.stop.clone().or_else(|| self.stop.clone()),
+            num_ctx: overrides.num_ctx.or(self.num_ctx),
+            enable_thinking: overrides.enable_thinking.or(self.enable_thinking),
+            thinking_budget: overrides.thinking_budget.or(self.thinking_budget),
+        }
+    }
+
+    /// Whether reasoning is requested, either explicitly or through a thinking budget
+    pub fn thinking_enabled(&self) -> bool {
+        self.enable_thinking
+            .unwrap_or_else(|| self.thinking_budget.is_some_and(|budget| budget > 0))
+    }
+
     /// Validate the parameters and return errors if any
     pub fn validate(&self) -> Result<(), String> {
         if let Some(temp) = self.temperature {
@@ -155,6 +195,12 @@ impl ModelParameters {
             }
         }
 
+        if let (Some(budget), Some(max_tokens)) = (self.thinking_budget, self.max_tokens) {
+            if budget >= max_tokens {
+                return Err("thinking_budget must be lower than max_tokens".to_string());
+            }
+        }
+
         if let Some(stop) = &self.stop {
             if stop.len() > 4 {
                 return Err("stop sequences cannot exceed 4 items".to_string());
@@ -343,6 +389,21 @@ impl ModelSettings {
     }
 }
 
+/// A (provider, model) pair tried when the previous entry fails with a retryable error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FallbackTarget {
+    pub provider_id: Uuid,
+    pub model_id: Uuid,
+}
+
+/// Decode a `fallback_chain` column, treating unreadable chains as empty
+pub fn parse_fallback_chain(row: &sqlx::postgres::PgRow) -> Result<Vec<FallbackTarget>, sqlx::Error> {
+    let chain_json: Option<serde_json::Value> = row.try_get("fallback_chain")?;
+    Ok(chain_json
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Model {
     pub id: Uuid,
@@ -355,6 +416,11 @@ pub struct Model {
     pub is_active: bool,
     pub capabilities: Option<ModelCapabilities>,
     pub parameters: Option<ModelParameters>,
+    /// Models to fail over to, in order, when this model's provider is unavailable
+    #[serde(default)]
+    pub fallback_chain: Vec<FallbackTarget>,
+    /// Tokens the model accepts per request, prompt and answer together
+    pub context_length: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     // Additional fields for Candle models (None for other providers)
@@ -424,6 +490,8 @@ impl FromRow<'_, sqlx::postgres::PgRow> for Model {
             is_active: row.try_get("is_active")?,
             capabilities,
             parameters,
+            fallback_chain: parse_fallback_chain(row)?,
+            context_length: row.try_get("context_length")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
             file_size_bytes: row.try_get("file_size_bytes")?,
@@ -448,6 +516,9 @@ pub struct CreateModelRequest {
     pub capabilities: Option<ModelCapabilities>,
     pub parameters: Option<ModelParameters>,
     pub settings: Option<ModelSettings>,
+    pub fallback_chain: Option<Vec<FallbackTarget>>,
+    #[serde(default)]
+    pub context_length: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -460,6 +531,11 @@ pub struct UpdateModelRequest {
     pub capabilities: Option<ModelCapabilities>,
     pub parameters: Option<ModelParameters>,
     pub settings: Option<ModelSettings>,
+    pub fallback_chain: Option<Vec<FallbackTarget>>,
+    /// Context window in tokens; absent keeps the current value and 0 clears it, so the
+    /// length is taken from the model settings again
+    #[serde(default)]
+    pub context_length: Option<i32>,
 }
 
 // Model file tracking for uploaded files
@@ -492,6 +568,19 @@ pub struct ModelUploadResponse {
     pub next_chunk_index: Option<u32>,
 }
 
+/// Outcome of syncing a provider's models with the vendor's model list
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModelSyncResponse {
+    /// Models the vendor lists that were not known yet, added disabled
+    pub added: Vec<String>,
+    /// Deprecated models the vendor lists again
+    pub restored: Vec<String>,
+    /// Models the vendor no longer lists, now marked deprecated
+    pub deprecated: Vec<String>,
+    /// Number of models the vendor reported
+    pub total: usize,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ModelListResponse {
     pub models: Vec<Model>,
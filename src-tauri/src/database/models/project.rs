@@ -3,6 +3,29 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row};
 use uuid::Uuid;
 
+use super::ModelParameters;
+
+/// Project files are included whole, in order, until the budget is spent
+pub const PROJECT_FILE_CONTEXT_ALWAYS: &str = "always";
+/// Only the chunks of project files that best match the user's message are included
+pub const PROJECT_FILE_CONTEXT_RETRIEVE: &str = "retrieve";
+
+/// Tokens of project file text a conversation gets unless the project sets its own budget
+pub const DEFAULT_PROJECT_FILE_CONTEXT_BUDGET: i32 = 8000;
+
+/// Check the file context settings of a create or update request
+pub fn validate_file_context(mode: Option<&str>, budget: Option<i32>) -> Result<(), String> {
+    if let Some(mode) = mode {
+        if mode != PROJECT_FILE_CONTEXT_ALWAYS && mode != PROJECT_FILE_CONTEXT_RETRIEVE {
+            return Err(format!("Unknown file context mode: {}", mode));
+        }
+    }
+    if budget.is_some_and(|budget| budget < 0) {
+        return Err("File context budget must not be negative".to_string());
+    }
+    Ok(())
+}
+
 // Main Project structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -11,6 +34,12 @@ pub struct Project {
     pub name: String,
     pub description: Option<String>,
     pub instruction: Option<String>,
+    /// Inference parameters applied over the assistant's for the project's conversations
+    pub parameters: Option<ModelParameters>,
+    /// How the project's files are added to its conversations
+    pub file_context_mode: String,
+    /// Tokens of file text added to each request; 0 leaves the files out
+    pub file_context_budget: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +52,11 @@ impl FromRow<'_, sqlx::postgres::PgRow> for Project {
             name: row.try_get("name")?,
             description: row.try_get("description")?,
             instruction: row.try_get("instruction")?,
+            parameters: row
+                .try_get::<Option<serde_json::Value>, _>("parameters")?
+                .and_then(|value| serde_json::from_value(value).ok()),
+            file_context_mode: row.try_get("file_context_mode")?,
+            file_context_budget: row.try_get("file_context_budget")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
@@ -37,6 +71,12 @@ pub struct CreateProjectRequest {
     pub name: String,
     pub description: Option<String>,
     pub instruction: Option<String>,
+    #[serde(default)]
+    pub parameters: Option<ModelParameters>,
+    #[serde(default)]
+    pub file_context_mode: Option<String>,
+    #[serde(default)]
+    pub file_context_budget: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +84,12 @@ pub struct UpdateProjectRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub instruction: Option<String>,
+    #[serde(default)]
+    pub parameters: Option<ModelParameters>,
+    #[serde(default)]
+    pub file_context_mode: Option<String>,
+    #[serde(default)]
+    pub file_context_budget: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row};
+use uuid::Uuid;
+
+pub const RAG_FILE_STATUS_PENDING: &str = "pending";
+pub const RAG_FILE_STATUS_PROCESSING: &str = "processing";
+pub const RAG_FILE_STATUS_COMPLETED: &str = "completed";
+pub const RAG_FILE_STATUS_FAILED: &str = "failed";
+
+/// A file ingested into a RAG database together with its ingestion state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RAGDatabaseFile {
+    pub id: Uuid,
+    pub rag_database_id: Uuid,
+    pub file_id: Uuid,
+    pub filename: String,
+    pub status: String,
+    pub chunk_count: i32,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for RAGDatabaseFile {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(RAGDatabaseFile {
+            id: row.try_get("id")?,
+            rag_database_id: row.try_get("rag_database_id")?,
+            file_id: row.try_get("file_id")?,
+            filename: row.try_get("filename")?,
+            status: row.try_get("status")?,
+            chunk_count: row.try_get("chunk_count")?,
+            error_message: row.try_get("error_message")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// An embedded chunk of text stored in a RAG database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RAGChunk {
+    pub id: Uuid,
+    pub rag_database_id: Uuid,
+    pub file_id: Option<Uuid>,
+    pub chunk_index: i32,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    #[serde(skip_serializing)]
+    pub embedding: Vec<f32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for RAGChunk {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(RAGChunk {
+            id: row.try_get("id")?,
+            rag_database_id: row.try_get("rag_database_id")?,
+            file_id: row.try_get("file_id")?,
+            chunk_index: row.try_get("chunk_index")?,
+            content: row.try_get("content")?,
+            metadata: row.try_get("metadata")?,
+            embedding: row.try_get("embedding")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Chunk data produced by ingestion, before it is stored
+#[derive(Debug, Clone)]
+pub struct NewRAGChunk {
+    pub chunk_index: i32,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub embedding: Vec<f32>,
+}
+
+/// A chunk returned from a similarity search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RAGSearchResult {
+    pub chunk_id: Uuid,
+    pub rag_database_id: Uuid,
+    pub file_id: Option<Uuid>,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub score: f32,
+}
+
+/// A retrieved chunk injected into a chat prompt, numbered as it is cited in the context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RAGCitation {
+    pub index: usize,
+    pub chunk_id: Uuid,
+    pub rag_database_id: Uuid,
+    pub rag_database_name: String,
+    pub file_id: Option<Uuid>,
+    pub filename: Option<String>,
+    pub content: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestRAGFilesRequest {
+    pub file_ids: Option<Vec<Uuid>>,
+    pub project_id: Option<Uuid>, // Ingest every file of this project
+}
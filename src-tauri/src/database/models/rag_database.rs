@@ -105,4 +105,20 @@ pub struct RAGDatabaseListResponse {
     pub total: i64,
     pub page: i32,
     pub per_page: i32,
-}
\ No newline at end of file
+}
+
+/// Replaces the RAG databases attached to an assistant or project
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetRAGDatabasesRequest {
+    pub rag_database_ids: Vec<Uuid>,
+}
+
+/// Runtime state of a local RAG database's in-memory index
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RAGDatabaseStatus {
+    pub database_id: Uuid,
+    pub is_active: bool,
+    pub index_loaded: bool,
+    pub vector_count: usize,
+    pub dimension: Option<usize>,
+}
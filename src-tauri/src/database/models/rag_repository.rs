@@ -74,8 +74,10 @@ pub struct RAGRepositoryConnectionTestResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DownloadRAGDatabaseFromRepositoryRequest {
     pub repository_id: Uuid,
-    pub database_id: String,
+    pub database_id: String, // Package path in the repository
     pub target_provider_id: Uuid,
     pub database_name: Option<String>,
     pub database_alias: Option<String>,
+    pub embedding_model: Option<String>, // Overrides the embedding model named in the package manifest
+    pub settings: Option<serde_json::Value>,
 }
\ No newline at end of file
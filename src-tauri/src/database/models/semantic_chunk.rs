@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row};
+use uuid::Uuid;
+
+/// Key in `files.processing_metadata` recording the semantic indexing of a file
+pub const SEMANTIC_INDEX_METADATA_KEY: &str = "semantic_index";
+
+/// A message or file whose text has not been embedded with the current model yet
+#[derive(Debug, Clone)]
+pub struct PendingSemanticSource {
+    /// Message or file id
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Message content; None for files, whose extracted text is read from storage
+    pub content: Option<String>,
+}
+
+/// An embedded chunk to store for a message or file
+#[derive(Debug, Clone)]
+pub struct NewSemanticChunk {
+    pub chunk_index: i32,
+    pub content: String,
+    pub embedding: Vec<f32>,
+}
+
+/// A conversation message or file matching a semantic search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSearchHit {
+    #[serde(skip)]
+    pub chunk_id: Uuid,
+    /// Cosine similarity between the query and the matching chunk
+    pub similarity: f32,
+    /// Text of the matching chunk
+    pub content: String,
+    pub conversation_id: Option<Uuid>,
+    pub conversation_title: Option<String>,
+    pub message_id: Option<Uuid>,
+    /// Branch holding the matching message, the active branch when it is on it
+    pub branch_id: Option<Uuid>,
+    pub role: Option<String>,
+    pub file_id: Option<Uuid>,
+    pub filename: Option<String>,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for SemanticSearchHit {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(SemanticSearchHit {
+            chunk_id: row.try_get("chunk_id")?,
+            // Filled in from the in-memory search
+            similarity: 0.0,
+            content: row.try_get("content")?,
+            conversation_id: row.try_get("conversation_id")?,
+            conversation_title: row.try_get("conversation_title")?,
+            message_id: row.try_get("message_id")?,
+            branch_id: row.try_get("branch_id")?,
+            role: row.try_get("role")?,
+            file_id: row.try_get("file_id")?,
+            filename: row.try_get("filename")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SemanticSearchResponse {
+    pub results: Vec<SemanticSearchHit>,
+}
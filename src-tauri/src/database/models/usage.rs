@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row};
+use uuid::Uuid;
+
+pub const USAGE_SOURCE_CHAT: &str = "chat";
+pub const USAGE_SOURCE_GATEWAY: &str = "gateway";
+pub const USAGE_SOURCE_EMBEDDINGS: &str = "embeddings";
+
+/// Monthly limits of a user group, reset at the start of each calendar month (UTC).
+/// Unset limits are unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserGroupQuotas {
+    /// Tokens all members of the group may use together
+    pub monthly_tokens: Option<i64>,
+    /// Provider requests all members of the group may make together
+    pub monthly_requests: Option<i64>,
+    /// Tokens each member of the group may use
+    pub user_monthly_tokens: Option<i64>,
+    /// Provider requests each member of the group may make
+    pub user_monthly_requests: Option<i64>,
+}
+
+impl UserGroupQuotas {
+    pub fn is_empty(&self) -> bool {
+        self.monthly_tokens.is_none()
+            && self.monthly_requests.is_none()
+            && self.user_monthly_tokens.is_none()
+            && self.user_monthly_requests.is_none()
+    }
+}
+
+/// Tokens used by one provider request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub message_id: Option<Uuid>,
+    pub provider_id: Option<Uuid>,
+    pub model_id: Option<Uuid>,
+    pub source: String,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+    pub total_tokens: i32,
+    pub is_estimated: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for TokenUsage {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(TokenUsage {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            message_id: row.try_get("message_id")?,
+            provider_id: row.try_get("provider_id")?,
+            model_id: row.try_get("model_id")?,
+            source: row.try_get("source")?,
+            prompt_tokens: row.try_get("prompt_tokens")?,
+            completion_tokens: row.try_get("completion_tokens")?,
+            total_tokens: row.try_get("total_tokens")?,
+            is_estimated: row.try_get("is_estimated")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordTokenUsageRequest {
+    pub user_id: Uuid,
+    pub message_id: Option<Uuid>,
+    pub provider_id: Uuid,
+    pub model_id: Uuid,
+    pub source: &'static str,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+    pub is_estimated: bool,
+}
+
+/// Totals over a period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for UsageTotals {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(UsageTotals {
+            requests: row.try_get("requests")?,
+            prompt_tokens: row.try_get("prompt_tokens")?,
+            completion_tokens: row.try_get("completion_tokens")?,
+            total_tokens: row.try_get("total_tokens")?,
+        })
+    }
+}
+
+/// Usage totals of one user, group, provider or model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageAggregate {
+    /// None for usage of deleted providers or models
+    pub id: Option<Uuid>,
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+impl FromRow<'_, sqlx::postgres::PgRow> for UsageAggregate {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        Ok(UsageAggregate {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            totals: UsageTotals::from_row(row)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroupBy {
+    User,
+    Group,
+    Provider,
+    Model,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageAggregateResponse {
+    pub group_by: UsageGroupBy,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub items: Vec<UsageAggregate>,
+}
+
+/// A group quota and how much of it has been used this month
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupQuotaStatus {
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub quotas: UserGroupQuotas,
+    /// Usage of all members of the group this month
+    pub group_usage: UsageTotals,
+}
+
+/// The current user's usage this month and the quotas that apply to them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserUsageResponse {
+    pub period_start: DateTime<Utc>,
+    pub usage: UsageTotals,
+    pub quotas: Vec<GroupQuotaStatus>,
+}
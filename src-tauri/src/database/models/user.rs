@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row};
 use uuid::Uuid;
 
+use super::usage::UserGroupQuotas;
+
 // Base User structure (for direct DB operations without aggregations)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserBase {
@@ -140,6 +142,7 @@ pub struct UserGroup {
     pub description: Option<String>,
     pub permissions: serde_json::Value,
     pub provider_ids: Vec<Uuid>,
+    pub quotas: Option<UserGroupQuotas>,
     pub is_protected: bool,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
@@ -154,6 +157,9 @@ impl FromRow<'_, sqlx::postgres::PgRow> for UserGroup {
             description: row.try_get("description")?,
             permissions: row.try_get("permissions")?,
             provider_ids: Vec::new(), // Loaded separately via joins
+            quotas: row
+                .try_get::<Option<serde_json::Value>, _>("quotas")?
+                .and_then(|quotas| serde_json::from_value(quotas).ok()),
             is_protected: row.try_get("is_protected")?,
             is_active: row.try_get("is_active")?,
             created_at: row.try_get("created_at")?,
@@ -275,6 +281,8 @@ pub struct CreateUserGroupRequest {
     pub description: Option<String>,
     pub permissions: serde_json::Value,
     pub provider_ids: Option<Vec<Uuid>>,
+    #[serde(default)]
+    pub quotas: Option<UserGroupQuotas>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -284,6 +292,8 @@ pub struct UpdateUserGroupRequest {
     pub permissions: Option<serde_json::Value>,
     pub provider_ids: Option<Vec<Uuid>>,
     pub is_active: Option<bool>,
+    #[serde(default)]
+    pub quotas: Option<UserGroupQuotas>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,93 @@
+use uuid::Uuid;
+
+use crate::database::{
+    get_database_pool,
+    models::{CreateUserApiKeyRequest, UserApiKey},
+};
+
+pub async fn create_user_api_key(
+    user_id: Uuid,
+    request: &CreateUserApiKeyRequest,
+    key_prefix: &str,
+    key_hash: &str,
+) -> Result<UserApiKey, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let api_key: UserApiKey = sqlx::query_as(
+        "INSERT INTO user_api_keys (id, user_id, name, key_prefix, key_hash, scopes, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING id, user_id, name, key_prefix, key_hash, scopes, expires_at, last_used_at, created_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(request.name.trim())
+    .bind(key_prefix)
+    .bind(key_hash)
+    .bind(request.scopes.as_ref().map(|scopes| serde_json::json!(scopes)))
+    .bind(request.expires_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(api_key)
+}
+
+pub async fn list_user_api_keys(user_id: Uuid) -> Result<Vec<UserApiKey>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let api_keys: Vec<UserApiKey> = sqlx::query_as(
+        "SELECT id, user_id, name, key_prefix, key_hash, scopes, expires_at, last_used_at, created_at
+         FROM user_api_keys
+         WHERE user_id = $1
+         ORDER BY created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(api_keys)
+}
+
+/// Revoke a key, returning whether the user owned it
+pub async fn delete_user_api_key(user_id: Uuid, key_id: Uuid) -> Result<bool, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let result = sqlx::query("DELETE FROM user_api_keys WHERE id = $1 AND user_id = $2")
+        .bind(key_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Find an unexpired key by the hash of its value
+pub async fn get_api_key_by_hash(key_hash: &str) -> Result<Option<UserApiKey>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let api_key: Option<UserApiKey> = sqlx::query_as(
+        "SELECT id, user_id, name, key_prefix, key_hash, scopes, expires_at, last_used_at, created_at
+         FROM user_api_keys
+         WHERE key_hash = $1 AND (expires_at IS NULL OR expires_at > NOW())",
+    )
+    .bind(key_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(api_key)
+}
+
+pub async fn touch_api_key_last_used(key_id: Uuid) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    sqlx::query("UPDATE user_api_keys SET last_used_at = NOW() WHERE id = $1")
+        .bind(key_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -39,9 +39,9 @@ pub async fn create_assistant(
     }
 
     let assistant_row: Assistant = sqlx::query_as(
-        "INSERT INTO assistants (id, name, description, instructions, parameters, created_by, is_template, is_default) 
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8) 
-         RETURNING id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at"
+        "INSERT INTO assistants (id, name, description, instructions, parameters, created_by, is_template, is_default, fallback_chain, response_format, context_window) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) 
+         RETURNING id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at"
     )
     .bind(assistant_id)
     .bind(&request.name)
@@ -51,6 +51,9 @@ pub async fn create_assistant(
     .bind(created_by)
     .bind(is_template)
     .bind(is_default)
+    .bind(serde_json::to_value(request.fallback_chain.unwrap_or_default()).unwrap())
+    .bind(request.response_format.as_ref().map(|f| serde_json::to_value(f).unwrap()))
+    .bind(request.context_window.as_ref().map(|w| serde_json::to_value(w).unwrap()))
     .fetch_one(&mut *tx)
     .await?;
 
@@ -69,7 +72,7 @@ pub async fn get_assistant_by_id(
     let pool = pool.as_ref();
 
     let assistant_row: Option<Assistant> = sqlx::query_as(
-        "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+        "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
          FROM assistants 
          WHERE id = $1 AND is_active = true AND (is_template = true OR created_by = $2)"
     )
@@ -95,7 +98,7 @@ pub async fn list_assistants(
     let (query, count_query) = if admin_view {
         // Admin can see only template assistants (created by admin)
         (
-            "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+            "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
              FROM assistants 
              WHERE is_template = true 
              ORDER BY created_at DESC 
@@ -105,7 +108,7 @@ pub async fn list_assistants(
     } else {
         // Regular users can see active template assistants and their own assistants
         (
-            "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+            "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
              FROM assistants 
              WHERE is_active = true AND ((is_template = true) OR created_by = $3)
              ORDER BY created_at DESC 
@@ -166,7 +169,7 @@ pub async fn update_assistant(
 
     // Get the current assistant to check its type
     let current_assistant: Option<Assistant> = sqlx::query_as(
-        "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+        "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
          FROM assistants WHERE id = $1"
     )
     .bind(assistant_id)
@@ -202,7 +205,7 @@ pub async fn update_assistant(
     let where_clause = if is_admin {
         "WHERE id = $1"
     } else {
-        "WHERE id = $1 AND created_by = $9"
+        "WHERE id = $1 AND created_by = $12"
     };
 
     let query = format!(
@@ -214,9 +217,12 @@ pub async fn update_assistant(
              is_template = COALESCE($6, is_template),
              is_default = COALESCE($7, is_default),
              is_active = COALESCE($8, is_active),
+             fallback_chain = COALESCE($9, fallback_chain),
+             response_format = COALESCE($10, response_format),
+             context_window = COALESCE($11, context_window),
              updated_at = CURRENT_TIMESTAMP
          {} 
-         RETURNING id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at",
+         RETURNING id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at",
         where_clause
     );
 
@@ -230,6 +236,9 @@ pub async fn update_assistant(
             .bind(request.is_template)
             .bind(request.is_default)
             .bind(request.is_active)
+            .bind(request.fallback_chain.as_ref().map(|c| serde_json::to_value(c).unwrap()))
+            .bind(request.response_format.as_ref().map(|f| serde_json::to_value(f).unwrap()))
+            .bind(request.context_window.as_ref().map(|w| serde_json::to_value(w).unwrap()))
             .fetch_optional(&mut *tx)
             .await?
     } else {
@@ -242,6 +251,9 @@ pub async fn update_assistant(
             .bind(request.is_template)
             .bind(request.is_default)
             .bind(request.is_active)
+            .bind(request.fallback_chain.as_ref().map(|c| serde_json::to_value(c).unwrap()))
+            .bind(request.response_format.as_ref().map(|f| serde_json::to_value(f).unwrap()))
+            .bind(request.context_window.as_ref().map(|w| serde_json::to_value(w).unwrap()))
             .bind(requesting_user_id)
             .fetch_optional(&mut *tx)
             .await?
@@ -284,7 +296,7 @@ pub async fn get_default_assistants() -> Result<Vec<Assistant>, sqlx::Error> {
     let pool = pool.as_ref();
 
     let assistant_rows: Vec<Assistant> = sqlx::query_as(
-        "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+        "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
          FROM assistants 
          WHERE is_template = true AND is_default = true AND is_active = true"
     )
@@ -306,7 +318,7 @@ pub async fn clone_assistant_for_user(
 
     // First get the template assistant
     let template: Option<Assistant> = sqlx::query_as(
-        "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+        "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
          FROM assistants 
          WHERE id = $1 AND is_template = true AND is_active = true"
     )
@@ -319,15 +331,18 @@ pub async fn clone_assistant_for_user(
     // Create a new assistant for the user based on the template
     let assistant_id = Uuid::new_v4();
     let assistant_row: Assistant = sqlx::query_as(
-        "INSERT INTO assistants (id, name, description, instructions, parameters, created_by, is_template, is_default, is_active) 
-         VALUES ($1, $2, $3, $4, $5, $6, false, false, true) 
-         RETURNING id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at"
+        "INSERT INTO assistants (id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, false, false, true) 
+         RETURNING id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at"
     )
     .bind(assistant_id)
     .bind(&template.name)
     .bind(&template.description)
     .bind(&template.instructions)
     .bind(template.parameters.as_ref().map(|p| serde_json::to_value(p).unwrap()))
+    .bind(serde_json::to_value(&template.fallback_chain).unwrap())
+    .bind(template.response_format.as_ref().map(|f| serde_json::to_value(f).unwrap()))
+    .bind(template.context_window.as_ref().map(|w| serde_json::to_value(w).unwrap()))
     .bind(user_id)
     .fetch_one(pool)
     .await?;
@@ -341,7 +356,7 @@ pub async fn get_default_assistant() -> Result<Option<Assistant>, sqlx::Error> {
     let pool = pool.as_ref();
 
     let assistant_row: Option<Assistant> = sqlx::query_as(
-        "SELECT id, name, description, instructions, parameters, created_by, is_template, is_default, is_active, created_at, updated_at 
+        "SELECT id, name, description, instructions, parameters, fallback_chain, response_format, context_window, created_by, is_template, is_default, is_active, created_at, updated_at 
          FROM assistants 
          WHERE name = 'Default Assistant' AND is_template = true AND is_active = true 
          LIMIT 1"
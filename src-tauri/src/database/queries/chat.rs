@@ -1,8 +1,9 @@
 use super::{branches, get_database_pool};
 use crate::database::models::{
-    Conversation, ConversationListResponse, ConversationSummary, CreateConversationRequest,
+    Branch, Conversation, ConversationListResponse, ConversationSearchFilters,
+    ConversationSearchResponse, ConversationSearchResult, ConversationSummary, CreateConversationRequest,
     EditMessageRequest, EditMessageResponse, Message, MessageBranch, SaveMessageRequest,
-    UpdateConversationRequest,
+    UpdateConversationRequest, MESSAGE_TYPE_TEXT,
 };
 use sqlx::{Error, Row};
 use std::collections::HashMap;
@@ -357,10 +358,83 @@ pub async fn save_message(
     request: SaveMessageRequest,
     user_id: Uuid,
     branch_id: Option<Uuid>,
+) -> Result<Message, Error> {
+    save_message_with_id(Uuid::new_v4(), request, user_id, branch_id).await
+}
+
+/// Save a message under an id allocated by the caller (used when the id must be known
+/// before the message is persisted, e.g. to cancel a streaming assistant response)
+pub async fn save_message_with_id(
+    message_id: Uuid,
+    request: SaveMessageRequest,
+    user_id: Uuid,
+    branch_id: Option<Uuid>,
 ) -> Result<Message, Error> {
     let pool = get_database_pool()?;
     let pool = pool.as_ref();
 
+    let target_branch_id = resolve_target_branch(pool, request.conversation_id, user_id, branch_id).await?;
+    let now = chrono::Utc::now();
+
+    // Start transaction for atomic message + branch_message creation
+    let mut tx = pool.begin().await?;
+    insert_message(&mut tx, message_id, &request, target_branch_id, now).await?;
+    tx.commit().await?;
+
+    let message_type = request.message_type();
+    // The model is recorded only for messages a provider generated
+    let generated_by_model = request.provider_id.map(|_| request.model_id);
+
+    // Return the created message with files
+    let files = if let Some(file_ids) = &request.file_ids {
+        // Get file details for the returned message
+        sqlx::query_as::<_, crate::database::models::File>(
+            r#"
+            SELECT f.id, f.user_id, f.filename, f.file_size, f.mime_type, f.checksum, 
+                   f.project_id, f.thumbnail_count, f.page_count, f.processing_metadata, 
+                   f.created_at, f.updated_at
+            FROM files f
+            WHERE f.id = ANY($1)
+            "#,
+        )
+        .bind(file_ids)
+        .fetch_all(pool)
+        .await?
+    } else {
+        vec![]
+    };
+
+    Ok(Message {
+        id: message_id,
+        conversation_id: request.conversation_id,
+        role: request.role.to_string(),
+        content: request.content.to_string(),
+        reasoning: request.reasoning,
+        originated_from_id: request.originated_from_id.or(Some(message_id)),
+        edit_count: Some(0),
+        created_at: now,
+        updated_at: now,
+        metadata: None,
+        files,
+        message_type: message_type.to_string(),
+        tool_calls: request.tool_calls,
+        tool_call_id: request.tool_call_id,
+        finish_reason: request.finish_reason,
+        provider_id: request.provider_id,
+        model_id: generated_by_model,
+        parameters: request.parameters,
+        system_prompt_override: request.system_prompt_override,
+    })
+}
+
+/// Resolve the branch a new message is saved to: `branch_id` when it belongs to the user,
+/// otherwise the conversation's active branch
+async fn resolve_target_branch(
+    pool: &sqlx::PgPool,
+    conversation_id: Uuid,
+    user_id: Uuid,
+    branch_id: Option<Uuid>,
+) -> Result<Uuid, Error> {
     // Determine which branch to use
     let target_branch_id = match branch_id {
         Some(branch_id) => {
@@ -386,7 +460,7 @@ pub async fn save_message(
         }
         None => {
             // Get the conversation to find the active branch
-            let conversation = match get_conversation_by_id(request.conversation_id, user_id).await? {
+            let conversation = match get_conversation_by_id(conversation_id, user_id).await? {
                 Some(conv) => conv,
                 None => return Err(Error::RowNotFound),
             };
@@ -403,31 +477,59 @@ pub async fn save_message(
         }
     };
 
-    let message_id = Uuid::new_v4();
-    let now = chrono::Utc::now();
+    Ok(target_branch_id)
+}
 
-    // Start transaction for atomic message + branch_message creation
-    let mut tx = pool.begin().await?;
+/// Insert a message, its branch relationship and its files within a transaction
+async fn insert_message(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    message_id: Uuid,
+    request: &SaveMessageRequest,
+    branch_id: Uuid,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    let message_type = request.message_type();
+    let tool_calls_json = request
+        .tool_calls
+        .as_ref()
+        .map(|calls| serde_json::to_value(calls).unwrap_or_default());
+    // The model is recorded only for messages a provider generated
+    let generated_by_model = request.provider_id.map(|_| request.model_id);
+    let parameters_json = request
+        .parameters
+        .as_ref()
+        .map(|parameters| serde_json::to_value(parameters).unwrap_or_default());
 
     // Insert the message
     sqlx::query(
         r#"
         INSERT INTO messages (
-            id, conversation_id, role, content,
+            id, conversation_id, role, content, reasoning,
             originated_from_id, edit_count,
+            message_type, tool_calls, tool_call_id, finish_reason,
+            provider_id, model_id, parameters, system_prompt_override,
             created_at, updated_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
         "#,
     )
     .bind(message_id)
     .bind(request.conversation_id)
     .bind(&request.role)
     .bind(&request.content)
-    .bind(message_id) // originated_from_id - same as id for new messages
+    .bind(&request.reasoning)
+    .bind(request.originated_from_id.unwrap_or(message_id)) // same as id for new messages
     .bind(0) // edit_count - 0 for new messages
+    .bind(message_type)
+    .bind(&tool_calls_json)
+    .bind(&request.tool_call_id)
+    .bind(&request.finish_reason)
+    .bind(request.provider_id)
+    .bind(generated_by_model)
+    .bind(&parameters_json)
+    .bind(&request.system_prompt_override)
     .bind(now)
     .bind(now)
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     // Insert the branch_message relationship (new messages are not clones)
@@ -442,7 +544,7 @@ pub async fn save_message(
     .bind(message_id)
     .bind(now)
     .bind(false) // New messages are not clones
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     // Insert file relationships if provided
@@ -457,45 +559,36 @@ pub async fn save_message(
             .bind(message_id)
             .bind(file_id)
             .bind(now)
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?;
         }
     }
 
-    // Commit transaction
-    tx.commit().await?;
+    Ok(())
+}
 
-    // Return the created message with files
-    let files = if let Some(file_ids) = &request.file_ids {
-        // Get file details for the returned message
-        sqlx::query_as::<_, crate::database::models::File>(
-            r#"
-            SELECT f.id, f.user_id, f.filename, f.file_size, f.mime_type, f.checksum, 
-                   f.project_id, f.thumbnail_count, f.page_count, f.processing_metadata, 
-                   f.created_at, f.updated_at
-            FROM files f
-            WHERE f.id = ANY($1)
-            "#,
-        )
-        .bind(file_ids)
-        .fetch_all(pool)
-        .await?
-    } else {
-        vec![]
-    };
+/// Save several messages to the conversation's active branch in one transaction, so either all
+/// of them are saved or none. Messages keep the order they are given in.
+pub async fn save_messages(
+    conversation_id: Uuid,
+    requests: &[SaveMessageRequest],
+    user_id: Uuid,
+) -> Result<(), Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
 
-    Ok(Message {
-        id: message_id,
-        conversation_id: request.conversation_id,
-        role: request.role.to_string(),
-        content: request.content.to_string(),
-        originated_from_id: Some(message_id),
-        edit_count: Some(0),
-        created_at: now,
-        updated_at: now,
-        metadata: None,
-        files,
-    })
+    let target_branch_id = resolve_target_branch(pool, conversation_id, user_id, None).await?;
+    let now = chrono::Utc::now();
+
+    let mut tx = pool.begin().await?;
+    for (index, request) in requests.iter().enumerate() {
+        // Messages are ordered by creation time
+        let created_at = now + chrono::Duration::microseconds(index as i64);
+        insert_message(&mut tx, Uuid::new_v4(), request, target_branch_id, created_at).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
 }
 
 /// Get messages for a conversation's active branch
@@ -523,8 +616,10 @@ pub async fn get_conversation_messages(
     let mut messages = sqlx::query_as::<_, Message>(
         r#"
         SELECT
-            m.id, m.conversation_id, m.role, m.content,
+            m.id, m.conversation_id, m.role, m.content, m.reasoning,
             m.originated_from_id, m.edit_count,
+            m.message_type, m.tool_calls, m.tool_call_id, m.finish_reason,
+            m.provider_id, m.model_id, m.parameters, m.system_prompt_override,
             m.created_at, m.updated_at
         FROM messages m
         INNER JOIN branch_messages bm ON m.id = bm.message_id
@@ -582,8 +677,10 @@ pub async fn get_conversation_messages_by_branch(
     let mut messages = sqlx::query_as::<_, Message>(
         r#"
         SELECT
-            m.id, m.conversation_id, m.role, m.content,
+            m.id, m.conversation_id, m.role, m.content, m.reasoning,
             m.originated_from_id, m.edit_count,
+            m.message_type, m.tool_calls, m.tool_call_id, m.finish_reason,
+            m.provider_id, m.model_id, m.parameters, m.system_prompt_override,
             m.created_at, m.updated_at
         FROM messages m
         INNER JOIN branch_messages bm ON m.id = bm.message_id
@@ -802,12 +899,21 @@ pub async fn edit_message(
         conversation_id,
         role,
         content: request.content,
+        reasoning: None,
         originated_from_id: Some(originated_from_id),
         edit_count: Some(edit_count + 1), // Incremented count
         created_at: original_created_at,
         updated_at: now,
         metadata: None,
         files,
+        message_type: MESSAGE_TYPE_TEXT.to_string(),
+        tool_calls: None,
+        tool_call_id: None,
+        finish_reason: None,
+        provider_id: None,
+        model_id: None,
+        parameters: None,
+        system_prompt_override: None,
     };
 
     Ok(Some(EditMessageResponse {
@@ -816,106 +922,136 @@ pub async fn edit_message(
     }))
 }
 
-/// Search conversations
+/// Matches of a search: messages and titles matching `websearch_to_tsquery($2)` in the
+/// user's conversations, with the best match of each conversation in `best`. Titles count
+/// double in the ranking and are filtered by the dates the conversation was active.
+const SEARCH_MATCHES_CTE: &str = r#"
+    WITH search AS (
+        SELECT websearch_to_tsquery('english', $2) AS query
+    ),
+    matches AS (
+        SELECT c.id AS conversation_id, m.id AS message_id, m.role, m.content AS matched_text,
+            ts_rank_cd(m.content_tsv, search.query) AS rank
+        FROM conversations c
+        INNER JOIN messages m ON m.conversation_id = c.id
+        CROSS JOIN search
+        WHERE c.user_id = $1
+          AND m.content_tsv @@ search.query
+          AND (($3::uuid IS NULL AND c.project_id IS NULL) OR c.project_id = $3)
+          AND ($4::timestamptz IS NULL OR m.created_at >= $4)
+          AND ($5::timestamptz IS NULL OR m.created_at < $5)
+          AND ($6::uuid IS NULL OR c.assistant_id = $6)
+          AND ($7::uuid IS NULL OR COALESCE(m.model_id, c.model_id) = $7)
+          AND ($8::text IS NULL OR m.role = $8)
+        UNION ALL
+        SELECT c.id, NULL, NULL, c.title, ts_rank_cd(c.title_tsv, search.query) * 2
+        FROM conversations c
+        CROSS JOIN search
+        WHERE c.user_id = $1
+          AND c.title_tsv @@ search.query
+          AND (($3::uuid IS NULL AND c.project_id IS NULL) OR c.project_id = $3)
+          AND ($4::timestamptz IS NULL OR c.updated_at >= $4)
+          AND ($5::timestamptz IS NULL OR c.created_at < $5)
+          AND ($6::uuid IS NULL OR c.assistant_id = $6)
+          AND ($7::uuid IS NULL OR c.model_id = $7)
+          AND $8::text IS NULL
+    ),
+    best AS (
+        SELECT DISTINCT ON (conversation_id) *
+        FROM matches
+        ORDER BY conversation_id, rank DESC
+    )
+"#;
+
+/// Search conversations by title and message content, best match first
 pub async fn search_conversations(
     user_id: Uuid,
     query: &str,
     page: i32,
     per_page: i32,
-    project_id: Option<Uuid>,
-) -> Result<ConversationListResponse, Error> {
+    filters: &ConversationSearchFilters,
+) -> Result<ConversationSearchResponse, Error> {
     let pool = get_database_pool()?;
     let pool = pool.as_ref();
 
+    if query.trim().is_empty() {
+        return Ok(ConversationSearchResponse {
+            conversations: Vec::new(),
+            total: 0,
+            page,
+            per_page,
+        });
+    }
+
     let offset = (page - 1) * per_page;
-    let search_pattern = format!("%{}%", query);
 
-    // Get total count for search results
-    let (total_query, total_bind_params) = if let Some(proj_id) = project_id {
-        (r#"
-        SELECT COUNT(DISTINCT c.id) as count
-        FROM conversations c
-        LEFT JOIN messages m ON c.id = m.conversation_id
-        WHERE c.user_id = $1
-        AND c.project_id = $2
-        AND (c.title ILIKE $3 OR m.content ILIKE $3)
-        "#, Some(proj_id))
-    } else {
-        (r#"
-        SELECT COUNT(DISTINCT c.id) as count
-        FROM conversations c
-        LEFT JOIN messages m ON c.id = m.conversation_id
-        WHERE c.user_id = $1
-        AND c.project_id IS NULL
-        AND (c.title ILIKE $2 OR m.content ILIKE $2)
-        "#, None)
-    };
-    
-    let mut total_query_builder = sqlx::query(total_query).bind(user_id);
-    if let Some(proj_id) = total_bind_params {
-        total_query_builder = total_query_builder.bind(proj_id).bind(&search_pattern);
-    } else {
-        total_query_builder = total_query_builder.bind(&search_pattern);
-    }
-    let total_row = total_query_builder.fetch_one(pool).await?;
-    let total: i64 = total_row.get("count");
+    let total_query = format!("{} SELECT COUNT(*) AS count FROM best", SEARCH_MATCHES_CTE);
+    let total: i64 = sqlx::query_scalar(&total_query)
+        .bind(user_id)
+        .bind(query)
+        .bind(filters.project_id)
+        .bind(filters.from)
+        .bind(filters.to)
+        .bind(filters.assistant_id)
+        .bind(filters.model_id)
+        .bind(filters.role.as_deref())
+        .fetch_one(pool)
+        .await?;
 
-    // Get conversations that match search with last message info
-    let (conversations_query, conversations_bind_params) = if let Some(proj_id) = project_id {
-        (r#"
-        SELECT DISTINCT ON (c.id)
-            c.id, c.title, c.user_id, c.project_id, c.assistant_id, c.model_id,
-            c.created_at, c.updated_at,
-            latest_msg.content as last_message,
-            (SELECT COUNT(*) FROM messages WHERE conversation_id = c.id) as message_count
-        FROM conversations c
-        LEFT JOIN messages m ON c.id = m.conversation_id
-        LEFT JOIN (
-            SELECT DISTINCT ON (conversation_id)
-                conversation_id, content
-            FROM messages
-            WHERE role = 'assistant'
-            ORDER BY conversation_id, created_at DESC
-        ) latest_msg ON c.id = latest_msg.conversation_id
-        WHERE c.user_id = $1
-        AND c.project_id = $2
-        AND (c.title ILIKE $3 OR m.content ILIKE $3)
-        ORDER BY c.id, c.updated_at DESC
-        LIMIT $4 OFFSET $5
-        "#, Some(proj_id))
-    } else {
-        (r#"
-        SELECT DISTINCT ON (c.id)
+    // Snippets are only highlighted for the page of results
+    let results_query = format!(
+        r#"{}
+        , page AS (
+            SELECT * FROM best
+            ORDER BY rank DESC, conversation_id
+            LIMIT $9 OFFSET $10
+        )
+        SELECT
             c.id, c.title, c.user_id, c.project_id, c.assistant_id, c.model_id,
             c.created_at, c.updated_at,
-            latest_msg.content as last_message,
-            (SELECT COUNT(*) FROM messages WHERE conversation_id = c.id) as message_count
-        FROM conversations c
-        LEFT JOIN messages m ON c.id = m.conversation_id
-        LEFT JOIN (
-            SELECT DISTINCT ON (conversation_id)
-                conversation_id, content
-            FROM messages
-            WHERE role = 'assistant'
-            ORDER BY conversation_id, created_at DESC
-        ) latest_msg ON c.id = latest_msg.conversation_id
-        WHERE c.user_id = $1
-        AND c.project_id IS NULL
-        AND (c.title ILIKE $2 OR m.content ILIKE $2)
-        ORDER BY c.id, c.updated_at DESC
-        LIMIT $3 OFFSET $4
-        "#, None)
-    };
-    
-    let mut conversations_query_builder = sqlx::query_as::<_, ConversationSummary>(conversations_query).bind(user_id);
-    if let Some(proj_id) = conversations_bind_params {
-        conversations_query_builder = conversations_query_builder.bind(proj_id).bind(&search_pattern).bind(per_page).bind(offset);
-    } else {
-        conversations_query_builder = conversations_query_builder.bind(&search_pattern).bind(per_page).bind(offset);
-    }
-    let conversations = conversations_query_builder.fetch_all(pool).await?;
+            latest_msg.content AS last_message,
+            (SELECT COUNT(*) FROM messages WHERE conversation_id = c.id) AS message_count,
+            page.rank::real AS rank,
+            page.message_id,
+            page.role AS matched_role,
+            ts_headline(
+                'english', page.matched_text, search.query,
+                'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2'
+            ) AS snippet,
+            (
+                SELECT bm.branch_id FROM branch_messages bm
+                WHERE bm.message_id = page.message_id
+                ORDER BY bm.branch_id = c.active_branch_id DESC, bm.is_clone, bm.created_at
+                LIMIT 1
+            ) AS branch_id
+        FROM page
+        INNER JOIN conversations c ON c.id = page.conversation_id
+        CROSS JOIN search
+        LEFT JOIN LATERAL (
+            SELECT content FROM messages
+            WHERE conversation_id = c.id AND role = 'assistant'
+            ORDER BY created_at DESC
+            LIMIT 1
+        ) latest_msg ON true
+        ORDER BY page.rank DESC, page.conversation_id
+        "#,
+        SEARCH_MATCHES_CTE
+    );
+    let conversations = sqlx::query_as::<_, ConversationSearchResult>(&results_query)
+        .bind(user_id)
+        .bind(query)
+        .bind(filters.project_id)
+        .bind(filters.from)
+        .bind(filters.to)
+        .bind(filters.assistant_id)
+        .bind(filters.model_id)
+        .bind(filters.role.as_deref())
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
 
-    Ok(ConversationListResponse {
+    Ok(ConversationSearchResponse {
         conversations,
         total,
         page,
@@ -1070,3 +1206,178 @@ pub async fn switch_conversation_branch(
 
     Ok(result.rows_affected() > 0)
 }
+
+/// Create a branch holding the messages of `source_branch_id` up to and including
+/// `through_message_id`, or all of its messages when None. The copied messages are clones.
+/// Returns None when the conversation, the branch or the message is not found
+pub async fn fork_branch(
+    conversation_id: Uuid,
+    source_branch_id: Uuid,
+    through_message_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<Option<Branch>, Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    // Verify user owns the conversation
+    if get_conversation_by_id(conversation_id, user_id).await?.is_none() {
+        return Ok(None);
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let branch_exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM branches WHERE id = $1 AND conversation_id = $2)",
+    )
+    .bind(source_branch_id)
+    .bind(conversation_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if !branch_exists {
+        return Ok(None);
+    }
+
+    // Messages are ordered by when they joined the branch
+    let cutoff = match through_message_id {
+        Some(message_id) => {
+            let created_at = sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>(
+                "SELECT created_at FROM branch_messages WHERE branch_id = $1 AND message_id = $2",
+            )
+            .bind(source_branch_id)
+            .bind(message_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            match created_at {
+                Some(created_at) => Some(created_at),
+                None => return Ok(None),
+            }
+        }
+        None => None,
+    };
+
+    let branch = branches::create_branch_tx(&mut tx, conversation_id, None).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO branch_messages (branch_id, message_id, created_at, is_clone)
+        SELECT $1, message_id, created_at, true
+        FROM branch_messages
+        WHERE branch_id = $2
+        AND ($3::timestamptz IS NULL OR created_at <= $3)
+        "#,
+    )
+    .bind(branch.id)
+    .bind(source_branch_id)
+    .bind(cutoff)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(branch))
+}
+
+/// The branch a message was created on, as opposed to the branches it was cloned into.
+/// Returns None when the message is not found
+pub async fn get_message_origin_branch(
+    message_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<Branch>, Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let branch = sqlx::query_as::<_, Branch>(
+        r#"
+        SELECT b.id, b.conversation_id, b.created_at
+        FROM branches b
+        INNER JOIN branch_messages bm ON b.id = bm.branch_id
+        INNER JOIN conversations c ON b.conversation_id = c.id
+        WHERE bm.message_id = $1
+          AND bm.is_clone = false
+          AND c.user_id = $2
+        "#,
+    )
+    .bind(message_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(branch)
+}
+
+/// Make the branch a message was created on the conversation's active branch.
+/// Returns the branch id, or None when the message is not found
+pub async fn select_message_branch(message_id: Uuid, user_id: Uuid) -> Result<Option<Uuid>, Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    // The branch holding the message itself rather than a clone of it
+    let branch_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE conversations c
+        SET active_branch_id = bm.branch_id, updated_at = CURRENT_TIMESTAMP
+        FROM branch_messages bm
+        INNER JOIN messages m ON bm.message_id = m.id
+        WHERE bm.message_id = $1
+          AND bm.is_clone = false
+          AND c.id = m.conversation_id
+          AND c.user_id = $2
+        RETURNING bm.branch_id
+        "#,
+    )
+    .bind(message_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(branch_id)
+}
+
+/// A value stored with a conversation under a key
+pub async fn get_conversation_metadata(
+    conversation_id: Uuid,
+    key: &str,
+) -> Result<Option<serde_json::Value>, Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let value = sqlx::query_scalar::<_, serde_json::Value>(
+        r#"
+        SELECT value FROM conversation_metadata
+        WHERE conversation_id = $1 AND key = $2
+        "#,
+    )
+    .bind(conversation_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(value)
+}
+
+/// Store a value with a conversation, replacing the one under the same key
+pub async fn set_conversation_metadata(
+    conversation_id: Uuid,
+    key: &str,
+    value: &serde_json::Value,
+) -> Result<(), Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    sqlx::query(
+        r#"
+        INSERT INTO conversation_metadata (conversation_id, key, value)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (conversation_id, key) DO UPDATE SET value = EXCLUDED.value
+        "#,
+    )
+    .bind(conversation_id)
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -108,6 +108,20 @@ pub async fn set_default_language(language: &str) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+pub async fn get_semantic_search_model_id() -> Result<Option<uuid::Uuid>, sqlx::Error> {
+    Ok(get_config_value::<Option<uuid::Uuid>>("semantic_search.embedding_model_id").await?.flatten())
+}
+
+pub async fn set_semantic_search_model_id(model_id: Option<uuid::Uuid>) -> Result<(), sqlx::Error> {
+    set_config_value(
+        "semantic_search.embedding_model_id",
+        &model_id,
+        Some("Embedding model used to index conversations and files for semantic search"),
+    )
+    .await?;
+    Ok(())
+}
+
 // HTTP Proxy configuration functions - using single JSON object
 pub async fn get_proxy_settings() -> Result<ProxySettings, sqlx::Error> {
     Ok(get_config_value::<ProxySettings>("proxy").await?.unwrap_or_default())
@@ -4,7 +4,7 @@ use crate::database::{
     models::{
         CreateDownloadInstanceRequest, DownloadInstance, DownloadInstanceListResponse,
         DownloadStatus, DownloadStatusSummary, UpdateDownloadProgressRequest,
-        UpdateDownloadStatusRequest,
+        UpdateDownloadStatusRequest, DOWNLOAD_TYPE_MODEL,
     },
     queries::get_database_pool,
 };
@@ -18,7 +18,7 @@ pub async fn get_download_instance_by_id(
 
     let download_row: Option<DownloadInstance> = sqlx::query_as(
         "SELECT id, provider_id, repository_id, request_data, status, progress_data, 
-         error_message, started_at, completed_at, model_id, created_at, updated_at
+         error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at
          FROM download_instances 
          WHERE id = $1",
     )
@@ -42,7 +42,7 @@ pub async fn get_download_instances(
     // Build the query with optional status filter
     let mut query = String::from(
         "SELECT id, provider_id, repository_id, request_data, status, progress_data, 
-         error_message, started_at, completed_at, model_id, created_at, updated_at
+         error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at
          FROM download_instances 
          WHERE 1=1",
     );
@@ -101,10 +101,10 @@ pub async fn create_download_instance(
     let download_id = Uuid::new_v4();
 
     let download_row: DownloadInstance = sqlx::query_as(
-        "INSERT INTO download_instances (id, provider_id, repository_id, request_data, status)
-         VALUES ($1, $2, $3, $4, $5) 
+        "INSERT INTO download_instances (id, provider_id, repository_id, request_data, status, download_type)
+         VALUES ($1, $2, $3, $4, $5, $6) 
          RETURNING id, provider_id, repository_id, request_data, status, progress_data, 
-         error_message, started_at, completed_at, model_id, created_at, updated_at",
+         error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at",
     )
     .bind(download_id)
     .bind(request.provider_id)
@@ -114,6 +114,7 @@ pub async fn create_download_instance(
             .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
     )
     .bind(DownloadStatus::Pending.as_str())
+    .bind(request.download_type.as_deref().unwrap_or(DOWNLOAD_TYPE_MODEL))
     .fetch_one(pool)
     .await?;
 
@@ -136,7 +137,7 @@ pub async fn update_download_progress(
                  updated_at = CURRENT_TIMESTAMP
              WHERE id = $1 
              RETURNING id, provider_id, repository_id, request_data, status, progress_data, 
-             error_message, started_at, completed_at, model_id, created_at, updated_at",
+             error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at",
         )
         .bind(download_id)
         .bind(
@@ -153,7 +154,7 @@ pub async fn update_download_progress(
                  updated_at = CURRENT_TIMESTAMP
              WHERE id = $1 
              RETURNING id, provider_id, repository_id, request_data, status, progress_data, 
-             error_message, started_at, completed_at, model_id, created_at, updated_at",
+             error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at",
         )
         .bind(download_id)
         .bind(
@@ -187,7 +188,7 @@ pub async fn update_download_status(
                      updated_at = CURRENT_TIMESTAMP
                  WHERE id = $1 
                  RETURNING id, provider_id, repository_id, request_data, status, progress_data, 
-                 error_message, started_at, completed_at, model_id, created_at, updated_at",
+                 error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at",
             )
             .bind(download_id)
             .bind(request.status.as_str())
@@ -205,7 +206,7 @@ pub async fn update_download_status(
                      updated_at = CURRENT_TIMESTAMP
                  WHERE id = $1 
                  RETURNING id, provider_id, repository_id, request_data, status, progress_data, 
-                 error_message, started_at, completed_at, model_id, created_at, updated_at",
+                 error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at",
             )
             .bind(download_id)
             .bind(request.status.as_str())
@@ -221,7 +222,7 @@ pub async fn update_download_status(
                      updated_at = CURRENT_TIMESTAMP
                  WHERE id = $1 
                  RETURNING id, provider_id, repository_id, request_data, status, progress_data, 
-                 error_message, started_at, completed_at, model_id, created_at, updated_at",
+                 error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at",
             )
             .bind(download_id)
             .bind(request.status.as_str())
@@ -234,6 +235,28 @@ pub async fn update_download_status(
     Ok(download_row)
 }
 
+/// Record the RAG database created by a completed RAG database download
+pub async fn set_download_rag_database(
+    download_id: Uuid,
+    rag_database_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    sqlx::query(
+        "UPDATE download_instances
+         SET rag_database_id = $2,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE id = $1",
+    )
+    .bind(download_id)
+    .bind(rag_database_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 /// Delete a download instance
 pub async fn delete_download_instance(download_id: Uuid) -> Result<bool, sqlx::Error> {
     let pool = get_database_pool()?;
@@ -282,9 +305,9 @@ pub async fn get_active_downloads_for_repository(
 
     let downloads: Vec<DownloadInstance> = sqlx::query_as(
         "SELECT id, provider_id, repository_id, request_data, status, progress_data, 
-         error_message, started_at, completed_at, model_id, created_at, updated_at
+         error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at
          FROM download_instances 
-         WHERE repository_id = $1 AND status IN ('pending', 'downloading')
+         WHERE repository_id = $1 AND download_type = 'model' AND status IN ('pending', 'downloading')
          ORDER BY created_at ASC",
     )
     .bind(repository_id)
@@ -301,7 +324,7 @@ pub async fn get_all_active_downloads() -> Result<Vec<DownloadInstance>, sqlx::E
 
     let downloads: Vec<DownloadInstance> = sqlx::query_as(
         "SELECT id, provider_id, repository_id, request_data, status, progress_data, 
-         error_message, started_at, completed_at, model_id, created_at, updated_at
+         error_message, started_at, completed_at, model_id, download_type, rag_database_id, created_at, updated_at
          FROM download_instances 
          WHERE status IN ('pending', 'downloading', 'failed', 'cancelled')
          ORDER BY created_at ASC",
@@ -1,3 +1,4 @@
+pub mod api_keys;
 pub mod assistants;
 pub mod branches;
 pub mod chat;
@@ -7,9 +8,13 @@ pub mod files;
 pub mod models;
 pub mod projects;
 pub mod providers;
+pub mod rag_attachments;
+pub mod rag_chunks;
 pub mod rag_providers;
 pub mod rag_repositories;
 pub mod repositories;
+pub mod semantic_chunks;
+pub mod usage;
 pub mod user_group_providers;
 pub mod user_groups;
 pub mod user_settings;
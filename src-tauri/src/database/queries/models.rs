@@ -5,8 +5,8 @@ use uuid::Uuid;
 use crate::database::{
     get_database_pool,
     models::{
-        CreateModelRequest, Model, ModelFile, ModelStatusCounts, ModelStorageInfo,
-        UpdateModelRequest, Provider,
+        CreateModelRequest, FallbackTarget, Model, ModelFile, ModelStatusCounts, ModelStorageInfo,
+        ModelSyncResponse, UpdateModelRequest, Provider,
     },
 };
 
@@ -36,9 +36,9 @@ pub async fn create_model(
     let model_id = Uuid::new_v4();
 
     let model_row: Model = sqlx::query_as(
-    "INSERT INTO models (id, provider_id, name, alias, description, enabled, capabilities, parameters, settings)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
-         RETURNING id, provider_id, name, alias, description, enabled, is_deprecated, is_active, capabilities, parameters, created_at, updated_at, file_size_bytes, validation_status, validation_issues, settings, port, pid"
+    "INSERT INTO models (id, provider_id, name, alias, description, enabled, capabilities, parameters, settings, fallback_chain, context_length)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) 
+         RETURNING id, provider_id, name, alias, description, enabled, is_deprecated, is_active, capabilities, parameters, fallback_chain, context_length, created_at, updated_at, file_size_bytes, validation_status, validation_issues, settings, port, pid"
   )
     .bind(model_id)
     .bind(provider_id)
@@ -49,12 +49,103 @@ pub async fn create_model(
     .bind(request.capabilities.as_ref().map(|c| serde_json::to_value(c).unwrap()).unwrap_or_else(|| serde_json::json!({})))
     .bind(request.parameters.as_ref().map(|p| serde_json::to_value(p).unwrap()).unwrap_or_else(|| serde_json::json!({})))
     .bind(request.settings.as_ref().map(|s| serde_json::to_value(s).unwrap()).unwrap_or_else(|| serde_json::json!({})))
+    .bind(serde_json::to_value(request.fallback_chain.as_deref().unwrap_or_default()).unwrap())
+    .bind(request.context_length)
       .fetch_one(pool)
     .await?;
 
     Ok(model_row)
 }
 
+/// Reconcile a provider's models with the models its vendor lists. New models are added
+/// disabled, listed models are un-deprecated and models that are no longer listed are
+/// marked deprecated. Capabilities are only overwritten when the vendor reports them.
+pub async fn sync_provider_models(
+    provider_id: Uuid,
+    discovered: &[CreateModelRequest],
+) -> Result<ModelSyncResponse, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+    let mut tx = pool.begin().await?;
+
+    let existing: Vec<(String, bool)> =
+        sqlx::query_as("SELECT name, is_deprecated FROM models WHERE provider_id = $1")
+            .bind(provider_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+    let mut result = ModelSyncResponse {
+        total: discovered.len(),
+        ..Default::default()
+    };
+
+    for model in discovered {
+        let capabilities = model
+            .capabilities
+            .as_ref()
+            .map(|c| serde_json::to_value(c).unwrap());
+
+        match existing.iter().find(|(name, _)| *name == model.name) {
+            Some((_, is_deprecated)) => {
+                sqlx::query(
+                    "UPDATE models
+                     SET is_deprecated = FALSE,
+                         capabilities = COALESCE($3, capabilities),
+                         updated_at = CURRENT_TIMESTAMP
+                     WHERE provider_id = $1 AND name = $2",
+                )
+                .bind(provider_id)
+                .bind(&model.name)
+                .bind(capabilities)
+                .execute(&mut *tx)
+                .await?;
+
+                if *is_deprecated {
+                    result.restored.push(model.name.clone());
+                }
+            }
+            None => {
+                sqlx::query(
+                    "INSERT INTO models (id, provider_id, name, alias, description, enabled, capabilities, parameters, settings)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, '{}', '{}')",
+                )
+                .bind(Uuid::new_v4())
+                .bind(provider_id)
+                .bind(&model.name)
+                .bind(&model.alias)
+                .bind(&model.description)
+                .bind(model.enabled.unwrap_or(false))
+                .bind(capabilities.unwrap_or_else(|| serde_json::json!({})))
+                .execute(&mut *tx)
+                .await?;
+
+                result.added.push(model.name.clone());
+            }
+        }
+    }
+
+    for (name, is_deprecated) in &existing {
+        if *is_deprecated || discovered.iter().any(|model| model.name == *name) {
+            continue;
+        }
+
+        sqlx::query(
+            "UPDATE models
+             SET is_deprecated = TRUE, updated_at = CURRENT_TIMESTAMP
+             WHERE provider_id = $1 AND name = $2",
+        )
+        .bind(provider_id)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+
+        result.deprecated.push(name.clone());
+    }
+
+    tx.commit().await?;
+    Ok(result)
+}
+
 pub async fn update_model(
     model_id: Uuid,
     request: UpdateModelRequest,
@@ -72,9 +163,11 @@ pub async fn update_model(
              capabilities = COALESCE($7, capabilities),
              parameters = COALESCE($8, parameters),
              settings = COALESCE($9, settings),
+             fallback_chain = COALESCE($10, fallback_chain),
+             context_length = CASE WHEN $11::int IS NULL THEN context_length ELSE NULLIF($11, 0) END,
              updated_at = CURRENT_TIMESTAMP
          WHERE id = $1 
-         RETURNING id, provider_id, name, alias, description, enabled, is_deprecated, is_active, capabilities, parameters, created_at, updated_at, file_size_bytes, validation_status, validation_issues, settings, port, pid"
+         RETURNING id, provider_id, name, alias, description, enabled, is_deprecated, is_active, capabilities, parameters, fallback_chain, context_length, created_at, updated_at, file_size_bytes, validation_status, validation_issues, settings, port, pid"
   )
     .bind(model_id)
     .bind(&request.name)
@@ -85,12 +178,38 @@ pub async fn update_model(
     .bind(request.capabilities.as_ref().map(|c| serde_json::to_value(c).unwrap()).unwrap_or_else(|| serde_json::json!({})))
     .bind(request.parameters.as_ref().map(|p| serde_json::to_value(p).unwrap()).unwrap_or_else(|| serde_json::json!({})))
     .bind(request.settings.as_ref().map(|s| serde_json::to_value(s).unwrap()).unwrap_or_else(|| serde_json::json!({})))
+    .bind(request.fallback_chain.as_ref().map(|c| serde_json::to_value(c).unwrap()))
+    .bind(request.context_length)
     .fetch_optional(pool)
     .await?;
 
     Ok(model_row)
 }
 
+/// Check that every entry of a fallback chain names an existing model of the given provider
+pub async fn fallback_chain_is_valid(chain: &[FallbackTarget]) -> Result<bool, sqlx::Error> {
+    if chain.is_empty() {
+        return Ok(true);
+    }
+
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let model_ids: Vec<Uuid> = chain.iter().map(|target| target.model_id).collect();
+    let provider_ids: Vec<Uuid> = chain.iter().map(|target| target.provider_id).collect();
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM UNNEST($1::uuid[], $2::uuid[]) AS chain(model_id, provider_id)
+         JOIN models m ON m.id = chain.model_id AND m.provider_id = chain.provider_id",
+    )
+    .bind(&model_ids)
+    .bind(&provider_ids)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0 as usize == chain.len())
+}
+
 pub async fn delete_model(model_id: Uuid) -> Result<bool, sqlx::Error> {
     let pool = get_database_pool()?;
     let pool = pool.as_ref();
@@ -147,12 +266,12 @@ pub async fn create_local_model(model_id: &Uuid, request: &CreateModelRequest) -
             id, provider_id, name, alias, description, 
             file_size_bytes, enabled, 
             is_deprecated, is_active, capabilities, parameters, 
-            validation_status, settings, created_at, updated_at
+            validation_status, settings, context_length, created_at, updated_at
         ) VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16
         ) RETURNING id, provider_id, name, alias, description, 
                    file_size_bytes, enabled, 
-                   is_deprecated, is_active, capabilities, parameters, 
+                   is_deprecated, is_active, capabilities, parameters, fallback_chain, context_length, 
                    validation_status, validation_issues, settings, port, pid, created_at, updated_at
         "#,
     )
@@ -175,6 +294,7 @@ pub async fn create_local_model(model_id: &Uuid, request: &CreateModelRequest) -
     .bind(serde_json::json!({}))
     .bind("pending")
     .bind(serde_json::json!({})) // Model settings with architecture
+    .bind(request.context_length)
     .bind(now)
     .bind(now)
     .fetch_one(pool)
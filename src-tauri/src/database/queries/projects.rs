@@ -4,6 +4,7 @@ use uuid::Uuid;
 use crate::database::models::{
     CreateProjectRequest, Project, 
     ProjectListResponse, UpdateProjectRequest,
+    DEFAULT_PROJECT_FILE_CONTEXT_BUDGET, PROJECT_FILE_CONTEXT_ALWAYS,
 };
 
 // Project CRUD operations
@@ -16,9 +17,13 @@ pub async fn create_project(
 
     let project = sqlx::query_as::<_, Project>(
         r#"
-        INSERT INTO projects (id, user_id, name, description, instruction)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, user_id, name, description, instruction, created_at, updated_at, 0 as conversation_count
+        INSERT INTO projects (
+            id, user_id, name, description, instruction, parameters,
+            file_context_mode, file_context_budget
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, user_id, name, description, instruction, parameters,
+                  file_context_mode, file_context_budget, created_at, updated_at, 0 as conversation_count
         "#,
     )
     .bind(id)
@@ -26,6 +31,9 @@ pub async fn create_project(
     .bind(&request.name)
     .bind(&request.description)
     .bind(&request.instruction)
+    .bind(request.parameters.as_ref().map(|parameters| serde_json::json!(parameters)))
+    .bind(request.file_context_mode.as_deref().unwrap_or(PROJECT_FILE_CONTEXT_ALWAYS))
+    .bind(request.file_context_budget.unwrap_or(DEFAULT_PROJECT_FILE_CONTEXT_BUDGET))
     .fetch_one(pool)
     .await?;
 
@@ -39,12 +47,14 @@ pub async fn get_project_by_id(
 ) -> Result<Option<Project>, sqlx::Error> {
     let project = sqlx::query_as::<_, Project>(
         r#"
-        SELECT p.id, p.user_id, p.name, p.description, p.instruction, p.created_at, p.updated_at,
+        SELECT p.id, p.user_id, p.name, p.description, p.instruction, p.parameters,
+               p.file_context_mode, p.file_context_budget, p.created_at, p.updated_at,
                COALESCE(COUNT(c.id), 0) as conversation_count
         FROM projects p
         LEFT JOIN conversations c ON p.id = c.project_id
         WHERE p.id = $1 AND p.user_id = $2
-        GROUP BY p.id, p.user_id, p.name, p.description, p.instruction, p.created_at, p.updated_at
+        GROUP BY p.id, p.user_id, p.name, p.description, p.instruction, p.parameters,
+                 p.file_context_mode, p.file_context_budget, p.created_at, p.updated_at
         "#,
     )
     .bind(project_id)
@@ -92,12 +102,14 @@ pub async fn list_projects(
     // Get projects with conversation counts
     let projects_query = format!(
         r#"
-        SELECT p.id, p.user_id, p.name, p.description, p.instruction, p.created_at, p.updated_at,
+        SELECT p.id, p.user_id, p.name, p.description, p.instruction, p.parameters,
+               p.file_context_mode, p.file_context_budget, p.created_at, p.updated_at,
                COALESCE(COUNT(c.id), 0) as conversation_count
         FROM projects p
         LEFT JOIN conversations c ON p.id = c.project_id
         {}
-        GROUP BY p.id, p.user_id, p.name, p.description, p.instruction, p.created_at, p.updated_at
+        GROUP BY p.id, p.user_id, p.name, p.description, p.instruction, p.parameters,
+                 p.file_context_mode, p.file_context_budget, p.created_at, p.updated_at
         ORDER BY p.updated_at DESC
         LIMIT $2 OFFSET $3
         "#,
@@ -142,7 +154,13 @@ pub async fn update_project(
     }
 
     // Use a simpler approach with conditional updates
-    if request.name.is_some() || request.description.is_some() || request.instruction.is_some() {
+    if request.name.is_some()
+        || request.description.is_some()
+        || request.instruction.is_some()
+        || request.parameters.is_some()
+        || request.file_context_mode.is_some()
+        || request.file_context_budget.is_some()
+    {
         // Get current values
         let current = existing_project.unwrap();
 
@@ -155,17 +173,33 @@ pub async fn update_project(
             .instruction
             .as_ref()
             .or(current.instruction.as_ref());
+        let parameters = request
+            .parameters
+            .as_ref()
+            .or(current.parameters.as_ref())
+            .map(|parameters| serde_json::json!(parameters));
+        let file_context_mode = request
+            .file_context_mode
+            .as_ref()
+            .unwrap_or(&current.file_context_mode);
+        let file_context_budget = request
+            .file_context_budget
+            .unwrap_or(current.file_context_budget);
 
         sqlx::query(
             r#"
             UPDATE projects 
-            SET name = $1, description = $2, instruction = $3, updated_at = NOW()
-            WHERE id = $4 AND user_id = $5
+            SET name = $1, description = $2, instruction = $3, parameters = $4,
+                file_context_mode = $5, file_context_budget = $6, updated_at = NOW()
+            WHERE id = $7 AND user_id = $8
             "#,
         )
         .bind(name)
         .bind(description)
         .bind(instruction)
+        .bind(parameters)
+        .bind(file_context_mode)
+        .bind(file_context_budget)
         .bind(project_id)
         .bind(user_id)
         .execute(pool)
@@ -0,0 +1,138 @@
+use uuid::Uuid;
+
+use crate::database::{get_database_pool, models::RAGDatabase};
+
+// Assistant RAG database queries
+pub async fn list_assistant_rag_databases(assistant_id: Uuid) -> Result<Vec<RAGDatabase>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let databases: Vec<RAGDatabase> = sqlx::query_as(
+        "SELECT d.id, d.provider_id, d.name, d.alias, d.description, d.enabled, d.is_active, d.collection_name,
+                d.embedding_model, d.chunk_size, d.chunk_overlap, d.capabilities, d.settings, d.created_at, d.updated_at
+         FROM rag_databases d
+         INNER JOIN assistant_rag_databases ard ON ard.rag_database_id = d.id
+         WHERE ard.assistant_id = $1
+         ORDER BY d.name ASC",
+    )
+    .bind(assistant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(databases)
+}
+
+pub async fn set_assistant_rag_databases(
+    assistant_id: Uuid,
+    rag_database_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM assistant_rag_databases WHERE assistant_id = $1")
+        .bind(assistant_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO assistant_rag_databases (assistant_id, rag_database_id)
+         SELECT $1, id FROM rag_databases WHERE id = ANY($2)",
+    )
+    .bind(assistant_id)
+    .bind(rag_database_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// Project RAG database queries
+pub async fn list_project_rag_databases(project_id: Uuid) -> Result<Vec<RAGDatabase>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let databases: Vec<RAGDatabase> = sqlx::query_as(
+        "SELECT d.id, d.provider_id, d.name, d.alias, d.description, d.enabled, d.is_active, d.collection_name,
+                d.embedding_model, d.chunk_size, d.chunk_overlap, d.capabilities, d.settings, d.created_at, d.updated_at
+         FROM rag_databases d
+         INNER JOIN project_rag_databases prd ON prd.rag_database_id = d.id
+         WHERE prd.project_id = $1
+         ORDER BY d.name ASC",
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(databases)
+}
+
+pub async fn set_project_rag_databases(
+    project_id: Uuid,
+    rag_database_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM project_rag_databases WHERE project_id = $1")
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO project_rag_databases (project_id, rag_database_id)
+         SELECT $1, id FROM rag_databases WHERE id = ANY($2)",
+    )
+    .bind(project_id)
+    .bind(rag_database_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Count how many of the given ids are enabled RAG databases, used to validate attachments
+pub async fn count_enabled_rag_databases(rag_database_ids: &[Uuid]) -> Result<i64, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM rag_databases WHERE id = ANY($1) AND enabled = true")
+        .bind(rag_database_ids)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count.0)
+}
+
+/// Enabled RAG databases (of enabled providers) attached to an assistant or a project
+pub async fn get_chat_rag_databases(
+    assistant_id: Uuid,
+    project_id: Option<Uuid>,
+) -> Result<Vec<RAGDatabase>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let databases: Vec<RAGDatabase> = sqlx::query_as(
+        "SELECT d.id, d.provider_id, d.name, d.alias, d.description, d.enabled, d.is_active, d.collection_name,
+                d.embedding_model, d.chunk_size, d.chunk_overlap, d.capabilities, d.settings, d.created_at, d.updated_at
+         FROM rag_databases d
+         INNER JOIN rag_providers p ON p.id = d.provider_id
+         WHERE d.enabled = true AND p.enabled = true
+           AND (d.id IN (SELECT rag_database_id FROM assistant_rag_databases WHERE assistant_id = $1)
+                OR d.id IN (SELECT rag_database_id FROM project_rag_databases WHERE project_id = $2))
+         ORDER BY d.name ASC",
+    )
+    .bind(assistant_id)
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(databases)
+}
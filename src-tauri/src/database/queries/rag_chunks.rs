@@ -0,0 +1,228 @@
+use uuid::Uuid;
+
+use crate::database::{
+    get_database_pool,
+    models::{NewRAGChunk, RAGChunk, RAGDatabaseFile, RAG_FILE_STATUS_PENDING},
+};
+
+// RAG database file queries
+pub async fn upsert_rag_database_file(
+    database_id: Uuid,
+    file_id: Uuid,
+) -> Result<RAGDatabaseFile, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    // Re-ingesting a file resets its state to pending
+    let file_row: RAGDatabaseFile = sqlx::query_as(
+        "WITH upserted AS (
+             INSERT INTO rag_database_files (rag_database_id, file_id, status)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (rag_database_id, file_id)
+             DO UPDATE SET status = EXCLUDED.status, error_message = NULL, updated_at = NOW()
+             RETURNING *
+         )
+         SELECT u.id, u.rag_database_id, u.file_id, f.filename, u.status, u.chunk_count,
+                u.error_message, u.created_at, u.updated_at
+         FROM upserted u
+         INNER JOIN files f ON f.id = u.file_id",
+    )
+    .bind(database_id)
+    .bind(file_id)
+    .bind(RAG_FILE_STATUS_PENDING)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(file_row)
+}
+
+pub async fn update_rag_database_file_status(
+    database_id: Uuid,
+    file_id: Uuid,
+    status: &str,
+    chunk_count: Option<i32>,
+    error_message: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    sqlx::query(
+        "UPDATE rag_database_files
+         SET status = $3,
+             chunk_count = COALESCE($4, chunk_count),
+             error_message = $5,
+             updated_at = NOW()
+         WHERE rag_database_id = $1 AND file_id = $2",
+    )
+    .bind(database_id)
+    .bind(file_id)
+    .bind(status)
+    .bind(chunk_count)
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_rag_database_files(database_id: Uuid) -> Result<Vec<RAGDatabaseFile>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let files: Vec<RAGDatabaseFile> = sqlx::query_as(
+        "SELECT rdf.id, rdf.rag_database_id, rdf.file_id, f.filename, rdf.status, rdf.chunk_count,
+                rdf.error_message, rdf.created_at, rdf.updated_at
+         FROM rag_database_files rdf
+         INNER JOIN files f ON f.id = rdf.file_id
+         WHERE rdf.rag_database_id = $1
+         ORDER BY rdf.created_at DESC",
+    )
+    .bind(database_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(files)
+}
+
+/// Remove a file and its chunks from a RAG database
+pub async fn delete_rag_database_file(database_id: Uuid, file_id: Uuid) -> Result<bool, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM rag_chunks WHERE rag_database_id = $1 AND file_id = $2")
+        .bind(database_id)
+        .bind(file_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM rag_database_files WHERE rag_database_id = $1 AND file_id = $2")
+        .bind(database_id)
+        .bind(file_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// RAG chunk queries
+/// Replace all chunks of a file in a RAG database, returning the stored chunks
+pub async fn replace_rag_file_chunks(
+    database_id: Uuid,
+    file_id: Option<Uuid>,
+    chunks: Vec<NewRAGChunk>,
+) -> Result<Vec<RAGChunk>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+    let mut tx = pool.begin().await?;
+
+    if let Some(file_id) = file_id {
+        sqlx::query("DELETE FROM rag_chunks WHERE rag_database_id = $1 AND file_id = $2")
+            .bind(database_id)
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let mut stored = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let chunk_row: RAGChunk = sqlx::query_as(
+            "INSERT INTO rag_chunks (id, rag_database_id, file_id, chunk_index, content, metadata, embedding)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, rag_database_id, file_id, chunk_index, content, metadata, embedding, created_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(database_id)
+        .bind(file_id)
+        .bind(chunk.chunk_index)
+        .bind(&chunk.content)
+        .bind(&chunk.metadata)
+        .bind(&chunk.embedding)
+        .fetch_one(&mut *tx)
+        .await?;
+        stored.push(chunk_row);
+    }
+
+    tx.commit().await?;
+
+    Ok(stored)
+}
+
+/// Load every chunk of a RAG database (used to build the in-memory index)
+pub async fn list_rag_chunks(database_id: Uuid) -> Result<Vec<RAGChunk>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let chunks: Vec<RAGChunk> = sqlx::query_as(
+        "SELECT id, rag_database_id, file_id, chunk_index, content, metadata, embedding, created_at
+         FROM rag_chunks
+         WHERE rag_database_id = $1
+         ORDER BY file_id, chunk_index",
+    )
+    .bind(database_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(chunks)
+}
+
+/// IDs of the chunks of a file, used to remove them from a remote vector store
+pub async fn list_rag_file_chunk_ids(database_id: Uuid, file_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let chunk_ids: Vec<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM rag_chunks WHERE rag_database_id = $1 AND file_id = $2")
+            .bind(database_id)
+            .bind(file_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(chunk_ids.into_iter().map(|(id,)| id).collect())
+}
+
+pub async fn get_rag_chunks_by_ids(chunk_ids: &[Uuid]) -> Result<Vec<RAGChunk>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let chunks: Vec<RAGChunk> = sqlx::query_as(
+        "SELECT id, rag_database_id, file_id, chunk_index, content, metadata, embedding, created_at
+         FROM rag_chunks
+         WHERE id = ANY($1)",
+    )
+    .bind(chunk_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(chunks)
+}
+
+/// IDs of a user's files belonging to a project
+pub async fn get_project_file_ids(user_id: Uuid, project_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let file_ids: Vec<(Uuid,)> = sqlx::query_as("SELECT id FROM files WHERE project_id = $1 AND user_id = $2")
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(file_ids.into_iter().map(|(id,)| id).collect())
+}
+
+/// The subset of `file_ids` owned by a user
+pub async fn get_user_file_ids(user_id: Uuid, file_ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let owned: Vec<(Uuid,)> = sqlx::query_as("SELECT id FROM files WHERE id = ANY($1) AND user_id = $2")
+        .bind(file_ids)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(owned.into_iter().map(|(id,)| id).collect())
+}
@@ -281,6 +281,24 @@ pub async fn delete_rag_database(database_id: Uuid) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+/// Databases of local providers marked active, whose index should be loaded in memory
+pub async fn list_active_local_rag_databases() -> Result<Vec<RAGDatabase>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let databases: Vec<RAGDatabase> = sqlx::query_as(
+        "SELECT d.id, d.provider_id, d.name, d.alias, d.description, d.enabled, d.is_active, d.collection_name, 
+                d.embedding_model, d.chunk_size, d.chunk_overlap, d.capabilities, d.settings, d.created_at, d.updated_at
+         FROM rag_databases d
+         INNER JOIN rag_providers p ON p.id = d.provider_id
+         WHERE d.is_active = true AND p.provider_type = 'local'"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(databases)
+}
+
 pub async fn set_rag_database_active(database_id: Uuid, is_active: bool) -> Result<RAGDatabase, sqlx::Error> {
     let pool = get_database_pool()?;
     let pool = pool.as_ref();
@@ -0,0 +1,285 @@
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::database::{
+    get_database_pool,
+    models::{
+        NewSemanticChunk, PendingSemanticSource, SemanticSearchHit, MESSAGE_TYPE_TEXT, SEMANTIC_INDEX_METADATA_KEY,
+    },
+};
+
+/// Text messages with no chunks for `model_id`, or whose chunks predate their last update.
+/// Messages in `excluded` are skipped
+pub async fn list_unindexed_messages(
+    model_id: Uuid,
+    excluded: &[Uuid],
+    limit: i64,
+) -> Result<Vec<PendingSemanticSource>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let rows = sqlx::query(
+        "SELECT m.id, c.user_id, m.content
+         FROM messages m
+         INNER JOIN conversations c ON c.id = m.conversation_id
+         WHERE m.message_type = $2
+           AND m.role IN ('user', 'assistant')
+           AND btrim(m.content) <> ''
+           AND NOT EXISTS (
+               SELECT 1 FROM semantic_chunks sc
+               WHERE sc.message_id = m.id
+                 AND sc.embedding_model_id = $1
+                 AND sc.created_at >= COALESCE(m.updated_at, m.created_at)
+           )
+           AND m.id <> ALL($4)
+         ORDER BY m.created_at DESC
+         LIMIT $3",
+    )
+    .bind(model_id)
+    .bind(MESSAGE_TYPE_TEXT)
+    .bind(limit)
+    .bind(excluded)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(PendingSemanticSource {
+                id: row.try_get("id")?,
+                user_id: row.try_get("user_id")?,
+                content: row.try_get("content")?,
+            })
+        })
+        .collect()
+}
+
+/// Files whose extracted text has not been indexed with `model_id`, per `processing_metadata`.
+/// Files in `excluded` are skipped
+pub async fn list_unindexed_files(
+    model_id: Uuid,
+    excluded: &[Uuid],
+    limit: i64,
+) -> Result<Vec<PendingSemanticSource>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let rows = sqlx::query(
+        "SELECT id, user_id
+         FROM files
+         WHERE COALESCE(processing_metadata -> $2 ->> 'embedding_model_id', '') <> $1::text
+           AND id <> ALL($4)
+         ORDER BY created_at DESC
+         LIMIT $3",
+    )
+    .bind(model_id)
+    .bind(SEMANTIC_INDEX_METADATA_KEY)
+    .bind(limit)
+    .bind(excluded)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(PendingSemanticSource {
+                id: row.try_get("id")?,
+                user_id: row.try_get("user_id")?,
+                content: None,
+            })
+        })
+        .collect()
+}
+
+/// Replace the chunks of a message embedded with `model_id`
+pub async fn replace_message_chunks(
+    message_id: Uuid,
+    user_id: Uuid,
+    model_id: Uuid,
+    chunks: Vec<NewSemanticChunk>,
+) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM semantic_chunks WHERE message_id = $1 AND embedding_model_id = $2")
+        .bind(message_id)
+        .bind(model_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for chunk in chunks {
+        sqlx::query(
+            "INSERT INTO semantic_chunks (user_id, message_id, embedding_model_id, chunk_index, content, embedding)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(user_id)
+        .bind(message_id)
+        .bind(model_id)
+        .bind(chunk.chunk_index)
+        .bind(&chunk.content)
+        .bind(&chunk.embedding)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Replace the chunks of a file embedded with `model_id` and record the indexing in its
+/// `processing_metadata`, so files without text are not picked up again
+pub async fn replace_file_chunks(
+    file_id: Uuid,
+    user_id: Uuid,
+    model_id: Uuid,
+    chunks: Vec<NewSemanticChunk>,
+) -> Result<(), sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM semantic_chunks WHERE file_id = $1 AND embedding_model_id = $2")
+        .bind(file_id)
+        .bind(model_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let chunk_count = chunks.len() as i32;
+    for chunk in chunks {
+        sqlx::query(
+            "INSERT INTO semantic_chunks (user_id, file_id, embedding_model_id, chunk_index, content, embedding)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(user_id)
+        .bind(file_id)
+        .bind(model_id)
+        .bind(chunk.chunk_index)
+        .bind(&chunk.content)
+        .bind(&chunk.embedding)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE files
+         SET processing_metadata = COALESCE(processing_metadata, '{}'::jsonb) || jsonb_build_object(
+             $2::text,
+             jsonb_build_object('embedding_model_id', $3::uuid, 'chunk_count', $4::int, 'indexed_at', NOW())
+         )
+         WHERE id = $1",
+    )
+    .bind(file_id)
+    .bind(SEMANTIC_INDEX_METADATA_KEY)
+    .bind(model_id)
+    .bind(chunk_count)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Remove the chunks of every model other than `model_id`, all chunks when None, and the
+/// indexing records of files that pointed to those models so they are indexed again later
+pub async fn delete_stale_chunks(model_id: Option<Uuid>) -> Result<u64, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+    let mut tx = pool.begin().await?;
+
+    let deleted = sqlx::query("DELETE FROM semantic_chunks WHERE embedding_model_id IS DISTINCT FROM $1")
+        .bind(model_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    sqlx::query(
+        "UPDATE files
+         SET processing_metadata = processing_metadata - $2
+         WHERE processing_metadata ? $2
+           AND (processing_metadata -> $2 ->> 'embedding_model_id') IS DISTINCT FROM $1::text",
+    )
+    .bind(model_id)
+    .bind(SEMANTIC_INDEX_METADATA_KEY)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(deleted)
+}
+
+/// Load the ids and vectors of a user's chunks (used to build the in-memory index for a search)
+pub async fn list_user_vectors(user_id: Uuid, model_id: Uuid) -> Result<Vec<(Uuid, Vec<f32>)>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let vectors: Vec<(Uuid, Vec<f32>)> = sqlx::query_as(
+        "SELECT id, embedding
+         FROM semantic_chunks
+         WHERE user_id = $1 AND embedding_model_id = $2",
+    )
+    .bind(user_id)
+    .bind(model_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(vectors)
+}
+
+/// Resolve chunks to the messages and files they came from. Chunks are re-checked against the
+/// owner of the conversation or file so another user's chunk can never be returned.
+pub async fn get_semantic_hits(user_id: Uuid, chunk_ids: &[Uuid]) -> Result<Vec<SemanticSearchHit>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let hits = sqlx::query_as::<_, SemanticSearchHit>(
+        r#"
+        SELECT sc.id AS chunk_id, sc.content,
+            c.id AS conversation_id, c.title AS conversation_title,
+            m.id AS message_id, m.role,
+            (
+                SELECT bm.branch_id FROM branch_messages bm
+                WHERE bm.message_id = m.id
+                ORDER BY bm.branch_id = c.active_branch_id DESC, bm.is_clone, bm.created_at
+                LIMIT 1
+            ) AS branch_id,
+            NULL::uuid AS file_id, NULL::varchar AS filename
+        FROM semantic_chunks sc
+        INNER JOIN messages m ON m.id = sc.message_id
+        INNER JOIN conversations c ON c.id = m.conversation_id
+        WHERE sc.id = ANY($2) AND c.user_id = $1
+        UNION ALL
+        SELECT sc.id, sc.content,
+            c.id, c.title,
+            m.id, m.role,
+            (
+                SELECT bm.branch_id FROM branch_messages bm
+                WHERE bm.message_id = m.id
+                ORDER BY bm.branch_id = c.active_branch_id DESC, bm.is_clone, bm.created_at
+                LIMIT 1
+            ),
+            f.id, f.filename
+        FROM semantic_chunks sc
+        INNER JOIN files f ON f.id = sc.file_id
+        -- Earliest message of the user's that the file was attached to, if any
+        LEFT JOIN LATERAL (
+            SELECT msg.id, msg.role, msg.conversation_id
+            FROM messages_files mf
+            INNER JOIN messages msg ON msg.id = mf.message_id
+            INNER JOIN conversations conv ON conv.id = msg.conversation_id
+            WHERE mf.file_id = f.id AND conv.user_id = $1
+            ORDER BY msg.created_at
+            LIMIT 1
+        ) m ON true
+        LEFT JOIN conversations c ON c.id = m.conversation_id
+        WHERE sc.id = ANY($2) AND f.user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(chunk_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(hits)
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::database::{
+    get_database_pool,
+    models::{RecordTokenUsageRequest, TokenUsage, UsageAggregate, UsageGroupBy, UsageTotals},
+};
+
+const TOTALS_COLUMNS: &str = "COUNT(t.id) AS requests,
+    COALESCE(SUM(t.prompt_tokens), 0)::BIGINT AS prompt_tokens,
+    COALESCE(SUM(t.completion_tokens), 0)::BIGINT AS completion_tokens,
+    COALESCE(SUM(t.total_tokens), 0)::BIGINT AS total_tokens";
+
+pub async fn record_token_usage(request: RecordTokenUsageRequest) -> Result<TokenUsage, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let usage: TokenUsage = sqlx::query_as(
+        "INSERT INTO token_usage (user_id, message_id, provider_id, model_id, source,
+                                  prompt_tokens, completion_tokens, total_tokens, is_estimated)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING *",
+    )
+    .bind(request.user_id)
+    .bind(request.message_id)
+    .bind(request.provider_id)
+    .bind(request.model_id)
+    .bind(request.source)
+    .bind(request.prompt_tokens)
+    .bind(request.completion_tokens)
+    .bind(request.prompt_tokens + request.completion_tokens)
+    .bind(request.is_estimated)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(usage)
+}
+
+/// Usage of one user since a point in time
+pub async fn get_user_usage_totals(
+    user_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<UsageTotals, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let totals: UsageTotals = sqlx::query_as(&format!(
+        "SELECT {} FROM token_usage t WHERE t.user_id = $1 AND t.created_at >= $2",
+        TOTALS_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(totals)
+}
+
+/// Usage of the current members of a group since a point in time
+pub async fn get_group_usage_totals(
+    group_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<UsageTotals, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    let totals: UsageTotals = sqlx::query_as(&format!(
+        "SELECT {} FROM token_usage t
+         JOIN user_group_memberships ugm ON ugm.user_id = t.user_id
+         WHERE ugm.group_id = $1 AND t.created_at >= $2",
+        TOTALS_COLUMNS
+    ))
+    .bind(group_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(totals)
+}
+
+/// Usage between two points in time, totalled per user, group, provider or model
+pub async fn aggregate_usage(
+    group_by: UsageGroupBy,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<UsageAggregate>, sqlx::Error> {
+    let pool = get_database_pool()?;
+    let pool = pool.as_ref();
+
+    // Usage of a user counts towards every group they belong to
+    let (key_columns, joins, group_columns) = match group_by {
+        UsageGroupBy::User => (
+            "u.id AS id, u.username AS name",
+            "JOIN users u ON u.id = t.user_id",
+            "u.id, u.username",
+        ),
+        UsageGroupBy::Group => (
+            "ug.id AS id, ug.name AS name",
+            "JOIN user_group_memberships ugm ON ugm.user_id = t.user_id
+             JOIN user_groups ug ON ug.id = ugm.group_id",
+            "ug.id, ug.name",
+        ),
+        UsageGroupBy::Provider => (
+            "t.provider_id AS id, p.name AS name",
+            "LEFT JOIN providers p ON p.id = t.provider_id",
+            "t.provider_id, p.name",
+        ),
+        UsageGroupBy::Model => (
+            "t.model_id AS id, m.name AS name",
+            "LEFT JOIN models m ON m.id = t.model_id",
+            "t.model_id, m.name",
+        ),
+    };
+
+    let aggregates: Vec<UsageAggregate> = sqlx::query_as(&format!(
+        "SELECT {}, {} FROM token_usage t {}
+         WHERE t.created_at >= $1 AND t.created_at < $2
+         GROUP BY {}
+         ORDER BY total_tokens DESC",
+        key_columns, TOTALS_COLUMNS, joins, group_columns
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(aggregates)
+}
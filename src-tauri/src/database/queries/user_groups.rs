@@ -9,19 +9,21 @@ pub async fn create_user_group(
     name: String,
     description: Option<String>,
     permissions: serde_json::Value,
+    quotas: Option<UserGroupQuotas>,
 ) -> Result<UserGroup, sqlx::Error> {
     let pool = get_database_pool()?;
 
     let mut group = sqlx::query_as::<_, UserGroup>(
         r#"
-        INSERT INTO user_groups (name, description, permissions)
-        VALUES ($1, $2, $3)
-        RETURNING id, name, description, permissions, is_protected, is_active, created_at, updated_at
+        INSERT INTO user_groups (name, description, permissions, quotas)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, name, description, permissions, quotas, is_protected, is_active, created_at, updated_at
         "#,
     )
     .bind(&name)
     .bind(&description)
     .bind(&permissions)
+    .bind(quotas.map(|quotas| serde_json::json!(quotas)))
     .fetch_one(&*pool)
     .await?;
 
@@ -94,6 +96,7 @@ pub async fn update_user_group(
     description: Option<String>,
     permissions: Option<serde_json::Value>,
     is_active: Option<bool>,
+    quotas: Option<UserGroupQuotas>,
 ) -> Result<Option<UserGroup>, sqlx::Error> {
     let pool = get_database_pool()?;
 
@@ -101,7 +104,7 @@ pub async fn update_user_group(
     let existing_group = get_user_group_by_id(group_id).await?;
     if let Some(group) = &existing_group {
         if group.is_protected {
-            // For protected groups, only allow editing description
+            // For protected groups, only allow editing description and quotas
             // Name, permissions, and is_active cannot be changed
             if name.is_some() || permissions.is_some() || is_active.is_some() {
                 return Err(sqlx::Error::RowNotFound);
@@ -133,6 +136,11 @@ pub async fn update_user_group(
         param_index += 1;
     }
 
+    if quotas.is_some() {
+        updates.push(format!(" quotas = ${}", param_index));
+        param_index += 1;
+    }
+
     // If no updates are provided, return the existing group
     if updates.is_empty() {
         return get_user_group_by_id(group_id).await;
@@ -158,6 +166,10 @@ pub async fn update_user_group(
     if let Some(is_active) = is_active {
         sql_query = sql_query.bind(is_active);
     }
+    if let Some(quotas) = quotas {
+        // Clearing every limit removes the quotas
+        sql_query = sql_query.bind((!quotas.is_empty()).then(|| serde_json::json!(quotas)));
+    }
 
     sql_query = sql_query.bind(group_id);
 
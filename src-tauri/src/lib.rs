@@ -4,6 +4,7 @@ mod auth;
 mod database;
 mod env;
 mod processing;
+mod rag;
 mod route;
 mod utils;
 
@@ -105,6 +106,15 @@ async fn initialize_app_common() -> Result<(), String> {
         }
     }
 
+    // Reload the indexes of local RAG databases that were running
+    rag::manager::restore_active_rag_databases().await;
+
+    // Reap dead model processes and stale RAG indexes
+    ai::model_manager::start_process_cleanup_task();
+
+    // Embed new messages and files for semantic search
+    rag::start_semantic_index_task();
+
     Ok(())
 }
 
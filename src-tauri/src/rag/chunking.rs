@@ -0,0 +1,128 @@
+/// A piece of source text with its character offsets in the original document
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub content: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Split text into chunks of at most `chunk_size` characters, with consecutive chunks
+/// sharing about `chunk_overlap` characters.
+///
+/// Chunks end on the most natural boundary found in the second half of the window:
+/// a paragraph break, then a line or sentence end, then any whitespace.
+pub fn chunk_text(text: &str, chunk_size: usize, chunk_overlap: usize) -> Vec<TextChunk> {
+    let chars: Vec<char> = text.chars().collect();
+    let total = chars.len();
+    let chunk_size = chunk_size.max(1);
+    // An overlap as large as the chunk would never make progress
+    let chunk_overlap = chunk_overlap.min(chunk_size / 2);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < total {
+        let mut end = (start + chunk_size).min(total);
+        if end < total {
+            end = find_break(&chars, start + chunk_size / 2, end).unwrap_or(end);
+        }
+
+        let content: String = chars[start..end].iter().collect();
+        let trimmed = content.trim();
+        if !trimmed.is_empty() {
+            let leading = content.chars().take_while(|c| c.is_whitespace()).count();
+            chunks.push(TextChunk {
+                content: trimmed.to_string(),
+                start: start + leading,
+                end: start + leading + trimmed.chars().count(),
+            });
+        }
+
+        if end >= total {
+            break;
+        }
+
+        // Step back by the overlap, then forward to a word boundary so chunks do not start mid-word
+        let mut next = end.saturating_sub(chunk_overlap).max(start + 1);
+        while next < end && next > 0 && !chars[next - 1].is_whitespace() {
+            next += 1;
+        }
+        start = next;
+    }
+
+    chunks
+}
+
+/// Find the best position in `chars[min..max]` to end a chunk (exclusive end index)
+fn find_break(chars: &[char], min: usize, max: usize) -> Option<usize> {
+    let window = min..max;
+
+    // Paragraph break
+    for i in window.clone().rev() {
+        if chars[i] == '\n' && i > 0 && chars[i - 1] == '\n' {
+            return Some(i + 1);
+        }
+    }
+
+    // Line break or sentence end
+    for i in window.clone().rev() {
+        if chars[i] == '\n' {
+            return Some(i + 1);
+        }
+        if matches!(chars[i], '.' | '!' | '?') && chars.get(i + 1).is_some_and(|c| c.is_whitespace()) {
+            return Some(i + 1);
+        }
+    }
+
+    // Any whitespace
+    window.rev().find(|&i| chars[i].is_whitespace()).map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_single_chunk() {
+        let chunks = chunk_text("  Hello world.  ", 100, 10);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "Hello world.");
+        assert_eq!(chunks[0].start, 2);
+        assert_eq!(chunks[0].end, 14);
+    }
+
+    #[test]
+    fn test_chunks_respect_size_and_overlap() {
+        let text = "one two three four five six seven eight nine ten eleven twelve";
+        let chunks = chunk_text(text, 20, 8);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.content.chars().count() <= 20);
+            // Offsets point back into the source text
+            let source: String = text.chars().skip(chunk.start).take(chunk.end - chunk.start).collect();
+            assert_eq!(source, chunk.content);
+        }
+        // Consecutive chunks overlap
+        for pair in chunks.windows(2) {
+            assert!(pair[1].start < pair[0].end);
+        }
+        assert!(chunks.last().unwrap().content.ends_with("twelve"));
+    }
+
+    #[test]
+    fn test_prefers_paragraph_boundaries() {
+        let text = "First paragraph here.\n\nSecond paragraph follows.";
+        let chunks = chunk_text(text, 30, 0);
+        assert_eq!(chunks[0].content, "First paragraph here.");
+        assert_eq!(chunks[1].content, "Second paragraph follows.");
+    }
+
+    #[test]
+    fn test_handles_multibyte_text_and_large_overlap() {
+        let text = "日本語のテキストを分割します。".repeat(10);
+        let chunks = chunk_text(&text, 16, 100);
+        assert!(!chunks.is_empty());
+        assert!(chunks.iter().all(|c| c.content.chars().count() <= 16));
+    }
+}
@@ -0,0 +1,392 @@
+use serde_json::json;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use super::package::{RAGPackageChunk, RAGPackageReader, RAG_PACKAGE_EXTENSION};
+use crate::database::{
+    models::{
+        CreateDownloadInstanceRequest, CreateRAGDatabaseRequest, DownloadInstance,
+        DownloadProgressData, DownloadRAGDatabaseFromRepositoryRequest, DownloadRequestData,
+        DownloadStatus, NewRAGChunk, RAGDatabase, RAGRepository, UpdateDownloadProgressRequest,
+        UpdateDownloadStatusRequest, DOWNLOAD_TYPE_RAG_DATABASE,
+    },
+    queries::{configuration::get_proxy_settings, download_instances, rag_chunks, rag_providers},
+};
+use crate::ai::core::provider_base::build_http_client;
+use crate::utils::cancellation::{create_cancellation_token, remove_download_tracking, CancellationToken};
+
+// Number of package chunks read and inserted per batch during import
+const IMPORT_BATCH_SIZE: usize = 500;
+// Minimum interval between progress updates written to the database
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+enum PackageDownloadError {
+    Cancelled,
+    Failed(BoxError),
+}
+
+impl<E: Into<BoxError>> From<E> for PackageDownloadError {
+    fn from(error: E) -> Self {
+        PackageDownloadError::Failed(error.into())
+    }
+}
+
+/// Whether `database_id` is a package path inside a repository. Absolute URLs and `.`/`..`
+/// segments are refused, so downloads, and the repository token sent with them, only ever
+/// go to the repository host.
+pub fn is_valid_database_id(database_id: &str) -> bool {
+    let path = database_id.trim_matches('/');
+    !path.is_empty()
+        && !path.contains("://")
+        && !path.contains('\\')
+        && !path.split('/').any(|segment| segment == "." || segment == "..")
+}
+
+/// URL of a package in a RAG repository
+pub fn package_url(repository: &RAGRepository, database_id: &str) -> String {
+    format!(
+        "{}/{}.{}",
+        repository.url.trim_end_matches('/'),
+        database_id.trim_matches('/'),
+        RAG_PACKAGE_EXTENSION
+    )
+}
+
+/// Create a download instance for a RAG database package and download and import it in the
+/// background. Progress and cancellation go through the same machinery as model downloads.
+pub async fn start_rag_database_download(
+    repository: RAGRepository,
+    request: DownloadRAGDatabaseFromRepositoryRequest,
+) -> Result<DownloadInstance, BoxError> {
+    if !is_valid_database_id(&request.database_id) {
+        return Err(format!("Invalid RAG database id: {}", request.database_id).into());
+    }
+    let url = package_url(&repository, &request.database_id);
+
+    let download_instance = download_instances::create_download_instance(CreateDownloadInstanceRequest {
+        provider_id: request.target_provider_id,
+        repository_id: repository.id,
+        request_data: DownloadRequestData {
+            model_name: request.database_id.clone(),
+            revision: None,
+            files: None,
+            quantization: None,
+            repository_path: Some(url.clone()),
+            alias: request.database_alias.clone(),
+            description: None,
+            file_format: Some(RAG_PACKAGE_EXTENSION.to_string()),
+            main_filename: None,
+            capabilities: None,
+            parameters: None,
+            settings: None,
+        },
+        download_type: Some(DOWNLOAD_TYPE_RAG_DATABASE.to_string()),
+    })
+    .await?;
+
+    let download_id = download_instance.id;
+    let cancellation_token = create_cancellation_token(download_id).await;
+
+    tokio::spawn(async move {
+        let _ = download_instances::update_download_status(
+            download_id,
+            UpdateDownloadStatusRequest {
+                status: DownloadStatus::Downloading,
+                error_message: None,
+                model_id: None,
+            },
+        )
+        .await;
+
+        let package_path = crate::get_app_data_dir()
+            .join("downloads/rag")
+            .join(format!("{}.{}", download_id, RAG_PACKAGE_EXTENSION));
+
+        let result = match download_package(download_id, &url, &repository, &package_path, &cancellation_token).await {
+            Ok(()) => import_package(download_id, &package_path, &request, &cancellation_token).await,
+            Err(e) => Err(e),
+        };
+
+        let _ = tokio::fs::remove_file(&package_path).await;
+        remove_download_tracking(download_id).await;
+
+        match result {
+            Ok(database) => {
+                println!("Imported RAG database {} ({}) from {}", database.name, database.id, url);
+                let _ = download_instances::set_download_rag_database(download_id, database.id).await;
+                let _ = download_instances::update_download_status(
+                    download_id,
+                    UpdateDownloadStatusRequest {
+                        status: DownloadStatus::Completed,
+                        error_message: None,
+                        model_id: None,
+                    },
+                )
+                .await;
+            }
+            Err(PackageDownloadError::Cancelled) => {
+                // The cancel endpoint already marked the download as cancelled
+                println!("RAG database download {} cancelled", download_id);
+            }
+            Err(PackageDownloadError::Failed(e)) => {
+                eprintln!("RAG database download {} failed: {}", download_id, e);
+                let _ = download_instances::update_download_status(
+                    download_id,
+                    UpdateDownloadStatusRequest {
+                        status: DownloadStatus::Failed,
+                        error_message: Some(e.to_string()),
+                        model_id: None,
+                    },
+                )
+                .await;
+            }
+        }
+    });
+
+    Ok(download_instance)
+}
+
+async fn report_progress(download_id: Uuid, progress_data: DownloadProgressData) {
+    let _ = download_instances::update_download_progress(
+        download_id,
+        UpdateDownloadProgressRequest {
+            progress_data,
+            status: None,
+        },
+    )
+    .await;
+}
+
+/// Stream the package to a temporary file, reporting byte progress
+async fn download_package(
+    download_id: Uuid,
+    url: &str,
+    repository: &RAGRepository,
+    package_path: &Path,
+    cancellation_token: &CancellationToken,
+) -> Result<(), PackageDownloadError> {
+    report_progress(
+        download_id,
+        DownloadProgressData {
+            phase: Some("Connecting".to_string()),
+            message: Some(format!("Connecting to {}", repository.name)),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    // Repositories have no proxy settings of their own, so the system-wide proxy applies
+    let proxy_config = get_proxy_settings()
+        .await
+        .ok()
+        .as_ref()
+        .and_then(crate::api::chat::create_proxy_config);
+    let client = build_http_client(url, proxy_config.as_ref())?;
+
+    let mut request = client.get(url);
+    if repository.requires_auth {
+        if let Some(token) = repository.auth_token.as_deref().filter(|token| !token.is_empty()) {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+    }
+
+    let mut response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(format!("Repository returned {} for {}", response.status(), url).into());
+    }
+    let total_bytes = response.content_length();
+
+    if let Some(parent) = package_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::File::create(package_path).await?;
+
+    let started = std::time::Instant::now();
+    let mut last_report = started;
+    let mut downloaded: u64 = 0;
+
+    while let Some(bytes) = response.chunk().await? {
+        if cancellation_token.is_cancelled().await {
+            return Err(PackageDownloadError::Cancelled);
+        }
+
+        file.write_all(&bytes).await?;
+        downloaded += bytes.len() as u64;
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = std::time::Instant::now();
+            let elapsed = started.elapsed().as_secs_f64();
+            let speed_bps = if elapsed > 0.0 { Some((downloaded as f64 / elapsed) as i64) } else { None };
+            let eta_seconds = match (total_bytes, speed_bps) {
+                (Some(total), Some(speed)) if speed > 0 && total > downloaded => {
+                    Some(((total - downloaded) / speed as u64) as i64)
+                }
+                _ => None,
+            };
+
+            report_progress(
+                download_id,
+                DownloadProgressData {
+                    phase: Some("Downloading".to_string()),
+                    current: Some(downloaded as i64),
+                    total: total_bytes.map(|total| total as i64),
+                    message: Some("Downloading RAG database package...".to_string()),
+                    speed_bps,
+                    eta_seconds,
+                },
+            )
+            .await;
+        }
+    }
+
+    file.flush().await?;
+    Ok(())
+}
+
+/// Create the target RAG database and copy the package chunks into it.
+/// The database is removed again if the import fails or is cancelled.
+async fn import_package(
+    download_id: Uuid,
+    package_path: &Path,
+    request: &DownloadRAGDatabaseFromRepositoryRequest,
+    cancellation_token: &CancellationToken,
+) -> Result<RAGDatabase, PackageDownloadError> {
+    let path = package_path.to_path_buf();
+    let reader = tokio::task::spawn_blocking(move || -> Result<_, BoxError> {
+        RAGPackageReader::new(std::fs::File::open(path)?)
+    })
+    .await??;
+    let manifest = reader.manifest().clone();
+
+    let mut settings = request.settings.clone().unwrap_or_else(|| json!({}));
+    if let Some(settings) = settings.as_object_mut() {
+        settings.insert(
+            "package".to_string(),
+            json!({
+                "source_database_id": request.database_id,
+                "repository_id": request.repository_id,
+                "format_version": manifest.format_version,
+                "embedding_model": manifest.embedding_model,
+                "embedding_dimension": manifest.embedding_dimension,
+            }),
+        );
+    }
+
+    let database = rag_providers::create_rag_database(
+        request.target_provider_id,
+        CreateRAGDatabaseRequest {
+            name: request.database_name.clone().unwrap_or_else(|| manifest.name.clone()),
+            alias: request
+                .database_alias
+                .clone()
+                .or_else(|| manifest.alias.clone())
+                .unwrap_or_else(|| request.database_id.clone()),
+            description: manifest.description.clone(),
+            enabled: Some(true),
+            collection_name: None,
+            // Queries must be embedded with the model that produced the package vectors
+            embedding_model: Some(
+                request
+                    .embedding_model
+                    .clone()
+                    .unwrap_or_else(|| manifest.embedding_model.clone()),
+            ),
+            chunk_size: Some(manifest.chunk_size),
+            chunk_overlap: Some(manifest.chunk_overlap),
+            capabilities: None,
+            settings: Some(settings),
+        },
+    )
+    .await?;
+
+    match import_chunks(download_id, database.id, reader, manifest.chunk_count, cancellation_token).await {
+        Ok(()) => Ok(database),
+        Err(e) => {
+            if let Err(delete_error) = rag_providers::delete_rag_database(database.id).await {
+                eprintln!("Failed to remove partially imported RAG database {}: {}", database.id, delete_error);
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn import_chunks(
+    download_id: Uuid,
+    database_id: Uuid,
+    mut reader: RAGPackageReader<std::fs::File>,
+    chunk_count: usize,
+    cancellation_token: &CancellationToken,
+) -> Result<(), PackageDownloadError> {
+    let mut imported = 0usize;
+
+    loop {
+        if cancellation_token.is_cancelled().await {
+            return Err(PackageDownloadError::Cancelled);
+        }
+
+        // Decompression and parsing are blocking work
+        let (returned_reader, batch) = tokio::task::spawn_blocking(move || {
+            let batch = reader.next_batch(IMPORT_BATCH_SIZE);
+            (reader, batch)
+        })
+        .await?;
+        reader = returned_reader;
+
+        let batch = batch?;
+        if batch.is_empty() {
+            break;
+        }
+
+        imported += batch.len();
+        let new_chunks = batch.into_iter().map(package_chunk_to_new_chunk).collect();
+        rag_chunks::replace_rag_file_chunks(database_id, None, new_chunks).await?;
+
+        report_progress(
+            download_id,
+            DownloadProgressData {
+                phase: Some("Importing".to_string()),
+                current: Some(imported as i64),
+                total: Some(chunk_count.max(imported) as i64),
+                message: Some(format!("Imported {} chunks", imported)),
+                speed_bps: None,
+                eta_seconds: None,
+            },
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+fn package_chunk_to_new_chunk(chunk: RAGPackageChunk) -> NewRAGChunk {
+    let mut metadata = if chunk.metadata.is_object() { chunk.metadata } else { json!({}) };
+    if let (Some(source), Some(object)) = (chunk.source, metadata.as_object_mut()) {
+        // Citations show the filename of a chunk
+        object.entry("filename").or_insert(json!(source));
+    }
+
+    NewRAGChunk {
+        chunk_index: chunk.chunk_index,
+        content: chunk.content,
+        metadata,
+        embedding: chunk.embedding,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_id_must_stay_inside_repository() {
+        assert!(is_valid_database_id("team/handbook"));
+        assert!(is_valid_database_id("/handbook/"));
+        assert!(!is_valid_database_id(""));
+        assert!(!is_valid_database_id("https://attacker.example/package"));
+        assert!(!is_valid_database_id("handbook/../../admin"));
+        assert!(!is_valid_database_id("..\\handbook"));
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::ai::AIProvider;
+use crate::api::chat::create_ai_provider_with_model_id;
+use crate::database::{
+    models::{Provider, RAGDatabase},
+    queries::{
+        models::{get_model_by_id, get_provider_by_model_id},
+        providers::get_provider_by_id,
+    },
+};
+
+/// Computes embedding vectors for text
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embed a batch of texts, returning one vector per input in the same order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Name of the embedding model
+    fn model_name(&self) -> &str;
+}
+
+/// Embedding provider backed by the `embed` capability of a configured AI provider
+pub struct ProviderEmbeddingModel {
+    provider: Box<dyn AIProvider>,
+    model: String,
+}
+
+#[async_trait]
+impl EmbeddingProvider for ProviderEmbeddingModel {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.provider.embed(&self.model, texts).await
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+}
+
+/// Create the embedding provider configured for a RAG database.
+///
+/// `embedding_model` is either the id of a configured model, or a model name served by the
+/// provider whose id is stored in the database settings as `embedding_provider_id`.
+pub async fn create_embedding_provider(
+    database: &RAGDatabase,
+) -> Result<Box<dyn EmbeddingProvider>, Box<dyn std::error::Error + Send + Sync>> {
+    let embedding_model = database
+        .embedding_model
+        .as_deref()
+        .map(str::trim)
+        .filter(|model| !model.is_empty())
+        .ok_or("RAG database has no embedding model configured")?;
+
+    let (provider, model_name, model_id) = if let Ok(model_id) = Uuid::parse_str(embedding_model) {
+        let model = get_model_by_id(model_id)
+            .await?
+            .ok_or("Embedding model not found")?;
+        let provider = get_provider_by_model_id(model_id)
+            .await?
+            .ok_or("Embedding model provider not found")?;
+        (provider, model.name, Some(model_id))
+    } else {
+        let provider_id = database
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.get("embedding_provider_id"))
+            .and_then(|value| value.as_str())
+            .and_then(|value| Uuid::parse_str(value).ok())
+            .ok_or("RAG database settings have no embedding_provider_id for the embedding model")?;
+        let provider = get_provider_by_id(provider_id)
+            .await?
+            .ok_or("Embedding provider not found")?;
+        (provider, embedding_model.to_string(), None)
+    };
+
+    create_provider_embedding_provider(&provider, model_name, model_id).await
+}
+
+/// Create an embedding provider for a model served by a configured provider.
+/// `model_id` is required for local providers, to find the port of the running model.
+pub async fn create_provider_embedding_provider(
+    provider: &Provider,
+    model_name: String,
+    model_id: Option<Uuid>,
+) -> Result<Box<dyn EmbeddingProvider>, Box<dyn std::error::Error + Send + Sync>> {
+    if !provider.enabled {
+        return Err(format!("Embedding provider {} is disabled", provider.name).into());
+    }
+
+    let provider = create_ai_provider_with_model_id(provider, model_id).await?;
+    Ok(Box::new(ProviderEmbeddingModel {
+        provider,
+        model: model_name,
+    }))
+}
@@ -0,0 +1,51 @@
+use super::package::{write_package, RAGPackageChunk, RAGPackageManifest, RAG_PACKAGE_FORMAT_VERSION};
+use crate::database::{models::RAGDatabase, queries::rag_chunks::list_rag_chunks};
+
+/// Build a package from the indexed chunks of a RAG database so it can be published to a
+/// RAG repository and imported elsewhere without re-embedding the documents
+pub async fn export_rag_database_package(
+    database: &RAGDatabase,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let embedding_model = database
+        .embedding_model
+        .clone()
+        .filter(|model| !model.trim().is_empty())
+        .ok_or("RAG database has no embedding model configured")?;
+
+    let chunks = list_rag_chunks(database.id).await?;
+    let embedding_dimension = chunks
+        .first()
+        .map(|chunk| chunk.embedding.len())
+        .ok_or("RAG database has no indexed chunks to export")?;
+
+    let manifest = RAGPackageManifest {
+        format_version: RAG_PACKAGE_FORMAT_VERSION,
+        name: database.name.clone(),
+        alias: Some(database.alias.clone()),
+        description: database.description.clone(),
+        embedding_model,
+        embedding_dimension,
+        chunk_size: database.chunk_size,
+        chunk_overlap: database.chunk_overlap,
+        chunk_count: chunks.len(),
+    };
+
+    let package_chunks: Vec<RAGPackageChunk> = chunks
+        .into_iter()
+        .map(|chunk| RAGPackageChunk {
+            source: chunk
+                .metadata
+                .get("filename")
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+            chunk_index: chunk.chunk_index,
+            content: chunk.content,
+            metadata: chunk.metadata,
+            embedding: chunk.embedding,
+        })
+        .collect();
+
+    // Compression is CPU bound
+    let bytes = tokio::task::spawn_blocking(move || write_package(Vec::new(), &manifest, package_chunks)).await??;
+    Ok(bytes)
+}
@@ -0,0 +1,225 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::database::{models::RAGChunk, queries::rag_chunks};
+
+/// In-memory vector index for a local RAG database.
+///
+/// Vectors are normalized on insert so cosine similarity reduces to a dot product.
+/// Search is an exact scan, which is fast enough for the document collections a
+/// desktop install holds and keeps the index free of native dependencies.
+#[derive(Debug, Default)]
+pub struct LocalVectorIndex {
+    entries: Vec<IndexEntry>,
+    dimension: Option<usize>,
+}
+
+#[derive(Debug)]
+struct IndexEntry {
+    chunk_id: Uuid,
+    file_id: Option<Uuid>,
+    vector: Vec<f32>,
+}
+
+impl LocalVectorIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    /// Add a vector. All vectors in an index must share the same dimension
+    pub fn add(&mut self, chunk_id: Uuid, file_id: Option<Uuid>, vector: &[f32]) -> Result<(), String> {
+        if vector.is_empty() {
+            return Err("Cannot index an empty vector".to_string());
+        }
+        match self.dimension {
+            Some(dimension) if dimension != vector.len() => {
+                return Err(format!(
+                    "Vector dimension {} does not match index dimension {}",
+                    vector.len(),
+                    dimension
+                ));
+            }
+            _ => self.dimension = Some(vector.len()),
+        }
+
+        self.entries.push(IndexEntry {
+            chunk_id,
+            file_id,
+            vector: normalize(vector),
+        });
+        Ok(())
+    }
+
+    /// Remove every vector that belongs to a file
+    pub fn remove_file(&mut self, file_id: Uuid) {
+        self.entries.retain(|entry| entry.file_id != Some(file_id));
+        if self.entries.is_empty() {
+            self.dimension = None;
+        }
+    }
+
+    /// Return up to `top_k` chunk ids ordered by cosine similarity to the query
+    pub fn search(&self, query: &[f32], top_k: usize) -> Vec<(Uuid, f32)> {
+        if self.dimension != Some(query.len()) || top_k == 0 {
+            return Vec::new();
+        }
+
+        let query = normalize(query);
+        let mut scored: Vec<(Uuid, f32)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.chunk_id, dot(&entry.vector, &query)))
+            .collect();
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k);
+        scored
+    }
+}
+
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|v| v / norm).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+// Loaded indexes keyed by RAG database id
+static LOCAL_INDEXES: Lazy<RwLock<HashMap<Uuid, LocalVectorIndex>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Build the in-memory index of a RAG database from its stored chunks
+pub async fn load_index(database_id: Uuid) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let chunks = rag_chunks::list_rag_chunks(database_id).await?;
+
+    let mut index = LocalVectorIndex::new();
+    for chunk in &chunks {
+        index.add(chunk.id, chunk.file_id, &chunk.embedding)?;
+    }
+    let count = index.len();
+
+    LOCAL_INDEXES.write().await.insert(database_id, index);
+    println!("Loaded RAG index {} with {} vectors", database_id, count);
+
+    Ok(count)
+}
+
+/// Drop the in-memory index of a RAG database, returning whether it was loaded
+pub async fn unload_index(database_id: Uuid) -> bool {
+    LOCAL_INDEXES.write().await.remove(&database_id).is_some()
+}
+
+pub async fn is_index_loaded(database_id: Uuid) -> bool {
+    LOCAL_INDEXES.read().await.contains_key(&database_id)
+}
+
+/// Ids of the RAG databases whose index is loaded
+pub async fn loaded_index_ids() -> Vec<Uuid> {
+    LOCAL_INDEXES.read().await.keys().copied().collect()
+}
+
+/// Vector count and dimension of a loaded index
+pub async fn index_stats(database_id: Uuid) -> Option<(usize, Option<usize>)> {
+    LOCAL_INDEXES
+        .read()
+        .await
+        .get(&database_id)
+        .map(|index| (index.len(), index.dimension()))
+}
+
+/// Replace the vectors of a file in a loaded index (no-op when the index is not loaded,
+/// since it is rebuilt from the database on load)
+pub async fn replace_file_vectors(
+    database_id: Uuid,
+    file_id: Option<Uuid>,
+    chunks: &[RAGChunk],
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut indexes = LOCAL_INDEXES.write().await;
+    if let Some(index) = indexes.get_mut(&database_id) {
+        if let Some(file_id) = file_id {
+            index.remove_file(file_id);
+        }
+        for chunk in chunks {
+            index.add(chunk.id, chunk.file_id, &chunk.embedding)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove a file's vectors from a loaded index
+pub async fn remove_file_vectors(database_id: Uuid, file_id: Uuid) {
+    if let Some(index) = LOCAL_INDEXES.write().await.get_mut(&database_id) {
+        index.remove_file(file_id);
+    }
+}
+
+/// Search the loaded index of a RAG database. The database has to be started first.
+pub async fn search_index(
+    database_id: Uuid,
+    query: &[f32],
+    top_k: usize,
+) -> Result<Vec<(Uuid, f32)>, Box<dyn std::error::Error + Send + Sync>> {
+    let indexes = LOCAL_INDEXES.read().await;
+    let index = indexes
+        .get(&database_id)
+        .ok_or("RAG database is not started. Please start the database first.")?;
+    Ok(index.search(query, top_k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_orders_by_cosine_similarity() {
+        let mut index = LocalVectorIndex::new();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        index.add(a, None, &[1.0, 0.0]).unwrap();
+        index.add(b, None, &[0.0, 1.0]).unwrap();
+        index.add(c, None, &[10.0, 10.0]).unwrap();
+
+        let results = index.search(&[2.0, 0.1], 2);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, a);
+        assert_eq!(results[1].0, c);
+        assert!(results[0].1 > results[1].1);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_dimensions() {
+        let mut index = LocalVectorIndex::new();
+        index.add(Uuid::new_v4(), None, &[1.0, 0.0, 0.0]).unwrap();
+        assert!(index.add(Uuid::new_v4(), None, &[1.0, 0.0]).is_err());
+        assert!(index.search(&[1.0, 0.0], 5).is_empty());
+    }
+
+    #[test]
+    fn test_remove_file() {
+        let mut index = LocalVectorIndex::new();
+        let file_id = Uuid::new_v4();
+        index.add(Uuid::new_v4(), Some(file_id), &[1.0, 0.0]).unwrap();
+        index.add(Uuid::new_v4(), None, &[0.0, 1.0]).unwrap();
+
+        index.remove_file(file_id);
+        assert_eq!(index.len(), 1);
+    }
+}
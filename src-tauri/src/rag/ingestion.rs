@@ -0,0 +1,176 @@
+use serde_json::json;
+use uuid::Uuid;
+
+use super::chunking::chunk_text;
+use super::embedding::{create_embedding_provider, EmbeddingProvider};
+use super::index::replace_file_vectors;
+use super::providers::{collection_name, create_rag_provider, is_vector_store_provider, RAGProvider, VectorRecord};
+use crate::database::{
+    models::{
+        self, NewRAGChunk, RAGDatabase, RAGDatabaseFile, RAG_FILE_STATUS_COMPLETED, RAG_FILE_STATUS_FAILED,
+        RAG_FILE_STATUS_PROCESSING,
+    },
+    queries::{files::get_file_by_id, rag_chunks},
+};
+use crate::FILE_STORAGE;
+
+// Number of chunks sent to the embedding provider per request
+const EMBEDDING_BATCH_SIZE: usize = 32;
+
+/// Mark files as pending for a RAG database and ingest them in the background
+pub async fn queue_file_ingestion(
+    provider: models::RAGProvider,
+    database: RAGDatabase,
+    file_ids: Vec<Uuid>,
+) -> Result<Vec<RAGDatabaseFile>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut queued = Vec::with_capacity(file_ids.len());
+    for file_id in &file_ids {
+        queued.push(rag_chunks::upsert_rag_database_file(database.id, *file_id).await?);
+    }
+
+    tokio::spawn(async move {
+        let embedder = match create_embedding_provider(&database).await {
+            Ok(embedder) => embedder,
+            Err(e) => {
+                eprintln!("Failed to create embedding provider for RAG database {}: {}", database.id, e);
+                for file_id in file_ids {
+                    mark_failed(database.id, file_id, &e.to_string()).await;
+                }
+                return;
+            }
+        };
+
+        let vector_store = if is_vector_store_provider(&provider.provider_type) {
+            match create_rag_provider(&provider) {
+                Ok(vector_store) => Some(vector_store),
+                Err(e) => {
+                    eprintln!("Failed to create vector store for RAG database {}: {}", database.id, e);
+                    for file_id in file_ids {
+                        mark_failed(database.id, file_id, &e.to_string()).await;
+                    }
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        // Files are ingested one after another to keep load on the embedding provider bounded
+        for file_id in file_ids {
+            match ingest_file(&database, embedder.as_ref(), vector_store.as_deref(), file_id).await {
+                Ok(chunk_count) => {
+                    println!(
+                        "Ingested file {} into RAG database {} ({} chunks)",
+                        file_id, database.id, chunk_count
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Failed to ingest file {} into RAG database {}: {}", file_id, database.id, e);
+                    mark_failed(database.id, file_id, &e.to_string()).await;
+                }
+            }
+        }
+    });
+
+    Ok(queued)
+}
+
+/// Chunk, embed and store the extracted text of a file, returning the number of chunks.
+/// Chunks are also upserted to `vector_store` when the database lives in one.
+pub async fn ingest_file(
+    database: &RAGDatabase,
+    embedder: &dyn EmbeddingProvider,
+    vector_store: Option<&dyn RAGProvider>,
+    file_id: Uuid,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    rag_chunks::update_rag_database_file_status(database.id, file_id, RAG_FILE_STATUS_PROCESSING, None, None)
+        .await?;
+
+    let file = get_file_by_id(file_id).await?.ok_or("File not found")?;
+
+    // Text extracted by the ProcessingManager when the file was uploaded
+    let text = FILE_STORAGE
+        .read_text_content(file_id)
+        .await?
+        .filter(|text| !text.trim().is_empty())
+        .ok_or("File has no extracted text content")?;
+
+    let chunks = chunk_text(
+        &text,
+        database.chunk_size.max(1) as usize,
+        database.chunk_overlap.max(0) as usize,
+    );
+
+    let mut new_chunks = Vec::with_capacity(chunks.len());
+    for (batch_index, batch) in chunks.chunks(EMBEDDING_BATCH_SIZE).enumerate() {
+        let texts: Vec<String> = batch.iter().map(|chunk| chunk.content.clone()).collect();
+        let vectors = embedder.embed(&texts).await?;
+
+        for (offset, (chunk, embedding)) in batch.iter().zip(vectors).enumerate() {
+            new_chunks.push(NewRAGChunk {
+                chunk_index: (batch_index * EMBEDDING_BATCH_SIZE + offset) as i32,
+                content: chunk.content.clone(),
+                metadata: json!({
+                    "filename": file.filename,
+                    "start": chunk.start,
+                    "end": chunk.end,
+                    "embedding_model": embedder.model_name(),
+                }),
+                embedding,
+            });
+        }
+    }
+
+    let stale_chunk_ids = match vector_store {
+        Some(_) => rag_chunks::list_rag_file_chunk_ids(database.id, file_id).await?,
+        None => Vec::new(),
+    };
+
+    let stored = rag_chunks::replace_rag_file_chunks(database.id, Some(file_id), new_chunks).await?;
+    replace_file_vectors(database.id, Some(file_id), &stored).await?;
+
+    if let Some(vector_store) = vector_store {
+        let collection = collection_name(database);
+        let records = stored
+            .iter()
+            .map(|chunk| {
+                let mut metadata = chunk.metadata.clone();
+                if let Some(object) = metadata.as_object_mut() {
+                    object.insert("file_id".to_string(), json!(file_id));
+                }
+                VectorRecord {
+                    id: chunk.id.to_string(),
+                    vector: chunk.embedding.clone(),
+                    content: chunk.content.clone(),
+                    metadata,
+                }
+            })
+            .collect();
+        vector_store.upsert(&collection, records).await?;
+
+        // Upsert first so the file stays searchable while its old chunks are removed
+        let stale_ids: Vec<String> = stale_chunk_ids.iter().map(Uuid::to_string).collect();
+        vector_store.delete(&collection, &stale_ids).await?;
+    }
+
+    let chunk_count = stored.len();
+    rag_chunks::update_rag_database_file_status(
+        database.id,
+        file_id,
+        RAG_FILE_STATUS_COMPLETED,
+        Some(chunk_count as i32),
+        None,
+    )
+    .await?;
+
+    Ok(chunk_count)
+}
+
+async fn mark_failed(database_id: Uuid, file_id: Uuid, error: &str) {
+    if let Err(e) =
+        rag_chunks::update_rag_database_file_status(database_id, file_id, RAG_FILE_STATUS_FAILED, Some(0), Some(error))
+            .await
+    {
+        eprintln!("Failed to record ingestion failure for file {}: {}", file_id, e);
+    }
+}
@@ -0,0 +1,87 @@
+//! Lifecycle of local RAG databases
+//!
+//! Starting a local RAG database loads its vectors into the in-memory index and stopping it
+//! frees them. `is_active` in the database mirrors whether the index is loaded; the periodic
+//! process cleanup reconciles the two when they drift apart.
+
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::index::{is_index_loaded, load_index, loaded_index_ids, unload_index};
+use crate::database::queries::rag_providers::{list_active_local_rag_databases, set_rag_database_active};
+
+// Serializes start/stop with the cleanup pass so it never sees a half-started database
+static LIFECYCLE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Load the index of a local RAG database and mark it active, returning the vector count
+pub async fn start_rag_database(database_id: Uuid) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let _guard = LIFECYCLE_LOCK.lock().await;
+
+    let count = load_index(database_id).await?;
+    if let Err(e) = set_rag_database_active(database_id, true).await {
+        unload_index(database_id).await;
+        return Err(e.into());
+    }
+
+    Ok(count)
+}
+
+/// Free the index of a local RAG database and mark it inactive
+pub async fn stop_rag_database(database_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let _guard = LIFECYCLE_LOCK.lock().await;
+
+    let was_loaded = unload_index(database_id).await;
+    set_rag_database_active(database_id, false).await?;
+
+    Ok(was_loaded)
+}
+
+/// Reload the indexes of local RAG databases that were active when the app last shut down
+pub async fn restore_active_rag_databases() {
+    let _guard = LIFECYCLE_LOCK.lock().await;
+
+    let databases = match list_active_local_rag_databases().await {
+        Ok(databases) => databases,
+        Err(e) => {
+            eprintln!("Failed to list active RAG databases: {}", e);
+            return;
+        }
+    };
+
+    for database in databases {
+        if let Err(e) = load_index(database.id).await {
+            eprintln!("Failed to restore RAG database {} ({}): {}", database.name, database.id, e);
+            let _ = set_rag_database_active(database.id, false).await;
+        }
+    }
+}
+
+/// Free indexes of stopped or deleted databases and mark databases without a loaded index
+/// as stopped. Runs as part of the model manager's periodic process cleanup.
+pub async fn cleanup_rag_indexes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _guard = LIFECYCLE_LOCK.lock().await;
+
+    let active: HashSet<Uuid> = list_active_local_rag_databases()
+        .await?
+        .into_iter()
+        .map(|database| database.id)
+        .collect();
+
+    for database_id in loaded_index_ids().await {
+        if !active.contains(&database_id) {
+            println!("Freeing index of inactive RAG database {}", database_id);
+            unload_index(database_id).await;
+        }
+    }
+
+    for database_id in active {
+        if !is_index_loaded(database_id).await {
+            println!("RAG database {} is marked active but has no index, marking it stopped", database_id);
+            set_rag_database_active(database_id, false).await?;
+        }
+    }
+
+    Ok(())
+}
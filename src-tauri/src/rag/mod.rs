@@ -0,0 +1,34 @@
+//! Retrieval-augmented generation over RAG databases
+//!
+//! Text extracted from uploaded files is split into overlapping chunks, embedded through the
+//! database's configured embedding model and stored in PostgreSQL. Similarity search runs over
+//! an in-memory index built from the stored vectors, and the best matches for a chat message are
+//! injected into the prompt of assistants and projects that have the database attached.
+//! Starting a local database loads its index into memory and stopping it frees it.
+//! Databases of Chroma and Qdrant providers are searched in that vector store instead, with
+//! the chunks upserted to it during ingestion.
+//! Indexed databases can be exported as portable packages and imported from RAG repositories.
+//! Messages and file text of every user are also embedded in the background for semantic
+//! search across their own conversations and files.
+
+pub mod chunking;
+pub mod download;
+pub mod embedding;
+pub mod export;
+pub mod index;
+pub mod ingestion;
+pub mod manager;
+pub mod package;
+pub mod providers;
+pub mod retrieval;
+pub mod search;
+pub mod semantic;
+
+pub use chunking::{chunk_text, TextChunk};
+pub use download::start_rag_database_download;
+pub use embedding::{create_embedding_provider, EmbeddingProvider};
+pub use export::export_rag_database_package;
+pub use ingestion::{ingest_file, queue_file_ingestion};
+pub use retrieval::{format_context_prompt, retrieve_citations, DEFAULT_RETRIEVAL_TOP_K};
+pub use search::search_rag_database;
+pub use semantic::{check_embedding_model, notify_semantic_indexer, semantic_search, start_semantic_index_task};
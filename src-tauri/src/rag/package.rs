@@ -0,0 +1,194 @@
+//! Portable RAG database package format
+//!
+//! A package is a gzip-compressed JSON Lines file (`.ragpkg`). The first line is the
+//! [`RAGPackageManifest`], which names the embedding model the vectors were produced with;
+//! every following line is a [`RAGPackageChunk`] holding the chunk text and its embedding.
+//! Keeping everything in one stream lets a package be imported while it is read, without
+//! holding all vectors in memory.
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+
+pub const RAG_PACKAGE_FORMAT_VERSION: u32 = 1;
+pub const RAG_PACKAGE_EXTENSION: &str = "ragpkg";
+
+/// Describes a packaged RAG database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RAGPackageManifest {
+    pub format_version: u32,
+    pub name: String,
+    pub alias: Option<String>,
+    pub description: Option<String>,
+    /// Id of the model that produced the embeddings; queries must be embedded with the same model
+    pub embedding_model: String,
+    pub embedding_dimension: usize,
+    pub chunk_size: i32,
+    pub chunk_overlap: i32,
+    pub chunk_count: usize,
+}
+
+/// A chunk of source text with its embedding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RAGPackageChunk {
+    /// Name of the document the chunk was taken from
+    #[serde(default)]
+    pub source: Option<String>,
+    pub chunk_index: i32,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    pub embedding: Vec<f32>,
+}
+
+/// Reads a package one batch of chunks at a time
+pub struct RAGPackageReader<R: Read> {
+    lines: std::io::Lines<BufReader<GzDecoder<R>>>,
+    manifest: RAGPackageManifest,
+    line_number: usize,
+}
+
+impl<R: Read> RAGPackageReader<R> {
+    /// Open a package and validate its manifest
+    pub fn new(reader: R) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut lines = BufReader::new(GzDecoder::new(reader)).lines();
+        let manifest_line = lines.next().ok_or("RAG package is empty")??;
+        let manifest: RAGPackageManifest = serde_json::from_str(&manifest_line)
+            .map_err(|e| format!("Invalid RAG package manifest: {}", e))?;
+
+        if manifest.format_version == 0 || manifest.format_version > RAG_PACKAGE_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported RAG package format version {}",
+                manifest.format_version
+            )
+            .into());
+        }
+        if manifest.embedding_model.trim().is_empty() {
+            return Err("RAG package manifest has no embedding model".into());
+        }
+        if manifest.embedding_dimension == 0 {
+            return Err("RAG package manifest has no embedding dimension".into());
+        }
+
+        Ok(Self {
+            lines,
+            manifest,
+            line_number: 1,
+        })
+    }
+
+    pub fn manifest(&self) -> &RAGPackageManifest {
+        &self.manifest
+    }
+
+    /// Read up to `max_chunks` chunks; an empty batch means the package is exhausted
+    pub fn next_batch(
+        &mut self,
+        max_chunks: usize,
+    ) -> Result<Vec<RAGPackageChunk>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut batch = Vec::new();
+        while batch.len() < max_chunks {
+            let line = match self.lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let chunk: RAGPackageChunk = serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid chunk on line {}: {}", self.line_number, e))?;
+            if chunk.embedding.len() != self.manifest.embedding_dimension {
+                return Err(format!(
+                    "Chunk on line {} has dimension {}, expected {}",
+                    self.line_number,
+                    chunk.embedding.len(),
+                    self.manifest.embedding_dimension
+                )
+                .into());
+            }
+            batch.push(chunk);
+        }
+        Ok(batch)
+    }
+}
+
+/// Write a package, returning the inner writer once the gzip stream is finished
+pub fn write_package<W: Write>(
+    writer: W,
+    manifest: &RAGPackageManifest,
+    chunks: impl IntoIterator<Item = RAGPackageChunk>,
+) -> Result<W, Box<dyn std::error::Error + Send + Sync>> {
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+
+    serde_json::to_writer(&mut encoder, manifest)?;
+    encoder.write_all(b"\n")?;
+    for chunk in chunks {
+        serde_json::to_writer(&mut encoder, &chunk)?;
+        encoder.write_all(b"\n")?;
+    }
+
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(dimension: usize) -> RAGPackageManifest {
+        RAGPackageManifest {
+            format_version: RAG_PACKAGE_FORMAT_VERSION,
+            name: "Handbook".to_string(),
+            alias: Some("handbook".to_string()),
+            description: None,
+            embedding_model: "text-embedding-3-small".to_string(),
+            embedding_dimension: dimension,
+            chunk_size: 1000,
+            chunk_overlap: 200,
+            chunk_count: 3,
+        }
+    }
+
+    fn chunk(index: i32, embedding: Vec<f32>) -> RAGPackageChunk {
+        RAGPackageChunk {
+            source: Some("guide.md".to_string()),
+            chunk_index: index,
+            content: format!("chunk {}", index),
+            metadata: serde_json::json!({}),
+            embedding,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_in_batches() {
+        let chunks = (0..3).map(|i| chunk(i, vec![i as f32, 1.0]));
+        let bytes = write_package(Vec::new(), &manifest(2), chunks).unwrap();
+
+        let mut reader = RAGPackageReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.manifest().embedding_model, "text-embedding-3-small");
+
+        let first = reader.next_batch(2).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[1].content, "chunk 1");
+        assert_eq!(reader.next_batch(2).unwrap().len(), 1);
+        assert!(reader.next_batch(2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_mismatched_embedding_dimension() {
+        let bytes = write_package(Vec::new(), &manifest(3), vec![chunk(0, vec![1.0, 0.0])]).unwrap();
+
+        let mut reader = RAGPackageReader::new(bytes.as_slice()).unwrap();
+        assert!(reader.next_batch(10).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_format_version() {
+        let mut future = manifest(2);
+        future.format_version = RAG_PACKAGE_FORMAT_VERSION + 1;
+        let bytes = write_package(Vec::new(), &future, Vec::new()).unwrap();
+
+        assert!(RAGPackageReader::new(bytes.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::{candidate_count, finalize_matches, RAGProvider, VectorMatch, VectorQuery, VectorRecord};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:8000";
+
+const DEFAULT_TENANT: &str = "default_tenant";
+const DEFAULT_DATABASE: &str = "default_database";
+
+/// Adapter for the Chroma v2 HTTP API. Collections are created with cosine distance,
+/// which is converted back to a similarity score.
+#[derive(Debug, Clone)]
+pub struct ChromaProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChromaCollection {
+    id: String,
+    name: String,
+}
+
+// Query results hold one list per query embedding
+type ChromaResultLists<T> = Option<Vec<Vec<Option<T>>>>;
+
+#[derive(Debug, Deserialize)]
+struct ChromaQueryResponse {
+    ids: Vec<Vec<String>>,
+    #[serde(default)]
+    documents: ChromaResultLists<String>,
+    #[serde(default)]
+    metadatas: ChromaResultLists<Map<String, Value>>,
+    #[serde(default)]
+    distances: ChromaResultLists<f32>,
+}
+
+impl ChromaProvider {
+    pub fn new(client: Client, base_url: String, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    fn collections_url(&self) -> String {
+        format!(
+            "{}/api/v2/tenants/{}/databases/{}/collections",
+            self.base_url, DEFAULT_TENANT, DEFAULT_DATABASE
+        )
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(api_key) => request.header("Authorization", format!("Bearer {}", api_key)),
+            None => request,
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.authorize(request).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Chroma API error ({}): {}", status, error_text).into());
+        }
+        Ok(response)
+    }
+
+    /// Look up a collection id by name, returning None when the collection does not exist
+    async fn find_collection_id(&self, collection: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .authorize(self.client.get(format!("{}/{}", self.collections_url(), collection)))
+            .send()
+            .await?;
+        // Chroma answers unknown collections with 404 or, in some versions, 400
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST) {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Chroma API error ({}): {}", status, error_text).into());
+        }
+
+        let collection: ChromaCollection = response.json().await?;
+        Ok(Some(collection.id))
+    }
+
+    async fn get_or_create_collection_id(&self, collection: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .send(self.client.post(self.collections_url()).json(&json!({
+                "name": collection,
+                "get_or_create": true,
+                "metadata": { "hnsw:space": "cosine" },
+            })))
+            .await?;
+
+        let collection: ChromaCollection = response.json().await?;
+        Ok(collection.id)
+    }
+}
+
+/// Chroma metadata values must be scalars, so nested values are stored as JSON strings
+fn chroma_metadata(metadata: &Value) -> Map<String, Value> {
+    metadata
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::Array(_) | Value::Object(_) => Value::String(value.to_string()),
+                        scalar => scalar.clone(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build a `where` clause matching every field of the filter
+fn chroma_where(filter: &Map<String, Value>) -> Option<Value> {
+    let mut conditions: Vec<Value> = filter
+        .iter()
+        .map(|(key, value)| json!({ key: { "$eq": value } }))
+        .collect();
+
+    match conditions.len() {
+        0 => None,
+        1 => conditions.pop(),
+        _ => Some(json!({ "$and": conditions })),
+    }
+}
+
+#[async_trait]
+impl RAGProvider for ChromaProvider {
+    async fn upsert(
+        &self,
+        collection: &str,
+        records: Vec<VectorRecord>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let collection_id = self.get_or_create_collection_id(collection).await?;
+        let ids: Vec<&str> = records.iter().map(|record| record.id.as_str()).collect();
+        let embeddings: Vec<&[f32]> = records.iter().map(|record| record.vector.as_slice()).collect();
+        let documents: Vec<&str> = records.iter().map(|record| record.content.as_str()).collect();
+        let metadatas: Vec<Map<String, Value>> = records.iter().map(|record| chroma_metadata(&record.metadata)).collect();
+
+        self.send(
+            self.client
+                .post(format!("{}/{}/upsert", self.collections_url(), collection_id))
+                .json(&json!({
+                    "ids": ids,
+                    "embeddings": embeddings,
+                    "documents": documents,
+                    "metadatas": metadatas,
+                })),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        collection: &str,
+        query: &VectorQuery,
+    ) -> Result<Vec<VectorMatch>, Box<dyn std::error::Error + Send + Sync>> {
+        let collection_id = self
+            .find_collection_id(collection)
+            .await?
+            .ok_or_else(|| format!("Chroma collection {} does not exist", collection))?;
+
+        let mut body = json!({
+            "query_embeddings": [query.vector],
+            "n_results": candidate_count(query),
+            "include": ["documents", "metadatas", "distances"],
+        });
+        if let Some(where_clause) = query.filter.as_ref().and_then(chroma_where) {
+            body["where"] = where_clause;
+        }
+
+        let response: ChromaQueryResponse = self
+            .send(
+                self.client
+                    .post(format!("{}/{}/query", self.collections_url(), collection_id))
+                    .json(&body),
+            )
+            .await?
+            .json()
+            .await?;
+
+        // Only one query embedding is sent
+        let ids = response.ids.into_iter().next().unwrap_or_default();
+        let mut documents = response.documents.and_then(|d| d.into_iter().next()).unwrap_or_default().into_iter();
+        let mut metadatas = response.metadatas.and_then(|m| m.into_iter().next()).unwrap_or_default().into_iter();
+        let mut distances = response.distances.and_then(|d| d.into_iter().next()).unwrap_or_default().into_iter();
+
+        let matches = ids
+            .into_iter()
+            .map(|id| VectorMatch {
+                id,
+                content: documents.next().flatten().unwrap_or_default(),
+                metadata: metadatas.next().flatten().map(Value::Object).unwrap_or(Value::Null),
+                score: 1.0 - distances.next().flatten().unwrap_or(1.0),
+            })
+            .collect();
+
+        Ok(finalize_matches(matches, query))
+    }
+
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        // Nothing to delete from a collection that was never created
+        let collection_id = match self.find_collection_id(collection).await? {
+            Some(collection_id) => collection_id,
+            None => return Ok(()),
+        };
+        self.send(
+            self.client
+                .post(format!("{}/{}/delete", self.collections_url(), collection_id))
+                .json(&json!({ "ids": ids })),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_collections(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let collections: Vec<ChromaCollection> = self.send(self.client.get(self.collections_url())).await?.json().await?;
+        Ok(collections.into_iter().map(|collection| collection.name).collect())
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "chroma"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_helpers::spawn_mock_server;
+    use super::*;
+    use axum::{extract::State, routing::{get, post}, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Requests = Arc<Mutex<Vec<Value>>>;
+
+    const COLLECTIONS_PATH: &str = "/api/v2/tenants/default_tenant/databases/default_database/collections";
+
+    async fn mock_chroma() -> (ChromaProvider, Requests) {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+
+        let router = Router::new()
+            .route(
+                COLLECTIONS_PATH,
+                get(|| async { Json(json!([{ "id": "c1", "name": "handbook" }])) })
+                    .post(|| async { Json(json!({ "id": "c1", "name": "handbook" })) }),
+            )
+            .route(
+                &format!("{}/handbook", COLLECTIONS_PATH),
+                get(|| async { Json(json!({ "id": "c1", "name": "handbook" })) }),
+            )
+            .route(
+                &format!("{}/c1/upsert", COLLECTIONS_PATH),
+                post(|State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push(body);
+                    Json(json!(true))
+                }),
+            )
+            .route(
+                &format!("{}/c1/query", COLLECTIONS_PATH),
+                post(|State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push(body);
+                    Json(json!({
+                        "ids": [["a", "b"]],
+                        "documents": [["Shipping times", "Our refund policy"]],
+                        "metadatas": [[{ "filename": "faq.md" }, null]],
+                        "distances": [[0.1, 0.6]],
+                    }))
+                }),
+            )
+            .with_state(requests.clone());
+
+        let base_url = spawn_mock_server(router).await;
+        (ChromaProvider::new(Client::new(), base_url, None), requests)
+    }
+
+    #[tokio::test]
+    async fn test_upsert_flattens_metadata() {
+        let (provider, requests) = mock_chroma().await;
+
+        provider
+            .upsert(
+                "handbook",
+                vec![VectorRecord {
+                    id: "a".to_string(),
+                    vector: vec![1.0, 0.0],
+                    content: "Shipping times".to_string(),
+                    metadata: json!({ "filename": "faq.md", "pages": [1, 2], "file_id": null }),
+                }],
+            )
+            .await
+            .unwrap();
+
+        let body = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(body["ids"], json!(["a"]));
+        assert_eq!(body["metadatas"][0], json!({ "filename": "faq.md", "pages": "[1,2]" }));
+    }
+
+    #[tokio::test]
+    async fn test_query_converts_distances_and_filters() {
+        let (provider, requests) = mock_chroma().await;
+
+        let mut filter = Map::new();
+        filter.insert("filename".to_string(), json!("faq.md"));
+        let matches = provider
+            .query(
+                "handbook",
+                &VectorQuery {
+                    vector: vec![1.0, 0.0],
+                    top_k: 5,
+                    filter: Some(filter),
+                    similarity_threshold: Some(0.5),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, "a");
+        assert!((matches[0].score - 0.9).abs() < 1e-6);
+        assert_eq!(matches[0].metadata["filename"], "faq.md");
+
+        let body = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(body["where"], json!({ "filename": { "$eq": "faq.md" } }));
+        assert_eq!(body["n_results"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_list_collections() {
+        let (provider, _) = mock_chroma().await;
+        assert_eq!(provider.list_collections().await.unwrap(), vec!["handbook"]);
+    }
+}
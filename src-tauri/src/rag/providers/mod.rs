@@ -0,0 +1,247 @@
+//! Adapters for remote vector stores
+//!
+//! A [`RAGProvider`] stores embedded chunks in collections of an external vector database so
+//! teams that already run one can point the app at it. Chunks are still embedded by the app;
+//! the vector store only persists and searches them.
+
+pub mod chroma;
+pub mod qdrant;
+
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+
+use crate::ai::core::provider_base::build_http_client;
+use crate::database::models::{self, RAGDatabase, RAGDatabaseCapabilities};
+
+pub use chroma::ChromaProvider;
+pub use qdrant::QdrantProvider;
+
+// Hybrid search re-ranks this many vector candidates per requested result
+const HYBRID_CANDIDATE_FACTOR: usize = 4;
+// Weight of the vector score in the hybrid score; the rest is keyword overlap
+const HYBRID_VECTOR_WEIGHT: f32 = 0.7;
+
+/// A chunk and its embedding to store in a collection
+#[derive(Debug, Clone)]
+pub struct VectorRecord {
+    pub id: String,
+    pub vector: Vec<f32>,
+    pub content: String,
+    pub metadata: Value,
+}
+
+/// A similarity query against a collection
+#[derive(Debug, Clone, Default)]
+pub struct VectorQuery {
+    pub vector: Vec<f32>,
+    /// Query text, used for keyword scoring when hybrid search is enabled
+    pub text: Option<String>,
+    pub top_k: usize,
+    /// Metadata fields that must equal the given values
+    pub filter: Option<Map<String, Value>>,
+    /// Minimum cosine similarity of a match
+    pub similarity_threshold: Option<f32>,
+}
+
+/// A stored chunk returned by a query, with a higher score meaning more similar
+#[derive(Debug, Clone)]
+pub struct VectorMatch {
+    pub id: String,
+    pub content: String,
+    pub metadata: Value,
+    pub score: f32,
+}
+
+#[async_trait]
+pub trait RAGProvider: Send + Sync {
+    /// Insert or replace records, creating the collection if it does not exist
+    async fn upsert(
+        &self,
+        collection: &str,
+        records: Vec<VectorRecord>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn query(
+        &self,
+        collection: &str,
+        query: &VectorQuery,
+    ) -> Result<Vec<VectorMatch>, Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn list_collections(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Returns the name of the provider for logging and debugging
+    fn provider_name(&self) -> &'static str;
+}
+
+/// Whether a RAG provider type is backed by a remote vector store adapter
+pub fn is_vector_store_provider(provider_type: &str) -> bool {
+    matches!(provider_type, "chroma" | "qdrant")
+}
+
+/// Create the vector store adapter for a RAG provider
+pub fn create_rag_provider(
+    provider: &models::RAGProvider,
+) -> Result<Box<dyn RAGProvider>, Box<dyn std::error::Error + Send + Sync>> {
+    let default_base_url = match provider.provider_type.as_str() {
+        "chroma" => chroma::DEFAULT_BASE_URL,
+        "qdrant" => qdrant::DEFAULT_BASE_URL,
+        other => return Err(format!("RAG provider type {} has no vector store adapter", other).into()),
+    };
+
+    let base_url = provider
+        .base_url
+        .clone()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| default_base_url.to_string());
+    let proxy_config = provider
+        .proxy_settings
+        .as_ref()
+        .and_then(crate::api::chat::create_proxy_config);
+    let client = build_http_client(&base_url, proxy_config.as_ref())?;
+    let api_key = provider.api_key.clone().filter(|key| !key.is_empty());
+
+    Ok(match provider.provider_type.as_str() {
+        "chroma" => Box::new(ChromaProvider::new(client, base_url, api_key)),
+        _ => Box::new(QdrantProvider::new(client, base_url, api_key)),
+    })
+}
+
+/// Name of the remote collection backing a RAG database
+pub fn collection_name(database: &RAGDatabase) -> String {
+    database
+        .collection_name
+        .clone()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| database.alias.clone())
+}
+
+/// Drop the parts of a query the database has not enabled. Hybrid search and the similarity
+/// threshold are optional refinements and are ignored when disabled; a metadata filter changes
+/// which results are correct, so it is rejected instead.
+pub fn apply_capabilities(
+    mut query: VectorQuery,
+    capabilities: Option<&RAGDatabaseCapabilities>,
+) -> Result<VectorQuery, Box<dyn std::error::Error + Send + Sync>> {
+    let enabled = |capability: fn(&RAGDatabaseCapabilities) -> Option<bool>| {
+        capabilities.and_then(capability).unwrap_or(false)
+    };
+
+    if !enabled(|c| c.hybrid_search) {
+        query.text = None;
+    }
+    if !enabled(|c| c.similarity_threshold) {
+        query.similarity_threshold = None;
+    }
+    if query.filter.as_ref().is_some_and(|filter| !filter.is_empty()) && !enabled(|c| c.metadata_filtering) {
+        return Err("Metadata filtering is not enabled for this RAG database".into());
+    }
+
+    Ok(query)
+}
+
+/// Number of vector candidates to fetch for a query
+fn candidate_count(query: &VectorQuery) -> usize {
+    if query.text.is_some() {
+        query.top_k * HYBRID_CANDIDATE_FACTOR
+    } else {
+        query.top_k
+    }
+}
+
+fn keyword_terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() > 1)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Apply the similarity threshold and, for hybrid queries, blend the vector score with the
+/// share of query terms found in each chunk before keeping the `top_k` best matches
+fn finalize_matches(mut matches: Vec<VectorMatch>, query: &VectorQuery) -> Vec<VectorMatch> {
+    if let Some(threshold) = query.similarity_threshold {
+        matches.retain(|m| m.score >= threshold);
+    }
+
+    if let Some(text) = &query.text {
+        let terms = keyword_terms(text);
+        if !terms.is_empty() {
+            for m in matches.iter_mut() {
+                let content = m.content.to_lowercase();
+                let found = terms.iter().filter(|term| content.contains(term.as_str())).count();
+                let keyword_score = found as f32 / terms.len() as f32;
+                m.score = HYBRID_VECTOR_WEIGHT * m.score + (1.0 - HYBRID_VECTOR_WEIGHT) * keyword_score;
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    matches.truncate(query.top_k);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector_match(id: &str, content: &str, score: f32) -> VectorMatch {
+        VectorMatch {
+            id: id.to_string(),
+            content: content.to_string(),
+            metadata: Value::Null,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_apply_capabilities_drops_disabled_features() {
+        let query = VectorQuery {
+            text: Some("refund policy".to_string()),
+            top_k: 3,
+            similarity_threshold: Some(0.5),
+            ..Default::default()
+        };
+
+        let query = apply_capabilities(query, None).unwrap();
+        assert!(query.text.is_none());
+        assert!(query.similarity_threshold.is_none());
+    }
+
+    #[test]
+    fn test_apply_capabilities_rejects_disabled_filter() {
+        let mut filter = Map::new();
+        filter.insert("filename".to_string(), Value::String("guide.md".to_string()));
+        let query = VectorQuery {
+            top_k: 3,
+            filter: Some(filter),
+            ..Default::default()
+        };
+
+        let capabilities = RAGDatabaseCapabilities {
+            semantic_search: Some(true),
+            hybrid_search: None,
+            metadata_filtering: Some(false),
+            similarity_threshold: None,
+        };
+        assert!(apply_capabilities(query, Some(&capabilities)).is_err());
+    }
+
+    #[test]
+    fn test_finalize_matches_reranks_hybrid_queries() {
+        let query = VectorQuery {
+            text: Some("refund policy".to_string()),
+            top_k: 2,
+            similarity_threshold: Some(0.2),
+            ..Default::default()
+        };
+        let matches = vec![
+            vector_match("a", "Shipping times", 0.8),
+            vector_match("b", "Our refund policy", 0.7),
+            vector_match("c", "Refund policy details", 0.1),
+        ];
+
+        let ids: Vec<_> = finalize_matches(matches, &query).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+    }
+}
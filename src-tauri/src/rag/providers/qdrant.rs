@@ -0,0 +1,338 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::{candidate_count, finalize_matches, RAGProvider, VectorMatch, VectorQuery, VectorRecord};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:6333";
+
+// Payload field holding the chunk text; the remaining payload fields are the chunk metadata
+const CONTENT_FIELD: &str = "content";
+
+/// Adapter for the Qdrant HTTP API. Point ids must be UUIDs or unsigned integers,
+/// and collections are created with cosine distance.
+#[derive(Debug, Clone)]
+pub struct QdrantProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantCollections {
+    collections: Vec<QdrantCollectionName>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantCollectionName {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantScoredPoint {
+    id: Value,
+    score: f32,
+    #[serde(default)]
+    payload: Option<Map<String, Value>>,
+}
+
+impl QdrantProvider {
+    pub fn new(client: Client, base_url: String, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(api_key) => request.header("api-key", api_key),
+            None => request,
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.authorize(request).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Qdrant API error ({}): {}", status, error_text).into());
+        }
+        Ok(response)
+    }
+
+    async fn ensure_collection(&self, collection: &str, dimension: usize) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/collections/{}", self.base_url, collection);
+        let response = self.authorize(self.client.get(&url)).send().await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(format!("Qdrant API error ({}): {}", status, error_text).into());
+            }
+            return Ok(());
+        }
+
+        self.send(self.client.put(&url).json(&json!({
+            "vectors": { "size": dimension, "distance": "Cosine" },
+        })))
+        .await?;
+        Ok(())
+    }
+}
+
+/// Build a filter requiring every field of the filter to match
+fn qdrant_filter(filter: &Map<String, Value>) -> Value {
+    let must: Vec<Value> = filter
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "match": { "value": value } }))
+        .collect();
+    json!({ "must": must })
+}
+
+fn point_payload(record: &VectorRecord) -> Map<String, Value> {
+    let mut payload = record.metadata.as_object().cloned().unwrap_or_default();
+    payload.insert(CONTENT_FIELD.to_string(), Value::String(record.content.clone()));
+    payload
+}
+
+fn point_id(id: Value) -> String {
+    match id {
+        Value::String(id) => id,
+        other => other.to_string(),
+    }
+}
+
+#[async_trait]
+impl RAGProvider for QdrantProvider {
+    async fn upsert(
+        &self,
+        collection: &str,
+        records: Vec<VectorRecord>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let dimension = match records.first() {
+            Some(record) => record.vector.len(),
+            None => return Ok(()),
+        };
+        self.ensure_collection(collection, dimension).await?;
+
+        let points: Vec<Value> = records
+            .iter()
+            .map(|record| {
+                json!({
+                    "id": record.id,
+                    "vector": record.vector,
+                    "payload": point_payload(record),
+                })
+            })
+            .collect();
+
+        self.send(
+            self.client
+                .put(format!("{}/collections/{}/points?wait=true", self.base_url, collection))
+                .json(&json!({ "points": points })),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        collection: &str,
+        query: &VectorQuery,
+    ) -> Result<Vec<VectorMatch>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut body = json!({
+            "vector": query.vector,
+            "limit": candidate_count(query),
+            "with_payload": true,
+        });
+        if let Some(filter) = query.filter.as_ref().filter(|filter| !filter.is_empty()) {
+            body["filter"] = qdrant_filter(filter);
+        }
+        if let Some(threshold) = query.similarity_threshold {
+            body["score_threshold"] = json!(threshold);
+        }
+
+        let response: QdrantResponse<Vec<QdrantScoredPoint>> = self
+            .send(
+                self.client
+                    .post(format!("{}/collections/{}/points/search", self.base_url, collection))
+                    .json(&body),
+            )
+            .await?
+            .json()
+            .await?;
+
+        let matches = response
+            .result
+            .into_iter()
+            .map(|point| {
+                let mut payload = point.payload.unwrap_or_default();
+                let content = match payload.remove(CONTENT_FIELD) {
+                    Some(Value::String(content)) => content,
+                    _ => String::new(),
+                };
+                VectorMatch {
+                    id: point_id(point.id),
+                    content,
+                    metadata: Value::Object(payload),
+                    score: point.score,
+                }
+            })
+            .collect();
+
+        Ok(finalize_matches(matches, query))
+    }
+
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        self.send(
+            self.client
+                .post(format!("{}/collections/{}/points/delete?wait=true", self.base_url, collection))
+                .json(&json!({ "points": ids })),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_collections(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let response: QdrantResponse<QdrantCollections> = self
+            .send(self.client.get(format!("{}/collections", self.base_url)))
+            .await?
+            .json()
+            .await?;
+
+        Ok(response.result.collections.into_iter().map(|collection| collection.name).collect())
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "qdrant"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_helpers::spawn_mock_server;
+    use super::*;
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
+    use std::sync::{Arc, Mutex};
+
+    type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+    async fn mock_qdrant() -> (QdrantProvider, Requests) {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+
+        let router = Router::new()
+            .route(
+                "/collections",
+                get(|| async { Json(json!({ "result": { "collections": [{ "name": "handbook" }] } })) }),
+            )
+            .route(
+                "/collections/handbook",
+                get(|| async { StatusCode::NOT_FOUND }).put(
+                    |State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                        requests.lock().unwrap().push(("create".to_string(), body));
+                        Json(json!({ "result": true }))
+                    },
+                ),
+            )
+            .route(
+                "/collections/handbook/points",
+                axum::routing::put(|State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push(("upsert".to_string(), body));
+                    Json(json!({ "result": { "status": "completed" } }))
+                }),
+            )
+            .route(
+                "/collections/handbook/points/search",
+                post(|State(requests): State<Requests>, Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push(("search".to_string(), body));
+                    Json(json!({
+                        "result": [
+                            { "id": "a", "score": 0.8, "payload": { "content": "Shipping times", "filename": "faq.md" } },
+                            { "id": 7, "score": 0.7, "payload": { "content": "Our refund policy" } },
+                        ]
+                    }))
+                }),
+            )
+            .with_state(requests.clone());
+
+        let base_url = spawn_mock_server(router).await;
+        (QdrantProvider::new(Client::new(), base_url, Some("secret".to_string())), requests)
+    }
+
+    #[tokio::test]
+    async fn test_upsert_creates_missing_collection() {
+        let (provider, requests) = mock_qdrant().await;
+
+        provider
+            .upsert(
+                "handbook",
+                vec![VectorRecord {
+                    id: "a".to_string(),
+                    vector: vec![1.0, 0.0, 0.0],
+                    content: "Shipping times".to_string(),
+                    metadata: json!({ "filename": "faq.md" }),
+                }],
+            )
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "create");
+        assert_eq!(requests[0].1["vectors"]["size"], 3);
+        assert_eq!(requests[1].0, "upsert");
+        assert_eq!(requests[1].1["points"][0]["payload"]["content"], "Shipping times");
+        assert_eq!(requests[1].1["points"][0]["payload"]["filename"], "faq.md");
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_query_oversamples_and_reranks() {
+        let (provider, requests) = mock_qdrant().await;
+
+        let matches = provider
+            .query(
+                "handbook",
+                &VectorQuery {
+                    vector: vec![1.0, 0.0, 0.0],
+                    text: Some("refund policy".to_string()),
+                    top_k: 1,
+                    similarity_threshold: Some(0.3),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, "7");
+        assert_eq!(matches[0].content, "Our refund policy");
+
+        let (_, body) = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(body["limit"], 4);
+        assert!((body["score_threshold"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_list_collections() {
+        let (provider, _) = mock_qdrant().await;
+        assert_eq!(provider.list_collections().await.unwrap(), vec!["handbook"]);
+    }
+}
@@ -0,0 +1,123 @@
+use uuid::Uuid;
+
+use super::providers::is_vector_store_provider;
+use super::search::search_rag_database;
+use crate::database::{
+    models::RAGCitation,
+    queries::{rag_attachments::get_chat_rag_databases, rag_providers::get_rag_provider_by_id},
+};
+
+// Number of chunks injected into a chat prompt across all attached databases
+pub const DEFAULT_RETRIEVAL_TOP_K: usize = 5;
+
+/// Search the RAG databases attached to an assistant or project and return the best chunks,
+/// numbered in the order they are cited in the prompt.
+///
+/// A database that cannot be searched is skipped so that chat still works without its context.
+pub async fn retrieve_citations(
+    assistant_id: Uuid,
+    project_id: Option<Uuid>,
+    query: &str,
+    top_k: usize,
+) -> Result<Vec<RAGCitation>, Box<dyn std::error::Error + Send + Sync>> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let databases = get_chat_rag_databases(assistant_id, project_id).await?;
+    let mut citations = Vec::new();
+
+    for database in databases {
+        let provider = match get_rag_provider_by_id(database.provider_id).await? {
+            Some(provider) => provider,
+            None => continue,
+        };
+
+        // Local databases are searchable once started; vector stores are always searchable
+        let searchable = match provider.provider_type.as_str() {
+            "local" => database.is_active,
+            provider_type => is_vector_store_provider(provider_type),
+        };
+        if !searchable {
+            continue;
+        }
+
+        match search_rag_database(&provider, &database, query, top_k, None, None).await {
+            Ok(results) => {
+                citations.extend(results.into_iter().map(|result| RAGCitation {
+                    index: 0,
+                    chunk_id: result.chunk_id,
+                    rag_database_id: result.rag_database_id,
+                    rag_database_name: database.name.clone(),
+                    file_id: result.file_id,
+                    filename: result
+                        .metadata
+                        .get("filename")
+                        .and_then(|value| value.as_str())
+                        .map(str::to_string),
+                    content: result.content,
+                    score: result.score,
+                }));
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to search RAG database {}: {}", database.id, e);
+            }
+        }
+    }
+
+    citations.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    citations.truncate(top_k);
+    for (position, citation) in citations.iter_mut().enumerate() {
+        citation.index = position + 1;
+    }
+
+    Ok(citations)
+}
+
+/// Format retrieved chunks as a context block for the system prompt
+pub fn format_context_prompt(citations: &[RAGCitation]) -> String {
+    let mut prompt = String::from(
+        "Use the following retrieved context to answer the user's question when it is relevant. \
+         Cite the sources you use with their bracketed number, for example [1]. \
+         If the context does not contain the answer, say so instead of guessing.\n",
+    );
+
+    for citation in citations {
+        let source = citation
+            .filename
+            .as_deref()
+            .unwrap_or(citation.rag_database_name.as_str());
+        prompt.push_str(&format!("\n[{}] {}\n{}\n", citation.index, source, citation.content.trim()));
+    }
+
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(index: usize, filename: Option<&str>, content: &str) -> RAGCitation {
+        RAGCitation {
+            index,
+            chunk_id: Uuid::new_v4(),
+            rag_database_id: Uuid::new_v4(),
+            rag_database_name: "Handbook".to_string(),
+            file_id: None,
+            filename: filename.map(str::to_string),
+            content: content.to_string(),
+            score: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_format_context_prompt_numbers_sources() {
+        let prompt = format_context_prompt(&[
+            citation(1, Some("guide.pdf"), "First chunk\n"),
+            citation(2, None, "Second chunk"),
+        ]);
+
+        assert!(prompt.contains("\n[1] guide.pdf\nFirst chunk\n"));
+        assert!(prompt.contains("\n[2] Handbook\nSecond chunk\n"));
+    }
+}
@@ -0,0 +1,111 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::embedding::create_embedding_provider;
+use super::index::search_index;
+use super::providers::{apply_capabilities, collection_name, create_rag_provider, VectorQuery};
+use crate::database::{
+    models::{RAGDatabase, RAGProvider, RAGSearchResult},
+    queries::rag_chunks::get_rag_chunks_by_ids,
+};
+
+/// Embed a query and return the most similar chunks of a RAG database, from the in-memory
+/// index for local providers and from the vector store otherwise.
+///
+/// `filter` and `similarity_threshold` only apply to vector store databases that enable the
+/// matching capability; the threshold defaults to `similarity_threshold` in the database settings.
+pub async fn search_rag_database(
+    provider: &RAGProvider,
+    database: &RAGDatabase,
+    query: &str,
+    top_k: usize,
+    filter: Option<Map<String, Value>>,
+    similarity_threshold: Option<f32>,
+) -> Result<Vec<RAGSearchResult>, Box<dyn std::error::Error + Send + Sync>> {
+    let embedder = create_embedding_provider(database).await?;
+    let query_vector = embedder
+        .embed(&[query.to_string()])
+        .await?
+        .pop()
+        .ok_or("Embedding provider returned no vector for the query")?;
+
+    if provider.provider_type != "local" {
+        let similarity_threshold = similarity_threshold.or_else(|| {
+            database
+                .settings
+                .as_ref()
+                .and_then(|settings| settings.get("similarity_threshold"))
+                .and_then(|value| value.as_f64())
+                .map(|value| value as f32)
+        });
+        let vector_query = apply_capabilities(
+            VectorQuery {
+                vector: query_vector,
+                text: Some(query.to_string()),
+                top_k,
+                filter,
+                similarity_threshold,
+            },
+            database.capabilities.as_ref(),
+        )?;
+
+        let vector_store = create_rag_provider(provider)?;
+        let matches = vector_store.query(&collection_name(database), &vector_query).await?;
+
+        return Ok(matches
+            .into_iter()
+            .map(|m| {
+                let metadata_uuid = |key: &str| {
+                    m.metadata
+                        .get(key)
+                        .and_then(|value| value.as_str())
+                        .and_then(|value| Uuid::parse_str(value).ok())
+                };
+                RAGSearchResult {
+                    // Collections filled outside the app may use ids that are not chunk ids
+                    chunk_id: Uuid::parse_str(&m.id)
+                        .ok()
+                        .or_else(|| metadata_uuid("chunk_id"))
+                        .unwrap_or_else(Uuid::nil),
+                    rag_database_id: database.id,
+                    file_id: metadata_uuid("file_id"),
+                    content: m.content,
+                    metadata: m.metadata,
+                    score: m.score,
+                }
+            })
+            .collect());
+    }
+
+    if filter.is_some_and(|filter| !filter.is_empty()) {
+        return Err("Metadata filtering is not supported by local RAG databases".into());
+    }
+
+    let hits = search_index(database.id, &query_vector, top_k).await?;
+    if hits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let chunk_ids: Vec<_> = hits.iter().map(|(chunk_id, _)| *chunk_id).collect();
+    let mut chunks: HashMap<_, _> = get_rag_chunks_by_ids(&chunk_ids)
+        .await?
+        .into_iter()
+        .map(|chunk| (chunk.id, chunk))
+        .collect();
+
+    // Keep the similarity order from the index
+    Ok(hits
+        .into_iter()
+        .filter_map(|(chunk_id, score)| {
+            chunks.remove(&chunk_id).map(|chunk| RAGSearchResult {
+                chunk_id,
+                rag_database_id: chunk.rag_database_id,
+                file_id: chunk.file_id,
+                content: chunk.content,
+                metadata: chunk.metadata,
+                score,
+            })
+        })
+        .collect())
+}
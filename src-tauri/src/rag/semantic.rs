@@ -0,0 +1,397 @@
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use super::chunking::chunk_text;
+use super::embedding::{create_provider_embedding_provider, EmbeddingProvider};
+use super::index::LocalVectorIndex;
+use crate::database::{
+    models::{NewSemanticChunk, SemanticSearchHit},
+    queries::{
+        configuration::get_semantic_search_model_id,
+        models::{get_model_by_id, get_provider_by_model_id},
+        semantic_chunks,
+    },
+};
+use crate::FILE_STORAGE;
+
+// Chunking of message and file text, matching the defaults of RAG databases
+const CHUNK_SIZE: usize = 1000;
+const CHUNK_OVERLAP: usize = 200;
+// Number of chunks sent to the embedding provider per request
+const EMBEDDING_BATCH_SIZE: usize = 32;
+// Number of messages or files loaded per indexing pass
+const INDEX_BATCH_SIZE: i64 = 64;
+// Pending work is also picked up periodically, e.g. once a local embedding model is started
+const INDEX_INTERVAL: Duration = Duration::from_secs(300);
+// Chunks fetched per requested result, since several chunks can belong to one message or file
+const CANDIDATES_PER_RESULT: usize = 4;
+// Retry delays of a message or file that failed to index, doubling with each failure
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+// Failures in a row after which the provider is assumed to be down and the pass stops
+const MAX_CONSECUTIVE_FAILURES: usize = 5;
+
+static INDEXER_WAKEUP: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// A message or file that failed to index and is skipped until `retry_at`
+struct IndexFailure {
+    attempts: u32,
+    retry_at: Instant,
+}
+
+// Failed messages and files by id, so one bad item does not hold up the rest
+static INDEX_FAILURES: Lazy<Mutex<HashMap<Uuid, IndexFailure>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Ids of the messages and files still waiting to be retried
+fn backed_off_ids() -> Vec<Uuid> {
+    let now = Instant::now();
+    let failures = INDEX_FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    failures
+        .iter()
+        .filter(|(_, failure)| failure.retry_at > now)
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+fn retry_delay(attempts: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+        .min(MAX_RETRY_DELAY)
+}
+
+fn record_failure(id: Uuid) {
+    let mut failures = INDEX_FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let failure = failures.entry(id).or_insert(IndexFailure {
+        attempts: 0,
+        retry_at: Instant::now(),
+    });
+    failure.attempts += 1;
+    failure.retry_at = Instant::now() + retry_delay(failure.attempts);
+}
+
+fn clear_failure(id: Uuid) {
+    INDEX_FAILURES.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+}
+
+/// Wake the semantic indexer to embed new messages and files
+pub fn notify_semantic_indexer() {
+    INDEXER_WAKEUP.notify_one();
+}
+
+/// Embed messages and extracted file text in the background, whenever new content is saved
+/// and every few minutes
+pub fn start_semantic_index_task() {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = index_pending().await {
+                eprintln!("Semantic indexing failed: {}", e);
+            }
+            tokio::select! {
+                _ = INDEXER_WAKEUP.notified() => {}
+                _ = tokio::time::sleep(INDEX_INTERVAL) => {}
+            }
+        }
+    });
+    println!("Started background semantic index task");
+}
+
+/// Create the embedding provider of the configured semantic search model
+async fn semantic_embedder(
+    model_id: Uuid,
+) -> Result<Box<dyn EmbeddingProvider>, Box<dyn std::error::Error + Send + Sync>> {
+    let model = get_model_by_id(model_id)
+        .await?
+        .ok_or("Semantic search embedding model not found")?;
+    let provider = get_provider_by_model_id(model_id)
+        .await?
+        .ok_or("Semantic search embedding model provider not found")?;
+    create_provider_embedding_provider(&provider, model.name, Some(model_id)).await
+}
+
+/// Check that a model can embed text, so a chat-only model is refused when it is configured.
+/// Local models must be running.
+pub async fn check_embedding_model(model_id: Uuid) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let embedder = semantic_embedder(model_id).await?;
+    let vectors = embedder.embed(&["semantic search".to_string()]).await?;
+    if vectors.first().is_none_or(|vector| vector.is_empty()) {
+        return Err("Model returned no embedding".into());
+    }
+    Ok(())
+}
+
+/// Index every message and file that has no chunks for the configured model yet. A message or
+/// file that fails is logged and retried later with backoff while the others are indexed.
+/// Once everything is indexed, chunks of previously configured models are removed.
+async fn index_pending() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(model_id) = get_semantic_search_model_id().await? else {
+        semantic_chunks::delete_stale_chunks(None).await?;
+        return Ok(());
+    };
+
+    // Nothing to index means no provider needs to be reachable
+    let mut messages =
+        semantic_chunks::list_unindexed_messages(model_id, &backed_off_ids(), INDEX_BATCH_SIZE).await?;
+    let mut files = semantic_chunks::list_unindexed_files(model_id, &backed_off_ids(), INDEX_BATCH_SIZE).await?;
+    if messages.is_empty() && files.is_empty() {
+        remove_stale_chunks(model_id).await;
+        return Ok(());
+    }
+
+    let embedder = semantic_embedder(model_id).await?;
+    let mut consecutive_failures = 0;
+
+    while !messages.is_empty() {
+        let mut indexed = 0;
+        for message in &messages {
+            let text = message.content.as_deref().unwrap_or_default();
+            let result = match embed_text(embedder.as_ref(), text).await {
+                Ok(chunks) => semantic_chunks::replace_message_chunks(message.id, message.user_id, model_id, chunks)
+                    .await
+                    .map_err(Into::into),
+                Err(e) => Err(e),
+            };
+            if !track_result(message.id, "message", result, &mut consecutive_failures)? {
+                continue;
+            }
+            indexed += 1;
+        }
+        println!("Semantic index: embedded {} messages", indexed);
+        messages =
+            semantic_chunks::list_unindexed_messages(model_id, &backed_off_ids(), INDEX_BATCH_SIZE).await?;
+    }
+
+    while !files.is_empty() {
+        let mut indexed = 0;
+        for file in &files {
+            // Text extracted by the ProcessingManager when the file was uploaded.
+            // Files without text are still recorded, with no chunks.
+            let result = match FILE_STORAGE.read_text_content(file.id).await {
+                Ok(text) => match embed_text(embedder.as_ref(), &text.unwrap_or_default()).await {
+                    Ok(chunks) => semantic_chunks::replace_file_chunks(file.id, file.user_id, model_id, chunks)
+                        .await
+                        .map_err(Into::into),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e.into()),
+            };
+            if !track_result(file.id, "file", result, &mut consecutive_failures)? {
+                continue;
+            }
+            indexed += 1;
+        }
+        println!("Semantic index: embedded {} files", indexed);
+        files = semantic_chunks::list_unindexed_files(model_id, &backed_off_ids(), INDEX_BATCH_SIZE).await?;
+    }
+
+    remove_stale_chunks(model_id).await;
+    Ok(())
+}
+
+/// Record the outcome of indexing a message or file, returning whether it succeeded. Fails the
+/// pass when too many items fail in a row, since the provider is then most likely unavailable.
+fn track_result(
+    id: Uuid,
+    kind: &str,
+    result: Result<(), Box<dyn std::error::Error + Send + Sync>>,
+    consecutive_failures: &mut usize,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    match result {
+        Ok(()) => {
+            clear_failure(id);
+            *consecutive_failures = 0;
+            Ok(true)
+        }
+        Err(e) => {
+            eprintln!("Semantic index: failed to index {} {}: {}", kind, id, e);
+            record_failure(id);
+            *consecutive_failures += 1;
+            if *consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                return Err(format!("{} items failed in a row, last error: {}", consecutive_failures, e).into());
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Remove the chunks of models that are no longer configured
+async fn remove_stale_chunks(model_id: Uuid) {
+    match semantic_chunks::delete_stale_chunks(Some(model_id)).await {
+        Ok(0) => {}
+        Ok(deleted) => println!("Semantic index: removed {} chunks of previous embedding models", deleted),
+        Err(e) => eprintln!("Semantic index: failed to remove chunks of previous embedding models: {}", e),
+    }
+}
+
+/// Chunk and embed text, returning no chunks for blank text
+async fn embed_text(
+    embedder: &dyn EmbeddingProvider,
+    text: &str,
+) -> Result<Vec<NewSemanticChunk>, Box<dyn std::error::Error + Send + Sync>> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let chunks = chunk_text(text, CHUNK_SIZE, CHUNK_OVERLAP);
+    let mut new_chunks = Vec::with_capacity(chunks.len());
+    for (batch_index, batch) in chunks.chunks(EMBEDDING_BATCH_SIZE).enumerate() {
+        let texts: Vec<String> = batch.iter().map(|chunk| chunk.content.clone()).collect();
+        let vectors = embedder.embed(&texts).await?;
+
+        for (offset, (chunk, embedding)) in batch.iter().zip(vectors).enumerate() {
+            new_chunks.push(NewSemanticChunk {
+                chunk_index: (batch_index * EMBEDDING_BATCH_SIZE + offset) as i32,
+                content: chunk.content.clone(),
+                embedding,
+            });
+        }
+    }
+
+    Ok(new_chunks)
+}
+
+/// Whether a semantic search embedding model is configured
+pub async fn is_semantic_search_enabled() -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(get_semantic_search_model_id().await?.is_some())
+}
+
+/// Embed a query and return the user's messages and files most similar to it, one hit per
+/// message or file. Only content already indexed with the configured model is searched.
+pub async fn semantic_search(
+    user_id: Uuid,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SemanticSearchHit>, Box<dyn std::error::Error + Send + Sync>> {
+    let model_id = get_semantic_search_model_id()
+        .await?
+        .ok_or("Semantic search has no embedding model configured")?;
+
+    let vectors = semantic_chunks::list_user_vectors(user_id, model_id).await?;
+    if vectors.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let embedder = semantic_embedder(model_id).await?;
+    let query_vector = embedder
+        .embed(&[query.to_string()])
+        .await?
+        .pop()
+        .ok_or("Embedding provider returned no vector for the query")?;
+
+    let mut index = LocalVectorIndex::new();
+    for (chunk_id, vector) in &vectors {
+        index.add(*chunk_id, None, vector)?;
+    }
+    let matches = index.search(&query_vector, limit * CANDIDATES_PER_RESULT);
+    if matches.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let chunk_ids: Vec<_> = matches.iter().map(|(chunk_id, _)| *chunk_id).collect();
+    let hits = semantic_chunks::get_semantic_hits(user_id, &chunk_ids).await?;
+
+    Ok(rank_hits(matches, hits, limit))
+}
+
+/// Order hits by the similarity of their chunk, keeping the best chunk of each message or file.
+/// Chunks without a hit, e.g. of another user, are left out
+fn rank_hits(matches: Vec<(Uuid, f32)>, hits: Vec<SemanticSearchHit>, limit: usize) -> Vec<SemanticSearchHit> {
+    let mut hits: HashMap<_, _> = hits.into_iter().map(|hit| (hit.chunk_id, hit)).collect();
+    let mut seen = HashSet::new();
+    matches
+        .into_iter()
+        .filter_map(|(chunk_id, similarity)| {
+            hits.remove(&chunk_id).map(|hit| SemanticSearchHit { similarity, ..hit })
+        })
+        .filter(|hit| seen.insert((hit.message_id, hit.file_id)))
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Embeds each text as its length and counts the calls
+    struct LengthEmbedder {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for LengthEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+            assert!(texts.len() <= EMBEDDING_BATCH_SIZE);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(texts.iter().map(|text| vec![text.len() as f32, 1.0]).collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "length"
+        }
+    }
+
+    fn hit(message_id: Option<Uuid>, file_id: Option<Uuid>) -> SemanticSearchHit {
+        SemanticSearchHit {
+            chunk_id: Uuid::new_v4(),
+            similarity: 0.0,
+            content: String::new(),
+            conversation_id: None,
+            conversation_title: None,
+            message_id,
+            branch_id: None,
+            role: None,
+            file_id,
+            filename: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embed_text_numbers_chunks_across_batches() {
+        let embedder = LengthEmbedder {
+            calls: AtomicUsize::new(0),
+        };
+        let text = "lorem ipsum dolor sit amet. ".repeat(2000);
+
+        let chunks = embed_text(&embedder, &text).await.unwrap();
+        assert!(chunks.len() > EMBEDDING_BATCH_SIZE);
+        assert_eq!(embedder.calls.load(Ordering::SeqCst), chunks.len().div_ceil(EMBEDDING_BATCH_SIZE));
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.chunk_index, index as i32);
+            assert_eq!(chunk.embedding[0], chunk.content.len() as f32);
+        }
+
+        assert!(embed_text(&embedder, "  \n").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rank_hits_keeps_best_chunk_per_message_or_file() {
+        let message_id = Some(Uuid::new_v4());
+        let file_id = Some(Uuid::new_v4());
+        let message_best = hit(message_id, None);
+        let message_other = hit(message_id, None);
+        let file = hit(None, file_id);
+        let other_user_chunk = Uuid::new_v4();
+
+        let matches = vec![
+            (other_user_chunk, 0.95),
+            (message_best.chunk_id, 0.9),
+            (file.chunk_id, 0.8),
+            (message_other.chunk_id, 0.7),
+        ];
+        let ranked = rank_hits(matches.clone(), vec![message_other, file, message_best.clone()], 10);
+
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].chunk_id, message_best.chunk_id);
+        assert_eq!(ranked[0].similarity, 0.9);
+        assert_eq!(ranked[1].file_id, file_id);
+        assert_eq!(ranked[1].similarity, 0.8);
+
+        let ranked = rank_hits(matches, vec![message_best], 0);
+        assert!(ranked.is_empty());
+    }
+}
@@ -32,4 +32,14 @@ pub fn admin_assistant_routes() -> Router {
                 api::middleware::groups_delete_middleware,
             )),
         )
+        .route(
+            "/api/admin/assistants/{assistant_id}/rag-databases",
+            get(api::assistants::get_assistant_rag_databases_admin)
+                .layer(middleware::from_fn(api::middleware::groups_read_middleware)),
+        )
+        .route(
+            "/api/admin/assistants/{assistant_id}/rag-databases",
+            put(api::assistants::set_assistant_rag_databases_admin)
+                .layer(middleware::from_fn(api::middleware::groups_edit_middleware)),
+        )
 }
\ No newline at end of file
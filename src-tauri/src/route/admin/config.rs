@@ -41,4 +41,16 @@ pub fn admin_config_routes() -> Router {
                 api::middleware::config_proxy_edit_middleware,
             )),
         )
-}
\ No newline at end of file
+        .route(
+            "/api/admin/config/semantic-search",
+            get(api::configuration::get_semantic_search_settings).layer(middleware::from_fn(
+                api::middleware::config_semantic_search_read_middleware,
+            )),
+        )
+        .route(
+            "/api/admin/config/semantic-search",
+            put(api::configuration::update_semantic_search_settings).layer(middleware::from_fn(
+                api::middleware::config_semantic_search_edit_middleware,
+            )),
+        )
+}
@@ -7,6 +7,7 @@ pub mod providers;
 pub mod rag_providers;
 pub mod rag_repositories;
 pub mod repositories;
+pub mod usage;
 pub mod users;
 
 use axum::Router;
@@ -23,4 +24,5 @@ pub fn admin_routes() -> Router {
         .merge(rag_repositories::admin_rag_repository_routes())
         .merge(assistants::admin_assistant_routes())
         .merge(downloads::admin_download_routes())
+        .merge(usage::admin_usage_routes())
 }
\ No newline at end of file
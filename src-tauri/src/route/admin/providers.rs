@@ -47,6 +47,18 @@ pub fn admin_provider_routes() -> Router {
                 api::middleware::providers_read_middleware,
             )),
         )
+        .route(
+            "/api/admin/providers/{provider_id}/models/sync",
+            post(api::models::sync_provider_models).layer(middleware::from_fn(
+                api::middleware::providers_edit_middleware,
+            )),
+        )
+        .route(
+            "/api/admin/providers/{provider_id}/loaded-models",
+            get(api::models::list_loaded_models).layer(middleware::from_fn(
+                api::middleware::providers_read_middleware,
+            )),
+        )
         .route(
             "/api/admin/devices",
             get(api::providers::get_available_devices).layer(middleware::from_fn(
@@ -1,4 +1,5 @@
 use axum::{
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
@@ -11,6 +12,7 @@ use crate::api::rag_providers::{
     update_rag_provider,
     delete_rag_provider,
     clone_rag_provider,
+    list_rag_provider_collections,
     // RAG Database endpoints
     list_rag_provider_databases,
     add_database_to_rag_provider,
@@ -19,8 +21,15 @@ use crate::api::rag_providers::{
     delete_rag_database,
     start_rag_database,
     stop_rag_database,
+    get_rag_database_status,
     enable_rag_database,
     disable_rag_database,
+    // RAG Database index endpoints
+    list_rag_database_files,
+    ingest_rag_database_files,
+    delete_rag_database_file,
+    search_rag_database,
+    export_rag_database,
 };
 
 pub fn admin_rag_provider_routes() -> Router {
@@ -29,12 +38,46 @@ pub fn admin_rag_provider_routes() -> Router {
         .route("/api/admin/rag-providers", get(list_rag_providers).post(create_rag_provider))
         .route("/api/admin/rag-providers/{provider_id}", get(get_rag_provider).put(update_rag_provider).delete(delete_rag_provider))
         .route("/api/admin/rag-providers/{provider_id}/clone", post(clone_rag_provider))
+        .route("/api/admin/rag-providers/{provider_id}/collections", get(list_rag_provider_collections))
         
         // RAG Database routes
         .route("/api/admin/rag-providers/{provider_id}/databases", get(list_rag_provider_databases).post(add_database_to_rag_provider))
         .route("/api/admin/rag-databases/{database_id}", get(get_rag_database).put(update_rag_database).delete(delete_rag_database))
         .route("/api/admin/rag-databases/{database_id}/start", post(start_rag_database))
         .route("/api/admin/rag-databases/{database_id}/stop", post(stop_rag_database))
+        .route("/api/admin/rag-databases/{database_id}/status", get(get_rag_database_status))
         .route("/api/admin/rag-databases/{database_id}/enable", post(enable_rag_database))
         .route("/api/admin/rag-databases/{database_id}/disable", post(disable_rag_database))
-}
\ No newline at end of file
+
+        // RAG Database index routes, which expose the text of indexed files
+        .route(
+            "/api/admin/rag-databases/{database_id}/files",
+            get(list_rag_database_files).layer(middleware::from_fn(
+                crate::api::middleware::rag_databases_read_middleware,
+            )),
+        )
+        .route(
+            "/api/admin/rag-databases/{database_id}/files",
+            post(ingest_rag_database_files).layer(middleware::from_fn(
+                crate::api::middleware::rag_databases_edit_middleware,
+            )),
+        )
+        .route(
+            "/api/admin/rag-databases/{database_id}/files/{file_id}",
+            delete(delete_rag_database_file).layer(middleware::from_fn(
+                crate::api::middleware::rag_databases_edit_middleware,
+            )),
+        )
+        .route(
+            "/api/admin/rag-databases/{database_id}/search",
+            post(search_rag_database).layer(middleware::from_fn(
+                crate::api::middleware::rag_databases_read_middleware,
+            )),
+        )
+        .route(
+            "/api/admin/rag-databases/{database_id}/export",
+            get(export_rag_database).layer(middleware::from_fn(
+                crate::api::middleware::rag_databases_read_middleware,
+            )),
+        )
+}
@@ -0,0 +1,13 @@
+use crate::api;
+use axum::routing::get;
+use axum::{middleware, Router};
+
+pub fn admin_usage_routes() -> Router {
+    Router::new()
+        // Token usage aggregated per user, group, provider or model
+        .route(
+            "/api/admin/usage",
+            get(api::usage::get_usage_aggregates)
+                .layer(middleware::from_fn(api::middleware::usage_read_middleware)),
+        )
+}
@@ -34,16 +34,41 @@ pub fn chat_routes() -> Router {
             post(api::chat::send_message_stream)
                 .layer(middleware::from_fn(api::middleware::auth_middleware)),
         )
+        .route(
+            "/api/chat/messages/tool-results/stream",
+            post(api::chat::submit_tool_results_stream)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/chat/messages/compare/stream",
+            post(api::chat::compare_message_stream)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
         .route(
             "/api/chat/messages/{message_id}/stream",
             put(api::chat::edit_message_stream)
                 .layer(middleware::from_fn(api::middleware::auth_middleware)),
         )
+        .route(
+            "/api/chat/messages/{message_id}/regenerate",
+            post(api::chat::regenerate_message_stream)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/chat/messages/{message_id}/cancel",
+            post(api::chat::cancel_message_stream)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
         .route(
             "/api/chat/messages/{message_id}/branches",
             get(api::chat::get_message_branches)
                 .layer(middleware::from_fn(api::middleware::auth_middleware)),
         )
+        .route(
+            "/api/chat/messages/{message_id}/select",
+            post(api::chat::select_message_branch)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
         .route(
             "/api/chat/conversations/{conversation_id}/branch/switch",
             put(api::chat::switch_conversation_branch)
@@ -59,4 +84,14 @@ pub fn chat_routes() -> Router {
             get(api::chat::search_conversations)
                 .layer(middleware::from_fn(api::middleware::auth_middleware)),
         )
+        .route(
+            "/api/chat/conversations/semantic-search",
+            get(api::chat::semantic_search_conversations)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/embeddings",
+            post(api::embeddings::create_embeddings)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
 }
@@ -0,0 +1,11 @@
+use crate::api;
+use axum::routing::{get, post};
+use axum::{middleware, Router};
+
+pub fn gateway_routes() -> Router {
+    Router::new()
+        .route("/v1/models", get(api::gateway::list_models))
+        .route("/v1/chat/completions", post(api::gateway::chat_completions))
+        .route("/v1/embeddings", post(api::gateway::embeddings))
+        .layer(middleware::from_fn(api::middleware::auth_middleware))
+}
@@ -3,6 +3,7 @@ mod auth;
 mod chat;
 mod config;
 mod files;
+mod gateway;
 mod hub;
 mod projects;
 mod user;
@@ -34,10 +35,14 @@ pub fn create_rest_router() -> Router {
     // File routes (already have auth middleware applied individually)
     let file_routes = files::file_routes();
 
+    // OpenAI-compatible gateway routes (authenticate with personal API keys)
+    let gateway_routes = gateway::gateway_routes();
+
     // Combine public and protected routes
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
         .merge(file_routes)
+        .merge(gateway_routes)
         .layer(CorsLayer::permissive())
 }
@@ -39,4 +39,14 @@ pub fn project_routes() -> Router {
             delete(api::projects::unlink_conversation)
                 .layer(middleware::from_fn(api::middleware::auth_middleware)),
         )
+        .route(
+            "/api/projects/{project_id}/rag-databases",
+            get(api::projects::get_project_rag_databases)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/projects/{project_id}/rag-databases",
+            put(api::projects::set_project_rag_databases)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
 }
@@ -36,6 +36,28 @@ pub fn user_routes() -> Router {
                 api::middleware::settings_delete_middleware,
             )),
         )
+        // Personal API key routes
+        .route(
+            "/api/user/api-keys",
+            get(api::user_api_keys::list_api_keys)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/user/api-keys",
+            post(api::user_api_keys::create_api_key)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/user/api-keys/{key_id}",
+            delete(api::user_api_keys::revoke_api_key)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        // Token usage and quotas of the current user
+        .route(
+            "/api/user/usage",
+            get(api::usage::get_my_usage)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
         // Assistant routes - User endpoints
         .route(
             "/api/assistants",
@@ -62,6 +84,16 @@ pub fn user_routes() -> Router {
             delete(api::assistants::delete_assistant)
                 .layer(middleware::from_fn(api::middleware::auth_middleware)),
         )
+        .route(
+            "/api/assistants/{assistant_id}/rag-databases",
+            get(api::assistants::get_assistant_rag_databases)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
+        .route(
+            "/api/assistants/{assistant_id}/rag-databases",
+            put(api::assistants::set_assistant_rag_databases)
+                .layer(middleware::from_fn(api::middleware::auth_middleware)),
+        )
         .route(
             "/api/assistants/default",
             get(api::assistants::get_default_assistant)
@@ -7,50 +7,149 @@
 //! - Single message construction for specialized tasks
 //!
 //! All functions handle file attachments, assistant instructions, and conversation history
-//! according to the patterns established in the main chat API.
+//! according to the patterns established in the main chat API. The instruction and files of the
+//! conversation's project and chunks retrieved from RAG databases attached to the assistant or
+//! project are added to the system message. Files attached to earlier user messages are attached
+//! again or summarized as text, following the assistant's attachment policy; providers resolve
+//! attached files through their `provider_files` cache, so files are uploaded once.
 
 use uuid::Uuid;
 
 use crate::ai::{
-    core::{ChatMessage, ContentPart, MessageContent},
-    file_helpers::load_file_reference,
+    context_window::{AttachmentPolicy, ContextWindowSettings},
+    core::{ChatMessage, ContentPart, FileReference, MessageContent},
+    file_helpers::{format_file_size, load_file_reference, load_text_content},
+    usage::truncate_to_tokens,
 };
 use crate::api::chat::ChatMessageRequest;
 use crate::database::{
-    models::{Message, Assistant},
+    models::{
+        chat::{MESSAGE_TYPE_TOOL_CALL, MESSAGE_TYPE_TOOL_RESULT},
+        File, Message, Assistant, RAGCitation,
+    },
     queries::{
         assistants::get_assistant_by_id,
-        chat::get_conversation_messages,
+        chat::{
+            get_conversation_by_id, get_conversation_messages,
+            get_conversation_messages_by_branch,
+        },
     },
 };
+use crate::rag::{format_context_prompt, retrieve_citations, DEFAULT_RETRIEVAL_TOP_K};
+use crate::utils::project_context::build_project_context;
+
+/// Most tokens of a file's extracted text kept when an earlier attachment is summarized
+const ATTACHMENT_SUMMARY_TOKENS: u32 = 300;
+
+/// Messages for an AI provider request together with the RAG chunks cited in them
+pub struct ChatContext {
+    pub messages: Vec<ChatMessage>,
+    pub citations: Vec<RAGCitation>,
+}
 
-/// Build messages array for a chat request with conversation history and file attachments
+/// Build messages array for a chat request with conversation history and file attachments.
+/// The history is read from `branch_id`, or from the active branch when None.
+/// `user_message_id` is the request's message when it is already saved; it is left out of the
+/// history and added last with the request's files
 pub async fn build_chat_messages(
     request: &ChatMessageRequest,
     user_id: Uuid,
-) -> Result<Vec<ChatMessage>, Box<dyn std::error::Error + Send + Sync>> {
+    branch_id: Option<Uuid>,
+    user_message_id: Option<Uuid>,
+) -> Result<ChatContext, Box<dyn std::error::Error + Send + Sync>> {
     let mut messages = Vec::new();
+    let mut system_parts = Vec::new();
+
+    let assistant = get_assistant_by_id(request.assistant_id, Some(user_id))
+        .await
+        .ok()
+        .flatten();
+
+    // Add assistant instructions as system message if available; an override sent with the
+    // request replaces them
+    let instructions = match &request.system_prompt_override {
+        Some(system_prompt) => Some(system_prompt.clone()),
+        None => assistant
+            .as_ref()
+            .and_then(|assistant| assistant.instructions.clone()),
+    };
+    let context_window = assistant
+        .and_then(|assistant| assistant.context_window)
+        .unwrap_or_default();
+    if let Some(instructions) = instructions {
+        if !instructions.trim().is_empty() {
+            system_parts.push(instructions);
+        }
+    }
+
+    let project_id = match get_conversation_by_id(request.conversation_id, user_id).await {
+        Ok(conversation) => conversation.and_then(|conversation| conversation.project_id),
+        Err(e) => {
+            eprintln!("Warning: Failed to load conversation for retrieval: {}", e);
+            None
+        }
+    };
 
-    // Add assistant instructions as system message if available
-    if let Ok(Some(assistant)) = get_assistant_by_id(request.assistant_id, Some(user_id)).await {
-        if let Some(instructions) = assistant.instructions {
-            if !instructions.trim().is_empty() {
-                messages.push(ChatMessage {
-                    role: "system".to_string(),
-                    content: MessageContent::Text(instructions),
-                });
+    // The project's instruction and files come after the assistant's instructions
+    let mut project_file_ids = Vec::new();
+    if let Some(project_id) = project_id {
+        match build_project_context(project_id, user_id, &request.content).await {
+            Ok(project_context) => {
+                system_parts.extend(project_context.system_parts);
+                project_file_ids = project_context.file_ids;
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to load project context: {}", e);
             }
         }
     }
 
+    // Retrieve context from RAG databases attached to the assistant or the conversation's project
+    let citations = match retrieve_citations(
+        request.assistant_id,
+        project_id,
+        &request.content,
+        DEFAULT_RETRIEVAL_TOP_K,
+    )
+    .await
+    {
+        Ok(citations) => citations,
+        Err(e) => {
+            eprintln!("Warning: Failed to retrieve RAG context: {}", e);
+            // Continue without retrieved context rather than failing completely
+            Vec::new()
+        }
+    };
+    if !citations.is_empty() {
+        system_parts.push(format_context_prompt(&citations));
+    }
+
+    if !system_parts.is_empty() {
+        messages.push(ChatMessage::text("system", &system_parts.join("\n\n")));
+    }
+
+    // A continuation after tool results has no new user input
+    let has_files = request.file_ids.as_ref().is_some_and(|ids| !ids.is_empty());
+    let sends_user_message = !request.content.is_empty() || has_files;
+
     // Add conversation history
-    match get_conversation_messages(request.conversation_id, user_id).await {
-        Ok(conversation_messages) => {
-            for msg in conversation_messages {
-                messages.push(ChatMessage {
-                    role: msg.role,
-                    content: MessageContent::Text(msg.content),
-                });
+    let history = match branch_id {
+        Some(branch_id) => {
+            get_conversation_messages_by_branch(request.conversation_id, branch_id, user_id).await
+        }
+        None => get_conversation_messages(request.conversation_id, user_id).await,
+    };
+    match history {
+        Ok(mut conversation_messages) => {
+            // The request's message is saved before the answer is generated, and is added
+            // below with the request's files
+            if let Some(user_message_id) = user_message_id.filter(|_| sends_user_message) {
+                conversation_messages.retain(|msg| msg.id != user_message_id);
+            }
+
+            let resend_from = resend_attachments_from(&conversation_messages, &context_window);
+            for (index, msg) in conversation_messages.into_iter().enumerate() {
+                messages.push(history_message(msg, index >= resend_from).await);
             }
         }
         Err(e) => {
@@ -59,14 +158,119 @@ pub async fn build_chat_messages(
         }
     }
 
-    // Add the current user's message with potential file references
-    let user_message_content = build_user_message_content(request.content.clone(), request.file_ids.clone()).await?;
+    if !sends_user_message {
+        return Ok(ChatContext { messages, citations });
+    }
+
+    // Add the current user's message with potential file references, including the project's
+    // files that have no text to put in the system message
+    let mut file_ids = request.file_ids.clone().unwrap_or_default();
+    file_ids.extend(project_file_ids.into_iter().filter(|id| !file_ids.contains(id)));
+    let file_ids = if file_ids.is_empty() { None } else { Some(file_ids) };
+    let user_message_content = build_user_message_content(request.content.clone(), file_ids).await?;
     messages.push(ChatMessage {
         role: "user".to_string(),
         content: user_message_content,
+        tool_calls: None,
+        tool_call_id: None,
     });
 
-    Ok(messages)
+    Ok(ChatContext { messages, citations })
+}
+
+/// Convert a stored message into a ChatMessage, keeping tool call metadata and the files of
+/// user messages, attached again when `resend_files` is set and summarized otherwise
+async fn history_message(msg: Message, resend_files: bool) -> ChatMessage {
+    match msg.message_type.as_str() {
+        MESSAGE_TYPE_TOOL_CALL => {
+            ChatMessage::tool_calls(&msg.content, msg.tool_calls.unwrap_or_default())
+        }
+        MESSAGE_TYPE_TOOL_RESULT => {
+            ChatMessage::tool_result(msg.tool_call_id.as_deref().unwrap_or_default(), &msg.content)
+        }
+        _ if msg.role == "user" && !msg.files.is_empty() => ChatMessage {
+            role: msg.role,
+            content: history_attachments_content(msg.content, msg.files, resend_files).await,
+            tool_calls: None,
+            tool_call_id: None,
+        },
+        _ => ChatMessage::text(&msg.role, &msg.content),
+    }
+}
+
+/// Index of the first history message whose files are attached again; the files of earlier
+/// messages are summarized
+fn resend_attachments_from(history: &[Message], settings: &ContextWindowSettings) -> usize {
+    match settings.attachments {
+        AttachmentPolicy::Summarize => history.len(),
+        AttachmentPolicy::Resend => match settings.attachment_turns {
+            None => 0,
+            Some(turns) => history
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, msg)| msg.role == "user")
+                .take(turns as usize)
+                .last()
+                .map_or(history.len(), |(index, _)| index),
+        },
+    }
+}
+
+/// Content of an earlier user message with its files
+async fn history_attachments_content(
+    text: String,
+    files: Vec<File>,
+    resend_files: bool,
+) -> MessageContent {
+    if !resend_files {
+        let mut parts = vec![text];
+        for file in &files {
+            parts.push(attachment_summary(file).await);
+        }
+        return MessageContent::Text(parts.join("\n\n"));
+    }
+
+    let mut parts = vec![ContentPart::Text(text)];
+    parts.extend(files.into_iter().map(|file| {
+        ContentPart::FileReference(FileReference {
+            file_id: file.id,
+            filename: file.filename,
+            file_size: file.file_size,
+            mime_type: file.mime_type,
+            checksum: file.checksum,
+        })
+    }));
+    MessageContent::Multimodal(parts)
+}
+
+/// Text standing in for a file attached to an earlier message: its name, type and size, and
+/// the start of its extracted text
+async fn attachment_summary(file: &File) -> String {
+    let mut summary = format!(
+        "[Attached earlier: {} ({}, {})]",
+        file.filename,
+        file.mime_type.as_deref().unwrap_or("unknown type"),
+        format_file_size(file.file_size)
+    );
+
+    match load_text_content(file.id).await {
+        Ok(Some(text)) if !text.trim().is_empty() => {
+            let text = text.trim();
+            let excerpt = truncate_to_tokens(text, ATTACHMENT_SUMMARY_TOKENS);
+            summary.push('\n');
+            summary.push_str(&excerpt);
+            if excerpt.len() < text.len() {
+                summary.push_str("\n[...]");
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Warning: Failed to load text of file {}: {}", file.id, e);
+        }
+    }
+
+    summary
 }
 
 /// Build MessageContent for user messages, handling text + file attachments
@@ -102,8 +306,5 @@ pub async fn build_user_message_content(
 
 /// Create a single user message (useful for simple requests like title generation)
 pub fn build_single_user_message(content: String) -> Vec<ChatMessage> {
-    vec![ChatMessage {
-        role: "user".to_string(),
-        content: MessageContent::Text(content),
-    }]
+    vec![ChatMessage::text("user", &content)]
 }
\ No newline at end of file
@@ -9,5 +9,8 @@ pub mod model_storage;
 pub mod pandoc;
 pub mod password;
 pub mod pdfium;
+pub mod project_context;
 pub mod proxy;
 pub mod resource_paths;
+#[cfg(test)]
+pub mod test_helpers;
@@ -0,0 +1,303 @@
+//! Project instructions and files in the context of a project's conversations
+//!
+//! The project instruction follows the assistant's instructions in the system prompt. Files
+//! with extracted text are added after it within the project's token budget: whole, newest
+//! first, or as the chunks that best match the user's message. Files without extracted text,
+//! such as images, are attached to the user's message as file references when files are
+//! included whole.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::ai::file_helpers::load_text_content;
+use crate::ai::usage::{estimate_tokens, truncate_to_tokens};
+use crate::database::{
+    models::PROJECT_FILE_CONTEXT_RETRIEVE,
+    queries::{files::get_files_by_project, get_database_pool, projects::get_project_by_id},
+};
+use crate::rag::chunk_text;
+
+/// Most project files considered for one request
+const MAX_PROJECT_CONTEXT_FILES: i32 = 100;
+
+// Chunking of project files in retrieve mode, in characters
+const PROJECT_CHUNK_SIZE: usize = 1500;
+const PROJECT_CHUNK_OVERLAP: usize = 150;
+
+/// What a project adds to a request
+#[derive(Debug, Default)]
+pub struct ProjectContext {
+    /// Project instruction and file text, in system prompt order
+    pub system_parts: Vec<String>,
+    /// Files without extracted text, attached to the user's message
+    pub file_ids: Vec<Uuid>,
+}
+
+// Most project files kept in the document cache
+const MAX_CACHED_DOCUMENTS: usize = 500;
+
+/// A project file's extracted text, chunked on first use in retrieve mode
+struct ProjectDocument {
+    filename: String,
+    text: String,
+    chunks: OnceLock<Vec<IndexedChunk>>,
+}
+
+/// A chunk of a project file with the counts of its terms
+struct IndexedChunk {
+    content: String,
+    term_counts: HashMap<String, usize>,
+}
+
+impl ProjectDocument {
+    fn new(filename: String, text: String) -> Self {
+        Self {
+            filename,
+            text,
+            chunks: OnceLock::new(),
+        }
+    }
+
+    fn chunks(&self) -> &[IndexedChunk] {
+        self.chunks.get_or_init(|| {
+            chunk_text(&self.text, PROJECT_CHUNK_SIZE, PROJECT_CHUNK_OVERLAP)
+                .into_iter()
+                .map(|chunk| {
+                    let mut term_counts = HashMap::new();
+                    for term in terms(&chunk.content) {
+                        *term_counts.entry(term).or_insert(0) += 1;
+                    }
+                    IndexedChunk {
+                        content: chunk.content,
+                        term_counts,
+                    }
+                })
+                .collect()
+        })
+    }
+}
+
+/// A cached project file; `document` is None for files without extracted text
+struct CachedFile {
+    updated_at: DateTime<Utc>,
+    document: Option<Arc<ProjectDocument>>,
+}
+
+// Project files keyed by file id, so their text is not reloaded and re-chunked on every request.
+// An entry is replaced when the file's updated_at changes.
+static PROJECT_DOCUMENTS: Lazy<RwLock<HashMap<Uuid, CachedFile>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Load a project file's text, from the cache when the file is unchanged
+async fn load_project_document(
+    file_id: Uuid,
+    filename: &str,
+    updated_at: DateTime<Utc>,
+) -> Result<Option<Arc<ProjectDocument>>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(cached) = PROJECT_DOCUMENTS.read().await.get(&file_id) {
+        if cached.updated_at == updated_at {
+            return Ok(cached.document.clone());
+        }
+    }
+
+    let document = load_text_content(file_id)
+        .await?
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| Arc::new(ProjectDocument::new(filename.to_string(), text)));
+
+    let mut cache = PROJECT_DOCUMENTS.write().await;
+    if cache.len() >= MAX_CACHED_DOCUMENTS && !cache.contains_key(&file_id) {
+        cache.clear();
+    }
+    cache.insert(
+        file_id,
+        CachedFile {
+            updated_at,
+            document: document.clone(),
+        },
+    );
+
+    Ok(document)
+}
+
+/// Load the instruction and files of a project for a request with the given user message
+pub async fn build_project_context(
+    project_id: Uuid,
+    user_id: Uuid,
+    query: &str,
+) -> Result<ProjectContext, Box<dyn std::error::Error + Send + Sync>> {
+    let pool = get_database_pool()?;
+    let Some(project) = get_project_by_id(&pool, project_id, user_id).await? else {
+        return Ok(ProjectContext::default());
+    };
+
+    let mut context = ProjectContext::default();
+    if let Some(instruction) = project.instruction.as_ref().filter(|i| !i.trim().is_empty()) {
+        context.system_parts.push(instruction.clone());
+    }
+
+    if project.file_context_budget <= 0 {
+        return Ok(context);
+    }
+
+    let (files, _) = get_files_by_project(project_id, user_id, 1, MAX_PROJECT_CONTEXT_FILES).await?;
+    let mut documents = Vec::new();
+    for file in files {
+        match load_project_document(file.id, &file.filename, file.updated_at).await {
+            Ok(Some(document)) => documents.push(document),
+            Ok(None) => context.file_ids.push(file.id),
+            Err(e) => {
+                eprintln!("Warning: Failed to load text of project file {}: {}", file.id, e);
+            }
+        }
+    }
+
+    let budget = project.file_context_budget as u32;
+    let sections = if project.file_context_mode == PROJECT_FILE_CONTEXT_RETRIEVE {
+        // Only text can be matched against the message
+        context.file_ids.clear();
+        relevant_chunks(&documents, query, budget)
+    } else {
+        whole_files(&documents, budget)
+    };
+    if !sections.is_empty() {
+        context.system_parts.push(format!(
+            "The following files belong to the project of this conversation. Use them when they \
+             are relevant to the user's request.\n\n{}",
+            sections.join("\n\n")
+        ));
+    }
+
+    Ok(context)
+}
+
+/// Files in order until the budget is spent; the file that does not fit is cut short
+fn whole_files(documents: &[Arc<ProjectDocument>], budget: u32) -> Vec<String> {
+    let mut sections = Vec::new();
+    let mut remaining = budget;
+
+    for document in documents {
+        let header = format!("File: {}\n", document.filename);
+        let header_tokens = estimate_tokens(&header);
+        if remaining <= header_tokens {
+            break;
+        }
+
+        let text = truncate_to_tokens(document.text.trim(), remaining - header_tokens);
+        if text.is_empty() {
+            break;
+        }
+        remaining = remaining.saturating_sub(header_tokens + estimate_tokens(&text));
+        sections.push(format!("{}{}", header, text));
+    }
+
+    sections
+}
+
+/// The chunks sharing the most terms with the query, best first, until the budget is spent.
+/// Terms are weighted by how few chunks contain them
+fn relevant_chunks(documents: &[Arc<ProjectDocument>], query: &str, budget: u32) -> Vec<String> {
+    let query_terms: HashSet<String> = terms(query).collect();
+    if query_terms.is_empty() {
+        return Vec::new();
+    }
+
+    let chunks: Vec<(&str, &IndexedChunk)> = documents
+        .iter()
+        .flat_map(|document| document.chunks().iter().map(|chunk| (document.filename.as_str(), chunk)))
+        .collect();
+
+    // Number of chunks containing each query term
+    let document_frequency: HashMap<&str, f32> = query_terms
+        .iter()
+        .map(|term| {
+            let containing = chunks
+                .iter()
+                .filter(|(_, chunk)| chunk.term_counts.contains_key(term))
+                .count();
+            (term.as_str(), containing as f32)
+        })
+        .collect();
+
+    let total = chunks.len() as f32;
+    let mut scored: Vec<(usize, f32)> = chunks
+        .iter()
+        .enumerate()
+        .map(|(index, (_, chunk))| {
+            let score = query_terms
+                .iter()
+                .filter_map(|term| chunk.term_counts.get(term).map(|count| (term, *count)))
+                .map(|(term, count)| {
+                    let containing = document_frequency[term.as_str()];
+                    (1.0 + (count as f32).ln()) * (1.0 + total / containing).ln()
+                })
+                .sum();
+            (index, score)
+        })
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut sections = Vec::new();
+    let mut remaining = budget;
+    for (index, _) in scored {
+        let (filename, chunk) = chunks[index];
+        let section = format!("File: {}\n{}", filename, chunk.content);
+        let tokens = estimate_tokens(&section);
+        if tokens > remaining {
+            continue;
+        }
+        remaining -= tokens;
+        sections.push(section);
+    }
+
+    sections
+}
+
+/// Lowercased words of two or more characters
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(filename: &str, text: &str) -> Arc<ProjectDocument> {
+        Arc::new(ProjectDocument::new(filename.to_string(), text.to_string()))
+    }
+
+    #[test]
+    fn test_whole_files_stop_at_budget() {
+        let documents = [
+            document("a.txt", &"alpha ".repeat(50)),
+            document("b.txt", &"beta ".repeat(500)),
+            document("c.txt", "gamma"),
+        ];
+
+        let sections = whole_files(&documents, 200);
+        assert_eq!(sections.len(), 2);
+        assert!(sections[0].starts_with("File: a.txt\n"));
+        assert!(sections[1].starts_with("File: b.txt\n"));
+        let used: u32 = sections.iter().map(|section| estimate_tokens(section)).sum();
+        assert!(used <= 200);
+    }
+
+    #[test]
+    fn test_relevant_chunks_rank_matching_text_first() {
+        let documents = [
+            document("menu.txt", "The cafeteria serves soup on Mondays."),
+            document("policy.txt", "Vacation requests need manager approval two weeks ahead."),
+        ];
+
+        let sections = relevant_chunks(&documents, "How do I request vacation?", 1000);
+        assert_eq!(sections.len(), 1);
+        assert!(sections[0].starts_with("File: policy.txt\n"));
+        assert!(relevant_chunks(&documents, "?", 1000).is_empty());
+    }
+}
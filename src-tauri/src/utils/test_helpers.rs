@@ -0,0 +1,11 @@
+//! Helpers shared by tests across modules
+
+/// Serve a router on a random local port, returning its base URL
+pub async fn spawn_mock_server(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", address)
+}
@@ -8,6 +8,7 @@ import { useEffect, useState } from 'react'
 import { useTranslation } from 'react-i18next'
 import { useParams } from 'react-router-dom'
 import { isDesktopApp } from '../../../../api/core'
+import { PROVIDERS_WITHOUT_API_KEY } from '../../../../constants/providers'
 import { Permission, usePermissions } from '../../../../permissions'
 import {
   clearProvidersError,
@@ -57,7 +58,11 @@ export function RemoteProviderSettings() {
     const providerModels = provider.id === provider_id ? models : []
     if (providerModels.length === 0) return false
     if (provider.type === 'local') return true
-    if (!provider.api_key || provider.api_key.trim() === '') return false
+    if (
+      !PROVIDERS_WITHOUT_API_KEY.includes(provider.type) &&
+      (!provider.api_key || provider.api_key.trim() === '')
+    )
+      return false
     if (!provider.base_url || provider.base_url.trim() === '') return false
     try {
       new globalThis.URL(provider.base_url)
@@ -73,7 +78,10 @@ export function RemoteProviderSettings() {
     if (providerModels.length === 0)
       return 'No models available. Add at least one model first.'
     if (provider.type === 'local') return null
-    if (!provider.api_key || provider.api_key.trim() === '')
+    if (
+      !PROVIDERS_WITHOUT_API_KEY.includes(provider.type) &&
+      (!provider.api_key || provider.api_key.trim() === '')
+    )
       return 'API key is required'
     if (!provider.base_url || provider.base_url.trim() === '')
       return 'Base URL is required'
@@ -139,6 +147,7 @@ export function RemoteProviderSettings() {
             )
           } else if (
             currentProvider.type !== 'local' &&
+            !PROVIDERS_WITHOUT_API_KEY.includes(currentProvider.type) &&
             (!currentProvider.api_key || currentProvider.api_key.trim() === '')
           ) {
             message.error(
@@ -12,6 +12,7 @@ const RAG_PROVIDER_OPTIONS = [
   { value: 'lightrag', label: '🔍 LightRAG' },
   { value: 'ragstack', label: '📚 RAGStack' },
   { value: 'chroma', label: '🌈 ChromaDB' },
+  { value: 'qdrant', label: '🎯 Qdrant' },
   { value: 'weaviate', label: '🕷️ Weaviate' },
   { value: 'pinecone', label: '🌲 Pinecone' },
   { value: 'custom', label: '🔧 Custom' },
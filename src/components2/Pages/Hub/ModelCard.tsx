@@ -155,6 +155,7 @@ export function ModelCard({ model }: ModelCardProps) {
         capabilities: model.capabilities || {},
         parameters: model.recommended_parameters || {},
         settings: {}, // Empty settings for now
+        context_length: model.context_length,
       }
 
       // Start the download
@@ -32,9 +32,14 @@ export const SUPPORTED_PROVIDERS: ProviderOption[] = [
   { value: 'groq', label: 'Groq' },
   { value: 'gemini', label: 'Gemini' },
   { value: 'mistral', label: 'Mistral' },
+  { value: 'ollama', label: 'Ollama' },
+  { value: 'llamacpp', label: 'llama.cpp' },
   { value: 'custom', label: 'Custom' },
 ]
 
+// Self-hosted servers that usually run without authentication
+export const PROVIDERS_WITHOUT_API_KEY: ProviderType[] = ['ollama', 'llamacpp']
+
 export const PROVIDER_DEFAULTS: Record<ProviderType, ProviderDefaults> = {
   openai: {
     base_url: 'https://api.openai.com/v1',
@@ -51,6 +56,12 @@ export const PROVIDER_DEFAULTS: Record<ProviderType, ProviderDefaults> = {
   mistral: {
     base_url: 'https://api.mistral.ai',
   },
+  ollama: {
+    base_url: 'http://localhost:11434',
+  },
+  llamacpp: {
+    base_url: 'http://localhost:8080',
+  },
   local: {
     settings: {
       device: 'cpu',
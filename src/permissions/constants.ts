@@ -45,6 +45,12 @@ export const PermissionDescription = {
   'config::proxy::*': 'Grants all proxy configuration permissions',
   'config::proxy::read': 'Allows viewing proxy settings',
   'config::proxy::edit': 'Allows configuring proxy settings',
+  'config::semantic-search::*':
+    'Grants all semantic search configuration permissions',
+  'config::semantic-search::read':
+    'Allows viewing the semantic search embedding model',
+  'config::semantic-search::edit':
+    'Allows choosing the embedding model used for semantic search',
   // Model provider permissions
   'config::providers::*': 'Grants all model provider configuration permissions',
   'config::providers::read': 'Allows viewing model provider settings',
@@ -56,6 +62,12 @@ export const PermissionDescription = {
   'config::repositories::read': 'Allows viewing model repository settings',
   'config::repositories::edit':
     'Allows configuring model repository access tokens and settings',
+  // RAG database index permissions
+  'config::rag-databases::*': 'Grants all RAG database index permissions',
+  'config::rag-databases::read':
+    'Allows listing, searching and exporting the files indexed in RAG databases',
+  'config::rag-databases::edit':
+    'Allows ingesting own files into RAG databases and removing indexed files',
   // Assistants permissions
   'config::assistants::*': 'Grants all assistants configuration permissions',
   'config::assistants::read': 'Allows viewing assistants settings',
@@ -68,6 +80,9 @@ export const PermissionDescription = {
     'Allows viewing document extraction settings',
   'config::document-extraction::edit':
     'Allows configuring document extraction methods and settings',
+  // Usage permissions
+  'usage::*': 'Grants all token usage permissions',
+  'usage::read': 'Allows viewing token usage of all users, groups and models',
   // Chat permissions
   'chat::use': 'Allows using chat functionality',
   // Profile permissions
@@ -2,12 +2,39 @@
  * Assistant API types - matching backend structure
  */
 
+import type { FallbackTarget } from './model'
+
+// How history that does not fit the model's context window is left out
+export type ContextStrategy = 'drop' | 'sliding_window' | 'summary'
+
+// How files attached to earlier messages are sent with later requests
+export type AttachmentPolicy = 'resend' | 'summarize'
+
+export interface ContextWindowSettings {
+  strategy?: ContextStrategy
+  window_turns?: number
+  summary_model_id?: string
+  attachments?: AttachmentPolicy
+  attachment_turns?: number
+}
+
+// JSON Schema the assistant's answers must match
+export interface ResponseFormat {
+  name: string
+  description?: string
+  schema: Record<string, any>
+  strict?: boolean
+}
+
 export interface Assistant {
   id: string
   name: string
   description?: string
   instructions?: string
   parameters?: Record<string, any>
+  fallback_chain: FallbackTarget[]
+  response_format?: ResponseFormat
+  context_window?: ContextWindowSettings
   created_by?: string
   is_template: boolean
   is_default: boolean
@@ -21,6 +48,9 @@ export interface CreateAssistantRequest {
   description?: string
   instructions?: string
   parameters?: Record<string, any>
+  fallback_chain?: FallbackTarget[]
+  response_format?: ResponseFormat
+  context_window?: ContextWindowSettings
   is_template?: boolean
   is_default?: boolean
 }
@@ -30,6 +60,9 @@ export interface UpdateAssistantRequest {
   description?: string
   instructions?: string
   parameters?: Record<string, any>
+  fallback_chain?: FallbackTarget[]
+  response_format?: ResponseFormat
+  context_window?: ContextWindowSettings
   is_template?: boolean
   is_default?: boolean
   is_active?: boolean
@@ -2,6 +2,8 @@
  * Chat API types - matching backend structure
  */
 import { File } from './files.ts'
+import type { ContextStrategy, ResponseFormat } from './assistant'
+import type { ModelParameters } from './model'
 
 export interface Conversation {
   id: string
@@ -20,6 +22,7 @@ export interface Message {
   conversation_id: string
   role: 'user' | 'assistant' | 'system'
   content: string
+  reasoning?: string // Model thinking, streamed as 'thinking' events
   originated_from_id: string
   edit_count: number
   created_at: string
@@ -29,6 +32,10 @@ export interface Message {
     value: any
   }>
   files: File[]
+  provider_id?: string // Provider that generated the message, after any failover
+  model_id?: string
+  parameters?: ModelParameters // Parameters the answer was generated with
+  system_prompt_override?: string
 }
 
 export interface Branch {
@@ -63,6 +70,52 @@ export interface SendMessageRequest {
   model_id: string
   assistant_id: string
   file_ids?: string[]
+  response_format?: ResponseFormat // Replaces the assistant's format for this message
+  parameters?: ModelParameters // Layered over the model's, assistant's and project's
+  system_prompt_override?: string // Replaces the assistant's instructions
+}
+
+// All fields default to those of the answer being regenerated
+export interface RegenerateMessageRequest {
+  model_id?: string
+  assistant_id?: string
+  parameters?: ModelParameters
+  system_prompt_override?: string
+}
+
+export interface CompareTarget {
+  model_id: string
+  assistant_id: string
+  parameters?: ModelParameters
+  system_prompt_override?: string
+}
+
+// Each target is answered on its own branch; events carry the target's index as the SSE id
+export interface CompareMessageRequest {
+  conversation_id: string
+  content: string
+  file_ids?: string[]
+  targets: CompareTarget[]
+  response_format?: ResponseFormat
+}
+
+export interface CompareStream {
+  stream_id: string
+  branch_id: string
+  message_id: string
+  model_id: string
+  assistant_id: string
+}
+
+export interface CompareStartEvent {
+  conversation_id: string
+  user_message_id: string
+  streams: CompareStream[]
+}
+
+export interface SelectMessageBranchResponse {
+  success: boolean
+  branch_id: string
 }
 
 export interface SwitchBranchRequest {
@@ -94,6 +147,39 @@ export interface ConversationSummary {
   message_count: number
 }
 
+// A conversation matching a search, with its best match
+export interface ConversationSearchResult extends ConversationSummary {
+  rank: number
+  snippet: string // Matched terms are wrapped in <mark> tags
+  message_id?: string // Unset when the title matched best
+  branch_id?: string
+  role?: string
+}
+
+export interface ConversationSearchResponse {
+  conversations: ConversationSearchResult[]
+  total: number
+  page: number
+  per_page: number
+}
+
+// A message or file matching a semantic search, most similar first
+export interface SemanticSearchHit {
+  similarity: number
+  content: string // Text of the matching chunk
+  conversation_id?: string
+  conversation_title?: string
+  message_id?: string
+  branch_id?: string
+  role?: string
+  file_id?: string // Set when the match is in the text of a file
+  filename?: string
+}
+
+export interface SemanticSearchResponse {
+  results: SemanticSearchHit[]
+}
+
 export interface ChatResponse {
   message: Message
   conversation: Conversation
@@ -108,4 +194,14 @@ export interface StreamCompleteData {
   created_at: string
   updated_at: string
   total_tokens?: number
+  context?: ContextWindowReport
+}
+
+// History left out of the prompt to fit the model's context window
+export interface ContextWindowReport {
+  strategy: ContextStrategy
+  context_length: number
+  estimated_prompt_tokens: number
+  dropped_messages: number
+  summarized_messages: number
 }
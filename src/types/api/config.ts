@@ -7,6 +7,10 @@ export interface UpdateUserRegistrationRequest {
   enabled: boolean
 }
 
+export interface SemanticSearchSettings {
+  embedding_model_id?: string | null
+}
+
 export interface ProxySettingsResponse {
   enabled: boolean
   url: string
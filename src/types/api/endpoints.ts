@@ -13,15 +13,18 @@ import { AuthResponse, InitResponse, LoginRequest } from './auth'
 import {
   Conversation,
   ConversationListResponse,
+  ConversationSearchResponse,
   CreateConversationRequest,
   Message,
   MessageBranch,
+  SemanticSearchResponse,
   SendMessageRequest,
   SwitchBranchRequest,
   UpdateConversationRequest,
 } from './chat'
 import {
   ProxySettingsResponse,
+  SemanticSearchSettings,
   TestProxyConnectionRequest,
   TestProxyConnectionResponse,
   UpdateProxySettingsRequest,
@@ -169,6 +172,8 @@ export const ApiEndpoints = {
   'Chat.switchConversationBranch':
     'PUT /api/chat/conversations/{conversation_id}/branch/switch',
   'Chat.searchConversations': 'GET /api/chat/conversations/search',
+  'Chat.semanticSearchConversations':
+    'GET /api/chat/conversations/semantic-search',
 
   // Project Management
   'Projects.list': 'GET /api/projects',
@@ -285,6 +290,9 @@ export const ApiEndpoints = {
   'Admin.updateDefaultLanguage': 'PUT /api/admin/config/default-language',
   'Admin.getProxySettings': 'GET /api/admin/config/proxy',
   'Admin.updateProxySettings': 'PUT /api/admin/config/proxy',
+  'Admin.getSemanticSearchSettings': 'GET /api/admin/config/semantic-search',
+  'Admin.updateSemanticSearchSettings':
+    'PUT /api/admin/config/semantic-search',
 
   // ===========================
   // RAG PROVIDER MANAGEMENT
@@ -377,6 +385,8 @@ export type ApiEndpointParameters = {
   'Admin.updateDefaultLanguage': UpdateDefaultLanguageRequest
   'Admin.getProxySettings': void
   'Admin.updateProxySettings': UpdateProxySettingsRequest
+  'Admin.getSemanticSearchSettings': void
+  'Admin.updateSemanticSearchSettings': SemanticSearchSettings
 
   'Utils.testProxy': TestProxyConnectionRequest
   // User settings management
@@ -446,6 +456,15 @@ export type ApiEndpointParameters = {
     page?: number
     per_page?: number
     project_id?: string
+    from?: string
+    to?: string
+    assistant_id?: string
+    model_id?: string
+    role?: string
+  }
+  'Chat.semanticSearchConversations': {
+    q: string
+    limit?: number
   }
   // Project endpoints
   'Projects.list': ProjectListParams
@@ -594,6 +613,8 @@ export type ApiEndpointResponses = {
   'Admin.updateDefaultLanguage': DefaultLanguageResponse
   'Admin.getProxySettings': ProxySettingsResponse
   'Admin.updateProxySettings': ProxySettingsResponse
+  'Admin.getSemanticSearchSettings': SemanticSearchSettings
+  'Admin.updateSemanticSearchSettings': SemanticSearchSettings
   // Document extraction configuration
   'Utils.testProxy': TestProxyConnectionResponse
   // User settings management
@@ -645,7 +666,8 @@ export type ApiEndpointResponses = {
   'Chat.getMessageBranches': MessageBranch[]
   'Chat.getConversationMessages': Message[]
   'Chat.switchConversationBranch': { success: boolean; message: string }
-  'Chat.searchConversations': ConversationListResponse
+  'Chat.searchConversations': ConversationSearchResponse
+  'Chat.semanticSearchConversations': SemanticSearchResponse
   // Project endpoints
   'Projects.list': ProjectListResponse
   'Projects.create': Project
@@ -29,6 +29,10 @@ export interface ModelParameters {
   // Generation control
   seed?: number // Random seed for reproducible outputs
   stop?: string[] // Stop sequences to terminate generation
+
+  // Reasoning control
+  enable_thinking?: boolean // Defaults to on when a thinking budget is set
+  thinking_budget?: number // Tokens the model may spend reasoning
 }
 
 export interface ModelSettings {
@@ -74,6 +78,12 @@ export interface ModelFileInfo {
   uploaded_at: string
 }
 
+// A (provider, model) pair tried when the previous one fails with a retryable error
+export interface FallbackTarget {
+  provider_id: string
+  model_id: string
+}
+
 export interface Model {
   id: string
   provider_id: string
@@ -85,6 +95,8 @@ export interface Model {
   is_active: boolean
   capabilities?: ModelCapabilities
   parameters?: ModelParameters
+  fallback_chain: FallbackTarget[]
+  context_length?: number // Context window of the model, in tokens
   created_at: string
   updated_at: string
   // Additional fields for Candle models (undefined for other providers)
@@ -105,6 +117,8 @@ export interface CreateModelRequest {
   enabled?: boolean
   capabilities?: ModelCapabilities
   settings?: ModelSettings
+  fallback_chain?: FallbackTarget[]
+  context_length?: number
 }
 
 export interface UpdateModelRequest {
@@ -116,6 +130,8 @@ export interface UpdateModelRequest {
   capabilities?: ModelCapabilities
   parameters?: ModelParameters
   settings?: ModelSettings
+  fallback_chain?: FallbackTarget[]
+  context_length?: number // 0 clears the context length
 }
 
 export interface ModelFile {
@@ -221,3 +237,16 @@ export interface ModelUploadStatus {
   validation_status?: string
   validation_issues?: string[]
 }
+
+// Embeddings
+export interface EmbeddingsRequest {
+  model_id: string
+  input: string | string[]
+}
+
+export interface EmbeddingsResponse {
+  model_id: string
+  embeddings: number[][]
+  dimensions: number
+  prompt_tokens: number
+}
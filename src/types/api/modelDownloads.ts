@@ -31,6 +31,7 @@ export interface DownloadFromRepositoryRequest {
   capabilities?: ModelCapabilities
   parameters?: ModelParameters
   settings?: ModelSettings
+  context_length?: number
 }
 
 export interface DownloadRequestData {
@@ -1,9 +1,17 @@
+import type { ModelParameters } from './model'
+
+// 'always' includes whole files newest first, 'retrieve' only the chunks matching each message
+export type ProjectFileContextMode = 'always' | 'retrieve'
+
 export interface Project {
   id: string
   user_id: string
   name: string
   description?: string
   instruction?: string
+  parameters?: ModelParameters
+  file_context_mode: ProjectFileContextMode
+  file_context_budget: number // Tokens of file text per request; 0 leaves the files out
   created_at: string
   updated_at: string
 }
@@ -33,12 +41,18 @@ export interface CreateProjectRequest {
   name: string
   description?: string
   instruction?: string
+  parameters?: ModelParameters
+  file_context_mode?: ProjectFileContextMode
+  file_context_budget?: number
 }
 
 export interface UpdateProjectRequest {
   name?: string
   description?: string
   instruction?: string
+  parameters?: ModelParameters
+  file_context_mode?: ProjectFileContextMode
+  file_context_budget?: number
 }
 
 export interface ProjectListResponse {
@@ -37,6 +37,8 @@ export type ProviderType =
   | 'groq'
   | 'gemini'
   | 'mistral'
+  | 'ollama'
+  | 'llamacpp'
   | 'custom'
 
 export interface CreateProviderRequest {
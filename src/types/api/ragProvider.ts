@@ -34,6 +34,7 @@ export type RAGProviderType =
   | 'lightrag'
   | 'ragstack'
   | 'chroma'
+  | 'qdrant'
   | 'weaviate'
   | 'pinecone'
   | 'custom'
@@ -31,6 +31,16 @@ export interface UserServices {
   password?: any
 }
 
+/**
+ * Monthly token and request limits of a group; unset limits are unlimited
+ */
+export interface UserGroupQuotas {
+  monthly_tokens?: number | null // Shared by all members
+  monthly_requests?: number | null
+  user_monthly_tokens?: number | null // For each member
+  user_monthly_requests?: number | null
+}
+
 /**
  * User group interface with array-based permissions
  */
@@ -40,6 +50,7 @@ export interface UserGroup {
   description?: string
   permissions: PermissionKey[] // Array of permission strings
   provider_ids: string[] // Array of model provider IDs assigned to this group
+  quotas?: UserGroupQuotas | null
   is_protected: boolean // Whether this group is protected (admin/user groups)
   is_active: boolean
   created_at: string
@@ -54,6 +65,7 @@ export interface CreateUserGroupRequest {
   description?: string
   permissions: PermissionKey[]
   provider_ids?: string[]
+  quotas?: UserGroupQuotas
 }
 
 export interface UpdateUserGroupRequest {
@@ -63,6 +75,7 @@ export interface UpdateUserGroupRequest {
   permissions?: PermissionKey[]
   provider_ids?: string[]
   is_active?: boolean
+  quotas?: UserGroupQuotas
 }
 
 /**